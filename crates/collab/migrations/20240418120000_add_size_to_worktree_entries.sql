@@ -0,0 +1 @@
+ALTER TABLE worktree_entries ADD COLUMN size INT8 NOT NULL DEFAULT 0;
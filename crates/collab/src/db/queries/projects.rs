@@ -306,6 +306,7 @@ impl Database {
                         git_status: ActiveValue::set(entry.git_status.map(|status| status as i64)),
                         is_deleted: ActiveValue::set(false),
                         scan_id: ActiveValue::set(update.scan_id as i64),
+                        size: ActiveValue::set(entry.size as i64),
                     }
                 }))
                 .on_conflict(
@@ -324,6 +325,7 @@ impl Database {
                         worktree_entry::Column::IsIgnored,
                         worktree_entry::Column::GitStatus,
                         worktree_entry::Column::ScanId,
+                        worktree_entry::Column::Size,
                     ])
                     .to_owned(),
                 )
@@ -703,6 +705,7 @@ impl Database {
                         is_ignored: db_entry.is_ignored,
                         is_external: db_entry.is_external,
                         git_status: db_entry.git_status.map(|status| status as i32),
+                        size: db_entry.size as u64,
                     });
                 }
             }
@@ -4865,6 +4865,7 @@ async fn test_project_search(
 
     // Perform a search as the guest.
     let mut results = HashMap::default();
+    let mut files_matched_filters = None;
     let mut search_rx = project_b.update(cx_b, |project, cx| {
         project.search(
             SearchQuery::text("world", false, false, false, Vec::new(), Vec::new()).unwrap(),
@@ -4876,11 +4877,18 @@ async fn test_project_search(
             SearchResult::Buffer { buffer, ranges } => {
                 results.entry(buffer).or_insert(ranges);
             }
+            SearchResult::Skipped { .. } => {}
+            SearchResult::FilesMatchedFilters { count } => files_matched_filters = Some(count),
             SearchResult::LimitReached => {
                 panic!("Unexpectedly reached search limit in tests. If you do want to assert limit-reached, change this panic call.")
             }
         };
     }
+    assert_eq!(
+        files_matched_filters,
+        Some(5),
+        "The host should report how many files it searched"
+    );
 
     let mut ranges_by_path = results
         .into_iter()
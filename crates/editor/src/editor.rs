@@ -427,6 +427,10 @@ pub struct Editor {
     highlighted_rows: HashMap<TypeId, Vec<(usize, Range<Anchor>, Hsla)>>,
     background_highlights: TreeMap<TypeId, BackgroundHighlight>,
     scrollbar_marker_state: ScrollbarMarkerState,
+    scrollbar_search_highlights: Option<TypeId>,
+    scrollbar_active_search_highlight: Option<TypeId>,
+    show_worktree_root_in_excerpt_headers: bool,
+    show_absolute_paths_in_excerpt_headers: bool,
     nav_history: Option<ItemNavHistory>,
     context_menu: RwLock<Option<ContextMenu>>,
     mouse_context_menu: Option<MouseContextMenu>,
@@ -457,6 +461,7 @@ pub struct Editor {
     show_inline_completions: bool,
     inlay_hint_cache: InlayHintCache,
     next_inlay_id: usize,
+    inline_markers: Vec<InlayId>,
     _subscriptions: Vec<Subscription>,
     pixel_position_of_newest_cursor: Option<gpui::Point<Pixels>>,
     gutter_width: Pixels,
@@ -1461,6 +1466,10 @@ impl Editor {
             highlighted_rows: HashMap::default(),
             background_highlights: Default::default(),
             scrollbar_marker_state: ScrollbarMarkerState::default(),
+            scrollbar_search_highlights: None,
+            scrollbar_active_search_highlight: None,
+            show_worktree_root_in_excerpt_headers: false,
+            show_absolute_paths_in_excerpt_headers: false,
             nav_history: None,
             context_menu: RwLock::new(None),
             mouse_context_menu: None,
@@ -1469,6 +1478,7 @@ impl Editor {
             next_completion_id: 0,
             completion_documentation_pre_resolve_debounce: DebouncedDelay::new(),
             next_inlay_id: 0,
+            inline_markers: Vec::new(),
             available_code_actions: Default::default(),
             code_actions_task: Default::default(),
             document_highlights_task: Default::default(),
@@ -1701,6 +1711,8 @@ impl Editor {
         self.collaboration_hub = Some(hub);
     }
 
+    /// Replaces the context menu with the one the given closure builds for the clicked point,
+    /// or with the default menu where it returns `None`.
     pub fn set_custom_context_menu(
         &mut self,
         f: impl 'static
@@ -3339,6 +3351,25 @@ impl Editor {
         cx.notify();
     }
 
+    /// Shows the given text inline at each position, such as to mark where text is left out,
+    /// in place of the markers shown before.
+    pub fn set_inline_markers(
+        &mut self,
+        positions: impl IntoIterator<Item = Anchor>,
+        text: &str,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let to_insert = positions
+            .into_iter()
+            .map(|position| Inlay::suggestion(post_inc(&mut self.next_inlay_id), position, text))
+            .collect::<Vec<_>>();
+        let to_remove = mem::replace(
+            &mut self.inline_markers,
+            to_insert.iter().map(|inlay| inlay.id).collect(),
+        );
+        self.splice_inlays(to_remove, to_insert, cx);
+    }
+
     fn trigger_on_type_formatting(
         &self,
         input: String,
@@ -8935,6 +8966,28 @@ impl Editor {
         cx.notify();
     }
 
+    /// Prefixes excerpt header paths with their worktree root name, even when the editor's
+    /// project has a single worktree. Useful when excerpts come from several projects.
+    pub fn set_show_worktree_root_in_excerpt_headers(
+        &mut self,
+        show_worktree_root: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.show_worktree_root_in_excerpt_headers = show_worktree_root;
+        cx.notify();
+    }
+
+    /// Shows the absolute paths of local files in excerpt headers, instead of their
+    /// worktree-relative paths.
+    pub fn set_show_absolute_paths_in_excerpt_headers(
+        &mut self,
+        show_absolute_paths: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.show_absolute_paths_in_excerpt_headers = show_absolute_paths;
+        cx.notify();
+    }
+
     pub fn reveal_in_finder(&mut self, _: &RevealInFinder, cx: &mut ViewContext<Self>) {
         if let Some(buffer) = self.buffer().read(cx).as_singleton() {
             if let Some(file) = buffer.read(cx).file().and_then(|f| f.as_local()) {
@@ -9318,6 +9371,26 @@ impl Editor {
             })
     }
 
+    /// Shows the background highlights of type `T` as search result markers in the scrollbar,
+    /// using their highlight color. Unlike buffer search markers, these are shown for multibuffers too.
+    pub fn show_search_highlights_in_scrollbar<T: 'static>(&mut self) {
+        self.scrollbar_search_highlights = Some(TypeId::of::<T>());
+        self.scrollbar_marker_state.dirty = true;
+    }
+
+    /// Shows the background highlights of type `T` as the active search result marker in the
+    /// scrollbar, painted over the other search markers in a stronger color.
+    pub fn show_active_search_highlight_in_scrollbar<T: 'static>(&mut self) {
+        self.scrollbar_active_search_highlight = Some(TypeId::of::<T>());
+        self.scrollbar_marker_state.dirty = true;
+    }
+
+    fn has_scrollbar_search_highlights(&self) -> bool {
+        self.scrollbar_search_highlights
+            .and_then(|type_id| self.background_highlights.get(&type_id))
+            .map_or(false, |(_, highlights)| !highlights.is_empty())
+    }
+
     pub fn has_background_highlights<T: 'static>(&self) -> bool {
         self.background_highlights
             .get(&TypeId::of::<T>())
@@ -1058,6 +1058,9 @@ impl EditorElement {
                     // Buffer Search Results
                     (is_singleton && scrollbar_settings.search_results && editor.has_background_highlights::<BufferSearchHighlights>())
                     ||
+                    // Search results highlighted by the editor's owner, e.g. project search
+                    (scrollbar_settings.search_results && editor.has_scrollbar_search_highlights())
+                    ||
                     // Selected Symbol Occurrences
                     (is_singleton && scrollbar_settings.selected_symbol && (editor.has_background_highlights::<DocumentHighlightRead>() || editor.has_background_highlights::<DocumentHighlightWrite>()))
                     ||
@@ -1628,13 +1631,14 @@ impl EditorElement {
                     id,
                     ..
                 } => {
-                    let include_root = self
-                        .editor
-                        .read(cx)
-                        .project
-                        .as_ref()
-                        .map(|project| project.read(cx).visible_worktrees(cx).count() > 1)
-                        .unwrap_or_default();
+                    let editor = self.editor.read(cx);
+                    let include_root = editor.show_worktree_root_in_excerpt_headers
+                        || editor
+                            .project
+                            .as_ref()
+                            .map(|project| project.read(cx).visible_worktrees(cx).count() > 1)
+                            .unwrap_or_default();
+                    let show_absolute_path = editor.show_absolute_paths_in_excerpt_headers;
 
                     #[derive(Clone)]
                     struct JumpData {
@@ -1680,7 +1684,15 @@ impl EditorElement {
                     });
 
                     let element = if *starts_new_buffer {
-                        let path = buffer.resolve_file_path(cx, include_root);
+                        let path = if show_absolute_path {
+                            buffer
+                                .file()
+                                .and_then(|file| file.as_local())
+                                .map(|file| file.abs_path(cx))
+                                .or_else(|| buffer.resolve_file_path(cx, include_root))
+                        } else {
+                            buffer.resolve_file_path(cx, include_root)
+                        };
                         let mut filename = None;
                         let mut parent_path = None;
                         // Can't use .and_then() because `.file_name()` and `.parent()` return references :(
@@ -2695,7 +2707,8 @@ impl EditorElement {
         cx: &mut WindowContext,
     ) {
         self.editor.update(cx, |editor, cx| {
-            if !editor.is_singleton(cx)
+            let is_singleton = editor.is_singleton(cx);
+            if !(is_singleton || editor.scrollbar_search_highlights.is_some())
                 || !editor
                     .scrollbar_marker_state
                     .should_refresh(scrollbar_layout.hitbox.size)
@@ -2705,6 +2718,8 @@ impl EditorElement {
 
             let scrollbar_layout = scrollbar_layout.clone();
             let background_highlights = editor.background_highlights.clone();
+            let scrollbar_search_highlights = editor.scrollbar_search_highlights;
+            let scrollbar_active_search_highlight = editor.scrollbar_active_search_highlight;
             let snapshot = layout.position_map.snapshot.clone();
             let theme = cx.theme().clone();
             let scrollbar_settings = EditorSettings::get_global(cx).scrollbar;
@@ -2719,7 +2734,7 @@ impl EditorElement {
                         .spawn(async move {
                             let mut marker_quads = Vec::new();
 
-                            if scrollbar_settings.git_diff {
+                            if is_singleton && scrollbar_settings.git_diff {
                                 let marker_row_ranges = snapshot
                                     .buffer_snapshot
                                     .git_diff_hunks_in_range(0..max_row)
@@ -2753,19 +2768,28 @@ impl EditorElement {
                                 );
                             }
 
-                            for (background_highlight_id, (_, background_ranges)) in
+                            for (background_highlight_id, (color_fetcher, background_ranges)) in
                                 background_highlights.iter()
                             {
-                                let is_search_highlights = *background_highlight_id
-                                    == TypeId::of::<BufferSearchHighlights>();
-                                let is_symbol_occurrences = *background_highlight_id
-                                    == TypeId::of::<DocumentHighlightRead>()
-                                    || *background_highlight_id
-                                        == TypeId::of::<DocumentHighlightWrite>();
-                                if (is_search_highlights && scrollbar_settings.search_results)
+                                let is_owner_search_highlights =
+                                    Some(*background_highlight_id) == scrollbar_search_highlights;
+                                let is_search_highlights = is_singleton
+                                    && *background_highlight_id
+                                        == TypeId::of::<BufferSearchHighlights>();
+                                let is_symbol_occurrences = is_singleton
+                                    && (*background_highlight_id
+                                        == TypeId::of::<DocumentHighlightRead>()
+                                        || *background_highlight_id
+                                            == TypeId::of::<DocumentHighlightWrite>());
+                                if ((is_search_highlights || is_owner_search_highlights)
+                                    && scrollbar_settings.search_results)
                                     || (is_symbol_occurrences && scrollbar_settings.selected_symbol)
                                 {
-                                    let mut color = theme.status().info;
+                                    let mut color = if is_owner_search_highlights {
+                                        color_fetcher(theme.colors())
+                                    } else {
+                                        theme.status().info
+                                    };
                                     if is_symbol_occurrences {
                                         color.fade_out(0.5);
                                     }
@@ -2790,7 +2814,30 @@ impl EditorElement {
                                 }
                             }
 
-                            if scrollbar_settings.diagnostics {
+                            // Painted last so that it stays visible over the other search markers.
+                            if let Some((_, active_ranges)) = scrollbar_active_search_highlight
+                                .filter(|_| scrollbar_settings.search_results)
+                                .and_then(|type_id| background_highlights.get(&type_id))
+                            {
+                                let color = theme.status().info;
+                                let marker_row_ranges = active_ranges.iter().map(|range| {
+                                    let display_start =
+                                        range.start.to_display_point(&snapshot.display_snapshot);
+                                    let display_end =
+                                        range.end.to_display_point(&snapshot.display_snapshot);
+                                    ColoredRange {
+                                        start: display_start.row(),
+                                        end: display_end.row(),
+                                        color,
+                                    }
+                                });
+                                marker_quads.extend(
+                                    scrollbar_layout
+                                        .marker_quads_for_ranges(marker_row_ranges, Some(1)),
+                                );
+                            }
+
+                            if is_singleton && scrollbar_settings.diagnostics {
                                 let max_point =
                                     snapshot.display_snapshot.buffer_snapshot.max_point();
 
@@ -51,13 +51,13 @@ pub fn deploy_context_menu(
         return;
     }
 
-    let context_menu = if let Some(custom) = editor.custom_context_menu.take() {
+    let custom_menu = editor.custom_context_menu.take().and_then(|custom| {
         let menu = custom(editor, point, cx);
         editor.custom_context_menu = Some(custom);
-        if menu.is_none() {
-            return;
-        }
-        menu.unwrap()
+        menu
+    });
+    let context_menu = if let Some(menu) = custom_menu {
+        menu
     } else {
         // Don't show the context menu if there isn't a project associated with this editor
         if editor.project.is_none() {
@@ -113,6 +113,7 @@ pub struct Metadata {
     pub mtime: SystemTime,
     pub is_symlink: bool,
     pub is_dir: bool,
+    pub len: u64,
 }
 
 #[derive(Default)]
@@ -359,6 +360,7 @@ impl Fs for RealFs {
             mtime: metadata.modified().unwrap(),
             is_symlink,
             is_dir: metadata.file_type().is_dir(),
+            len: metadata.len(),
         }))
     }
 
@@ -1376,17 +1378,23 @@ impl Fs for FakeFs {
 
             let entry = entry.lock();
             Ok(Some(match &*entry {
-                FakeFsEntry::File { inode, mtime, .. } => Metadata {
+                FakeFsEntry::File {
+                    inode,
+                    mtime,
+                    content,
+                } => Metadata {
                     inode: *inode,
                     mtime: *mtime,
                     is_dir: false,
                     is_symlink,
+                    len: content.len() as u64,
                 },
                 FakeFsEntry::Dir { inode, mtime, .. } => Metadata {
                     inode: *inode,
                     mtime: *mtime,
                     is_dir: true,
                     is_symlink,
+                    len: 0,
                 },
                 FakeFsEntry::Symlink { .. } => unreachable!(),
             }))
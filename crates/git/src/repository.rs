@@ -55,6 +55,9 @@ pub trait GitRepository: Send {
     /// no need to consider the working directory file if the mtime matches.
     fn status(&self, path: &RepoPath, mtime: SystemTime) -> Option<GitFileStatus>;
 
+    /// Get the paths of all of the files in the index, which are the files git tracks.
+    fn tracked_paths(&self) -> Vec<RepoPath>;
+
     fn branches(&self) -> Result<Vec<Branch>>;
     fn change_branch(&self, _: &str) -> Result<()>;
     fn create_branch(&self, _: &str) -> Result<()>;
@@ -190,6 +193,17 @@ impl GitRepository for RealGitRepository {
         status
     }
 
+    fn tracked_paths(&self) -> Vec<RepoPath> {
+        let Some(index) = self.repository.index().log_err() else {
+            return Vec::new();
+        };
+        index
+            .iter()
+            .filter_map(|entry| PathBuf::try_from_bytes(&entry.path).log_err())
+            .map(RepoPath)
+            .collect()
+    }
+
     fn branches(&self) -> Result<Vec<Branch>> {
         let local_branches = self.repository.branches(Some(BranchType::Local))?;
         let valid_branches = local_branches
@@ -342,6 +356,15 @@ impl GitRepository for FakeGitRepository {
         state.worktree_statuses.get(path).cloned()
     }
 
+    fn tracked_paths(&self) -> Vec<RepoPath> {
+        let state = self.state.lock();
+        state
+            .index_contents
+            .keys()
+            .map(|path| RepoPath(path.clone()))
+            .collect()
+    }
+
     fn branches(&self) -> Result<Vec<Branch>> {
         Ok(vec![])
     }
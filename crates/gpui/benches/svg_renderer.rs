@@ -0,0 +1,38 @@
+use std::{borrow::Cow, sync::Arc};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use gpui::{size, AssetSource, RenderSvgParams, Result, SharedString, SvgQuality, SvgRenderer};
+
+const ICON: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" width="16" height="16" viewBox="0 0 16 16">
+    <circle cx="8" cy="8" r="6" fill="none" stroke="black" stroke-width="1.5"/>
+    <path d="M5 8h6M8 5v6" stroke="black" stroke-width="1.5" stroke-linecap="round"/>
+</svg>"#;
+
+struct Icons;
+
+impl AssetSource for Icons {
+    fn load(&self, _path: &str) -> Result<Cow<'static, [u8]>> {
+        Ok(Cow::Borrowed(ICON.as_bytes()))
+    }
+
+    fn list(&self, _path: &str) -> Result<Vec<SharedString>> {
+        Ok(Vec::new())
+    }
+}
+
+fn svg_renderer_benchmarks(c: &mut Criterion) {
+    let renderer = SvgRenderer::new(Arc::new(Icons));
+    let params = RenderSvgParams {
+        path: "icons/plus.svg".into(),
+        size: size(32.into(), 32.into()),
+        quality: SvgQuality::default(),
+        opacity: 1.,
+    };
+
+    c.bench_function("render same icon", |b| {
+        b.iter(|| renderer.render(black_box(&params)).unwrap())
+    });
+}
+
+criterion_group!(benches, svg_renderer_benchmarks);
+criterion_main!(benches);
@@ -5,7 +5,7 @@ use std::sync::Arc;
 use crate::{
     point, px, size, AbsoluteLength, Asset, Bounds, DefiniteLength, DevicePixels, Element, Hitbox,
     ImageData, InteractiveElement, Interactivity, IntoElement, LayoutId, Length, Pixels, SharedUri,
-    Size, StyleRefinement, Styled, SvgSize, UriOrPath, WindowContext,
+    Size, StyleRefinement, Styled, SvgError, SvgSize, UriOrPath, WindowContext,
 };
 use futures::{AsyncReadExt, Future};
 use image::{ImageBuffer, ImageError};
@@ -409,7 +409,7 @@ pub enum ImageCacheError {
     Image(Arc<ImageError>),
     /// An error that occurred while processing an SVG.
     #[error("svg error: {0}")]
-    Usvg(Arc<usvg::Error>),
+    Svg(Arc<SvgError>),
 }
 
 impl From<std::io::Error> for ImageCacheError {
@@ -424,8 +424,8 @@ impl From<ImageError> for ImageCacheError {
     }
 }
 
-impl From<usvg::Error> for ImageCacheError {
-    fn from(error: usvg::Error) -> Self {
-        Self::Usvg(Arc::new(error))
+impl From<SvgError> for ImageCacheError {
+    fn from(error: SvgError) -> Self {
+        Self::Svg(Arc::new(error))
     }
 }
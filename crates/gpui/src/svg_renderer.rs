@@ -1,69 +1,2435 @@
-use crate::{AssetSource, DevicePixels, IsZero, Result, SharedString, Size};
-use anyhow::anyhow;
+use crate::{
+    AssetSource, BackgroundExecutor, Bounds, DevicePixels, IsZero, Result, Rgba, SharedString,
+    Size, Task,
+};
+use collections::{FxHashMap, VecDeque};
+use parking_lot::Mutex;
 use resvg::tiny_skia::Pixmap;
-use std::{hash::Hash, sync::Arc};
+use std::{
+    borrow::Cow,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+use thiserror::Error;
 
-#[derive(Clone, PartialEq, Hash, Eq)]
-pub(crate) struct RenderSvgParams {
-    pub(crate) path: SharedString,
-    pub(crate) size: Size<DevicePixels>,
+/// The parameters used to render an SVG asset into an alpha mask.
+#[derive(Clone)]
+pub struct RenderSvgParams {
+    /// The path of the SVG in the asset source.
+    pub path: SharedString,
+    /// The size to render the SVG at.
+    pub size: Size<DevicePixels>,
+    /// The anti-aliasing quality to render the SVG with.
+    pub quality: SvgQuality,
+    /// A multiplier applied to the mask's alpha, clamped to `0..=1`. Useful for rendering
+    /// disabled icons without a separate asset. NaN is treated as fully opaque.
+    pub opacity: f32,
+}
+
+impl PartialEq for RenderSvgParams {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+            && self.size == other.size
+            && self.quality == other.quality
+            && sanitize_opacity(self.opacity).to_bits() == sanitize_opacity(other.opacity).to_bits()
+    }
+}
+
+impl Eq for RenderSvgParams {}
+
+impl Hash for RenderSvgParams {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.path.hash(state);
+        self.size.hash(state);
+        self.quality.hash(state);
+        sanitize_opacity(self.opacity).to_bits().hash(state);
+    }
+}
+
+/// The anti-aliasing quality used when rasterizing an SVG.
+///
+/// Anti-aliased edges look smooth at larger sizes, but can make small icons look muddy.
+/// Crisp edges keep small icons sharp, at the cost of jagged curves and diagonals.
+/// Elements that set their own `shape-rendering` property are unaffected.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SvgQuality {
+    /// Render shapes with anti-aliasing.
+    #[default]
+    Smooth,
+    /// Render shapes without anti-aliasing.
+    Crisp,
+}
+
+impl SvgQuality {
+    fn shape_rendering(self) -> usvg::ShapeRendering {
+        match self {
+            SvgQuality::Smooth => usvg::ShapeRendering::GeometricPrecision,
+            SvgQuality::Crisp => usvg::ShapeRendering::CrispEdges,
+        }
+    }
 }
 
+/// Rasterizes SVGs loaded from an [`AssetSource`].
+///
+/// gpui builds usvg without its `text` feature, so no font database is ever loaded and
+/// `<text>` elements are skipped. Icons that need text should convert it to paths.
+///
+/// Internal `<style>` sheets are always applied. They support CSS 2.1 selectors: type,
+/// class, id, universal and attribute selectors, descendant and child combinators, and
+/// `:first-child`. At-rules like `@media` are skipped, and keywords must be lowercase.
 #[derive(Clone)]
-pub(crate) struct SvgRenderer {
+pub struct SvgRenderer {
     asset_source: Arc<dyn AssetSource>,
+    trees: Arc<Mutex<FxHashMap<(SharedString, SvgQuality), Arc<ParsedSvg>>>>,
+    pixmaps: PixmapPool,
+    masks: MaskCache,
+    cache_stats: Arc<Mutex<SvgCacheStats>>,
+    parse_fallback: bool,
+    stroke_outlines: bool,
+}
+
+/// Counters describing how effective one of an [`SvgRenderer`]'s caches has been.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SvgCacheStats {
+    /// The number of lookups that found a cached entry.
+    pub hits: u64,
+    /// The number of lookups that had to load, parse or render the SVG.
+    pub misses: u64,
+    /// The number of entries that were removed from the cache.
+    pub evictions: u64,
+}
+
+/// An error that can occur when rendering an SVG.
+#[derive(Debug, Error)]
+pub enum SvgError {
+    /// The SVG couldn't be loaded from the asset source.
+    #[error("failed to load svg asset: {0}")]
+    AssetLoad(anyhow::Error),
+    /// The SVG data couldn't be parsed or rasterized.
+    #[error("failed to parse svg: {0}")]
+    Parse(#[from] usvg::Error),
+    /// The SVG was requested at a zero size.
+    #[error("can't render at a zero size")]
+    ZeroSize,
+    /// The SVG's own size is zero, negative or not finite, so it can't be scaled.
+    #[error("svg has a zero or invalid size")]
+    InvalidSourceSize,
+    /// The requested region doesn't overlap the SVG's view box.
+    #[error("region {0:?} lies outside of the svg")]
+    RegionOutOfBounds(Bounds<f32>),
+}
+
+/// Options for rendering an SVG into RGBA pixels.
+#[derive(Clone, Copy, Debug)]
+pub struct RenderRgbaOptions {
+    /// The color to fill the pixmap with before rendering. When `None`, the
+    /// background is left transparent.
+    pub background: Option<Rgba>,
+    /// The anti-aliasing quality to render the SVG with.
+    pub quality: SvgQuality,
+    /// Whether to un-premultiply the rendered pixels, for consumers that expect straight
+    /// alpha. The pixmap's bytes should then be read with [`Pixmap::data`] or [`Pixmap::take`],
+    /// since its pixel accessors assume premultiplied alpha.
+    pub straight_alpha: bool,
+    /// A multiplier applied to every pixel, clamped to `0..=1`. This also fades the
+    /// background, if there is one.
+    pub opacity: f32,
+    /// How to flip and rotate the SVG, e.g. to mirror directional icons in right-to-left
+    /// layouts without shipping mirrored assets.
+    pub orientation: SvgOrientation,
+}
+
+impl Default for RenderRgbaOptions {
+    fn default() -> Self {
+        Self {
+            background: None,
+            quality: SvgQuality::default(),
+            straight_alpha: false,
+            opacity: 1.,
+            orientation: SvgOrientation::default(),
+        }
+    }
+}
+
+/// Flips and a rotation applied to an SVG as it's rendered. The SVG is flipped first, and
+/// then rotated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SvgOrientation {
+    /// Whether to mirror the SVG horizontally.
+    pub flip_x: bool,
+    /// Whether to mirror the SVG vertically.
+    pub flip_y: bool,
+    /// How far to rotate the SVG clockwise.
+    pub rotation: SvgRotation,
+}
+
+/// A clockwise rotation by a multiple of 90 degrees.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SvgRotation {
+    /// Don't rotate the SVG.
+    #[default]
+    None,
+    /// Rotate the SVG by 90 degrees clockwise.
+    Clockwise90,
+    /// Rotate the SVG by 180 degrees.
+    Clockwise180,
+    /// Rotate the SVG by 270 degrees clockwise, or 90 degrees counterclockwise.
+    Clockwise270,
+}
+
+impl SvgOrientation {
+    /// Returns the size of an SVG rendered at the given size once it's been rotated, which
+    /// swaps its dimensions for quarter turns.
+    fn rotated_size(&self, size: Size<DevicePixels>) -> Size<DevicePixels> {
+        match self.rotation {
+            SvgRotation::None | SvgRotation::Clockwise180 => size,
+            SvgRotation::Clockwise90 | SvgRotation::Clockwise270 => {
+                crate::size(size.height, size.width)
+            }
+        }
+    }
+
+    /// Returns the transform that maps pixels in an unrotated render of the given size to
+    /// pixels in the flipped and rotated render.
+    fn transform(&self, size: Size<DevicePixels>) -> resvg::tiny_skia::Transform {
+        let width = size.width.0 as f32;
+        let height = size.height.0 as f32;
+        let (scale_x, translate_x) = if self.flip_x { (-1., width) } else { (1., 0.) };
+        let (scale_y, translate_y) = if self.flip_y { (-1., height) } else { (1., 0.) };
+        let flip = resvg::tiny_skia::Transform::from_row(
+            scale_x,
+            0.,
+            0.,
+            scale_y,
+            translate_x,
+            translate_y,
+        );
+        let rotation = match self.rotation {
+            SvgRotation::None => resvg::tiny_skia::Transform::identity(),
+            SvgRotation::Clockwise90 => {
+                resvg::tiny_skia::Transform::from_row(0., 1., -1., 0., height, 0.)
+            }
+            SvgRotation::Clockwise180 => {
+                resvg::tiny_skia::Transform::from_row(-1., 0., 0., -1., width, height)
+            }
+            SvgRotation::Clockwise270 => {
+                resvg::tiny_skia::Transform::from_row(0., -1., 1., 0., 0., width)
+            }
+        };
+        flip.post_concat(rotation)
+    }
 }
 
+/// The size at which an SVG should be rendered.
 pub enum SvgSize {
+    /// Render at an exact size, fitting the SVG within it while preserving its aspect ratio.
+    /// Like the scaled size, this is the size before any [`SvgOrientation::rotation`].
     Size(Size<DevicePixels>),
+    /// Render at the SVG's intrinsic size, multiplied by the given factor. SVGs whose root
+    /// element is sized in percentages use the size of their `viewBox` instead, and SVGs that
+    /// only set one of `width` or `height` derive the other from the `viewBox`.
     ScaleFactor(f32),
 }
 
 impl SvgRenderer {
+    /// Creates a renderer that loads SVGs from the given asset source.
     pub fn new(asset_source: Arc<dyn AssetSource>) -> Self {
-        Self { asset_source }
+        Self {
+            asset_source,
+            trees: Default::default(),
+            pixmaps: Default::default(),
+            masks: Default::default(),
+            cache_stats: Default::default(),
+            parse_fallback: false,
+            stroke_outlines: false,
+        }
+    }
+
+    /// When enabled, SVGs that fail to parse are rendered as a bordered placeholder box
+    /// instead of returning an error, so that broken icons remain visible.
+    pub fn with_parse_fallback(mut self, parse_fallback: bool) -> Self {
+        self.parse_fallback = parse_fallback;
+        self
+    }
+
+    /// Caches the alpha masks rendered by [`Self::render`] and [`Self::render_into`], keeping
+    /// at most `max_entries` masks totalling at most `max_bytes`. The least recently used masks
+    /// are evicted to make room, and masks larger than `max_bytes` aren't cached at all.
+    ///
+    /// Rendered masks aren't cached by default, since gpui's sprite atlas already keeps the
+    /// masks it paints.
+    pub fn with_mask_cache(mut self, max_bytes: usize, max_entries: usize) -> Self {
+        self.masks = MaskCache::new(max_bytes, max_entries);
+        self
     }
 
-    pub fn render(&self, params: &RenderSvgParams) -> Result<Vec<u8>> {
+    /// When enabled, strokes in alpha masks are converted to filled outlines before they're
+    /// rasterized, which keeps thin lines crisper than tiny-skia's hairline stroking. SVGs
+    /// that use clip paths, masks, filters, blend modes, images, or gradient and pattern
+    /// paints are rendered as usual.
+    pub fn with_stroke_outlines(mut self, stroke_outlines: bool) -> Self {
+        self.stroke_outlines = stroke_outlines;
+        self
+    }
+
+    /// Renders the SVG at the given path into an alpha mask, returning the mask's dimensions
+    /// along with its bytes so that it can be uploaded with a matching stride.
+    pub fn render(
+        &self,
+        params: &RenderSvgParams,
+    ) -> Result<(Size<DevicePixels>, Vec<u8>), SvgError> {
+        let mut alpha_mask = Vec::new();
+        let size = self.render_into(params, &mut alpha_mask)?;
+        Ok((size, alpha_mask))
+    }
+
+    /// Renders the SVG at the given path into an alpha mask, writing it into `out` instead of
+    /// allocating a new buffer. `out` is resized to fit the mask, so callers that render often
+    /// can reuse a single buffer. Returns the mask's dimensions, which determine its stride.
+    pub fn render_into(
+        &self,
+        params: &RenderSvgParams,
+        out: &mut Vec<u8>,
+    ) -> Result<Size<DevicePixels>, SvgError> {
         if params.size.is_zero() {
-            return Err(anyhow!("can't render at a zero size"));
+            return Err(SvgError::ZeroSize);
+        }
+        if let Some(size) = self.masks.get(params, out) {
+            return Ok(size);
         }
 
-        // Load the tree.
-        let bytes = self.asset_source.load(&params.path)?;
+        let result = self.tree(&params.path, params.quality).and_then(|svg| {
+            self.render_alpha_mask(&svg.tree, params.size, out)
+                .map_err(|error| {
+                    log::warn!("failed to rasterize svg {:?}: {}", params.path, error);
+                    SvgError::Parse(error)
+                })
+        });
+        let size = match result {
+            Err(SvgError::Parse(_)) if self.parse_fallback => {
+                placeholder_alpha_mask(params.size, out);
+                apply_opacity(out, params.opacity);
+                return Ok(params.size);
+            }
+            result => result?,
+        };
+        apply_opacity(out, params.opacity);
+        self.masks.insert(params, size, out);
+        Ok(size)
+    }
+
+    /// Renders the SVG at the given path into an alpha mask at a logical size, scaled by the
+    /// display's device pixel ratio. Each dimension is rounded to the nearest device pixel.
+    pub fn render_scaled(
+        &self,
+        path: &SharedString,
+        logical_size: Size<f32>,
+        scale: f32,
+    ) -> Result<(Size<DevicePixels>, Vec<u8>), SvgError> {
+        let size = logical_size.map(|dimension| DevicePixels((dimension * scale).round() as i32));
+        self.render(&RenderSvgParams {
+            path: path.clone(),
+            size,
+            quality: SvgQuality::default(),
+            opacity: 1.,
+        })
+    }
+
+    /// Renders the SVG at the given path into an alpha mask on a background thread, so that
+    /// rasterizing large SVGs doesn't stall the current frame. Parsed trees are shared with
+    /// this renderer, so later renders of the same path can reuse them.
+    pub fn render_async(
+        &self,
+        params: RenderSvgParams,
+        executor: &BackgroundExecutor,
+    ) -> Task<Result<(Size<DevicePixels>, Vec<u8>), SvgError>> {
+        let this = self.clone();
+        executor.spawn(async move { this.render(&params) })
+    }
+
+    /// Loads and renders the given icons ahead of time, so that the first frames that show them
+    /// don't have to. Parsed SVGs are always cached, and rendered masks are cached too if
+    /// [`Self::with_mask_cache`] enabled it. Icons that fail to load or render are skipped.
+    pub fn prewarm(&self, entries: &[(SharedString, Size<DevicePixels>)]) {
+        for (path, size) in entries {
+            if self.masks.is_enabled() {
+                self.render(&RenderSvgParams {
+                    path: path.clone(),
+                    size: *size,
+                    quality: SvgQuality::default(),
+                    opacity: 1.,
+                })
+                .ok();
+            } else {
+                self.tree(path, SvgQuality::default()).ok();
+            }
+        }
+    }
+
+    /// Like [`Self::prewarm`], but on a background thread, so that it doesn't delay startup.
+    pub fn prewarm_async(
+        &self,
+        entries: Vec<(SharedString, Size<DevicePixels>)>,
+        executor: &BackgroundExecutor,
+    ) -> Task<()> {
+        let this = self.clone();
+        executor.spawn(async move { this.prewarm(&entries) })
+    }
+
+    /// Renders the SVG at the given path at each of the given sizes, parsing it only once.
+    /// The alpha masks are returned in the same order as `sizes`.
+    pub fn render_many(
+        &self,
+        path: &SharedString,
+        sizes: &[Size<DevicePixels>],
+    ) -> Result<Vec<Vec<u8>>, SvgError> {
+        if sizes.iter().any(|size| size.is_zero()) {
+            return Err(SvgError::ZeroSize);
+        }
+
+        let svg = self.tree(path, SvgQuality::default())?;
+        sizes
+            .iter()
+            .map(|size| {
+                let mut alpha_mask = Vec::new();
+                self.render_alpha_mask(&svg.tree, *size, &mut alpha_mask)?;
+                Ok(alpha_mask)
+            })
+            .collect()
+    }
+
+    /// Renders an alpha mask from SVG data that's already in memory, without
+    /// going through the asset source.
+    pub fn render_bytes(&self, svg: &[u8], size: Size<DevicePixels>) -> Result<Vec<u8>, SvgError> {
+        if size.is_zero() {
+            return Err(SvgError::ZeroSize);
+        }
+
+        let svg = parse_tree(svg, &self.options(None, SvgQuality::default()))?;
+        let mut alpha_mask = Vec::new();
+        self.render_alpha_mask(&svg.tree, size, &mut alpha_mask)?;
+        Ok(alpha_mask)
+    }
+
+    /// Renders SVG data into a premultiplied RGBA pixmap.
+    pub fn render_pixmap(&self, bytes: &[u8], size: SvgSize) -> Result<Pixmap, SvgError> {
+        self.render_rgba(bytes, size, &RenderRgbaOptions::default())
+    }
+
+    /// Renders SVG data into an RGBA pixmap with the given options.
+    pub fn render_rgba(
+        &self,
+        bytes: &[u8],
+        size: SvgSize,
+        options: &RenderRgbaOptions,
+    ) -> Result<Pixmap, SvgError> {
+        let svg = parse_tree(bytes, &self.options(None, options.quality))?;
+        let mut pixmap = render_tree(&svg, size, options)?;
+        apply_rgba_options(&mut pixmap, options);
+        Ok(pixmap)
+    }
+
+    /// Renders a region of SVG data into an RGBA pixmap of the given size. The
+    /// region is expressed in SVG user units, which makes it possible to rasterize a single
+    /// sprite out of a sprite sheet. The size is that of the region before it's rotated.
+    pub fn render_region(
+        &self,
+        bytes: &[u8],
+        region: Bounds<f32>,
+        size: Size<DevicePixels>,
+        options: &RenderRgbaOptions,
+    ) -> Result<Pixmap, SvgError> {
+        if size.is_zero() {
+            return Err(SvgError::ZeroSize);
+        }
+
+        let tree = parse_tree(bytes, &self.options(None, options.quality))?.tree;
+        let view_box = tree.view_box().rect;
+        let region_right = region.origin.x + region.size.width;
+        let region_bottom = region.origin.y + region.size.height;
+        if !is_valid_dimension(region.size.width)
+            || !is_valid_dimension(region.size.height)
+            || region.origin.x >= view_box.right()
+            || region.origin.y >= view_box.bottom()
+            || region_right <= view_box.left()
+            || region_bottom <= view_box.top()
+        {
+            return Err(SvgError::RegionOutOfBounds(region));
+        }
+
+        let scale_x = size.width.0 as f32 / region.size.width;
+        let scale_y = size.height.0 as f32 / region.size.height;
+        let transform = resvg::tiny_skia::Transform::from_row(
+            scale_x,
+            0.,
+            0.,
+            scale_y,
+            -region.origin.x * scale_x,
+            -region.origin.y * scale_y,
+        );
+        let transform = user_space_transform(&tree, transform)?
+            .post_concat(options.orientation.transform(size));
+
+        let mut pixmap = rasterize(
+            &tree,
+            options.orientation.rotated_size(size),
+            transform,
+            options.background,
+        )?;
+        apply_rgba_options(&mut pixmap, options);
+        Ok(pixmap)
+    }
+
+    /// Returns the natural size of the SVG at the given path, in SVG user units.
+    ///
+    /// This is useful for preserving an icon's aspect ratio when choosing a size to render it at.
+    /// SVGs whose root element is sized in percentages report the size of their `viewBox`.
+    pub fn intrinsic_size(&self, path: &SharedString) -> Result<Size<f32>, SvgError> {
+        let size = self.tree(path, SvgQuality::default())?.size()?;
+        Ok(crate::size(size.width(), size.height()))
+    }
+
+    /// Forgets every parsed SVG, so that later renders load them from the asset source again.
+    ///
+    /// Parsed SVGs are cached by path, so embedders that reload assets at runtime (e.g. when
+    /// a theme's icons change on disk) must call this, or [`Self::invalidate`], afterwards.
+    pub fn clear_cache(&self) {
+        let mut trees = self.trees.lock();
+        self.cache_stats.lock().evictions += trees.len() as u64;
+        trees.clear();
+        self.masks.remove_where(|_| true);
+    }
+
+    /// Forgets the parsed SVG at the given path, so that the next render loads it from the
+    /// asset source again.
+    pub fn invalidate(&self, path: &SharedString) {
+        let mut trees = self.trees.lock();
+        let len = trees.len();
+        trees.retain(|(cached_path, _), _| cached_path != path);
+        self.cache_stats.lock().evictions += (len - trees.len()) as u64;
+        self.masks.remove_where(|params| &params.path == path);
+    }
 
-        let pixmap = self.render_pixmap(&bytes, SvgSize::Size(params.size))?;
+    /// Returns the hit, miss and eviction counts of the cache of parsed SVGs, accumulated
+    /// since the renderer was created or [`Self::reset_cache_stats`] was last called.
+    pub fn cache_stats(&self) -> SvgCacheStats {
+        *self.cache_stats.lock()
+    }
+
+    /// Returns the hit, miss and eviction counts of the cache of rendered masks enabled with
+    /// [`Self::with_mask_cache`], accumulated since the renderer was created or
+    /// [`Self::reset_cache_stats`] was last called. Masks too large to be cached count as misses.
+    pub fn mask_cache_stats(&self) -> SvgCacheStats {
+        self.masks.0.lock().stats
+    }
+
+    /// Resets the counts returned by [`Self::cache_stats`] and [`Self::mask_cache_stats`] to zero.
+    pub fn reset_cache_stats(&self) {
+        *self.cache_stats.lock() = SvgCacheStats::default();
+        self.masks.0.lock().stats = SvgCacheStats::default();
+    }
+
+    /// Renders the tree into the given alpha mask buffer, using a pooled pixmap as scratch
+    /// space. Returns the dimensions of the pixmap the mask was read from.
+    fn render_alpha_mask(
+        &self,
+        tree: &usvg::Tree,
+        size: Size<DevicePixels>,
+        alpha_mask: &mut Vec<u8>,
+    ) -> Result<Size<DevicePixels>, usvg::Error> {
+        let transform = fit_transform(tree, size)?;
+        let mut pixmap = self.pixmaps.checkout(size)?;
+        if self.stroke_outlines && supports_stroke_outlines(tree.root()) {
+            let transform = transform.pre_concat(tree.view_box().to_transform(tree.size()));
+            render_outlined(tree.root(), transform, &mut pixmap.as_mut());
+        } else {
+            resvg::render(tree, transform, &mut pixmap.as_mut());
+        }
 
         // Convert the pixmap's pixels into an alpha mask.
-        let alpha_mask = pixmap
+        let mask_size = crate::size(pixmap.width().into(), pixmap.height().into());
+        alpha_mask.clear();
+        alpha_mask.extend(pixmap.pixels().iter().map(|p| p.alpha()));
+        self.pixmaps.checkin(pixmap);
+        Ok(mask_size)
+    }
+
+    /// Loads and parses the SVG at the given path, reusing a previously parsed tree if possible.
+    fn tree(&self, path: &SharedString, quality: SvgQuality) -> Result<Arc<ParsedSvg>, SvgError> {
+        let key = (path.clone(), quality);
+        if let Some(tree) = self.trees.lock().get(&key) {
+            self.cache_stats.lock().hits += 1;
+            return Ok(tree.clone());
+        }
+        self.cache_stats.lock().misses += 1;
+
+        // Load the tree.
+        let bytes = self.asset_source.load(path).map_err(|error| {
+            log::warn!("failed to load svg {:?}: {}", path, error);
+            SvgError::AssetLoad(error)
+        })?;
+        let tree = parse_tree(&bytes, &self.options(Some(path), quality)).map_err(|error| {
+            log::warn!("failed to parse svg {:?}: {}", path, error);
+            error
+        })?;
+        let tree = Arc::new(tree);
+        self.trees.lock().insert(key, tree.clone());
+        Ok(tree)
+    }
+
+    /// Returns the options used to parse SVGs, resolving relative `<image>` hrefs through the
+    /// asset source. Hrefs are relative to the directory of the SVG at `path`, if there is one.
+    fn options(&self, path: Option<&str>, quality: SvgQuality) -> usvg::Options {
+        let asset_source = self.asset_source.clone();
+        let dir = path
+            .and_then(|path| path.rsplit_once('/'))
+            .map(|(dir, _)| dir.to_string());
+
+        usvg::Options {
+            shape_rendering: quality.shape_rendering(),
+            image_href_resolver: usvg::ImageHrefResolver {
+                resolve_data: usvg::ImageHrefResolver::default_data_resolver(),
+                resolve_string: Box::new(move |href, options| {
+                    let path = match &dir {
+                        Some(dir) if !href.starts_with('/') => format!("{dir}/{href}"),
+                        _ => href.to_string(),
+                    };
+                    load_image_href(asset_source.as_ref(), &path, options)
+                }),
+            },
+            ..Default::default()
+        }
+    }
+}
+
+fn load_image_href(
+    asset_source: &dyn AssetSource,
+    path: &str,
+    options: &usvg::Options,
+) -> Option<usvg::ImageKind> {
+    let bytes = match asset_source.load(path) {
+        Ok(bytes) => bytes,
+        Err(error) => {
+            log::warn!("failed to load svg image {:?}: {}", path, error);
+            return None;
+        }
+    };
+
+    let data = Arc::new(bytes.into_owned());
+    match image::guess_format(&data) {
+        Ok(image::ImageFormat::Png) => Some(usvg::ImageKind::PNG(data)),
+        Ok(image::ImageFormat::Jpeg) => Some(usvg::ImageKind::JPEG(data)),
+        Ok(image::ImageFormat::Gif) => Some(usvg::ImageKind::GIF(data)),
+        _ => match usvg::Tree::from_data(&data, options) {
+            Ok(tree) => Some(usvg::ImageKind::SVG(tree)),
+            Err(error) => {
+                log::warn!("failed to parse svg image {:?}: {}", path, error);
+                None
+            }
+        },
+    }
+}
+
+/// A parsed SVG, along with the size it's rendered at when scaled by a factor.
+struct ParsedSvg {
+    tree: usvg::Tree,
+    /// `None` if the root element is sized in percentages, or not at all, and has no `viewBox`.
+    size: Option<usvg::Size>,
+}
+
+impl ParsedSvg {
+    fn size(&self) -> Result<usvg::Size, usvg::Error> {
+        self.size.ok_or(usvg::Error::InvalidSize)
+    }
+}
+
+fn parse_tree(bytes: &[u8], options: &usvg::Options) -> Result<ParsedSvg, SvgError> {
+    let bytes = decompress(bytes)?;
+    let normalized = normalize_style_blocks(&bytes);
+    let bytes = normalized.as_deref().map_or(&*bytes, str::as_bytes);
+    // usvg rejects a root element whose size is zero or negative with `InvalidSize`.
+    let tree = usvg::Tree::from_data(bytes, options).map_err(|error| match error {
+        usvg::Error::InvalidSize => SvgError::InvalidSourceSize,
+        error => SvgError::Parse(error),
+    })?;
+
+    // A degenerate source size would propagate NaN or infinity into the scale transform.
+    let tree_size = tree.size();
+    if !is_valid_dimension(tree_size.width()) || !is_valid_dimension(tree_size.height()) {
+        return Err(SvgError::InvalidSourceSize);
+    }
+
+    // usvg resolves a percentage or missing size against its default size, which says
+    // nothing about how large the SVG is meant to be, so use the view box instead. When only
+    // one dimension is absolute, the other follows from the view box's aspect ratio.
+    let size = match root_sizing(bytes)? {
+        RootSizing::Absolute => Some(tree_size),
+        RootSizing::AbsoluteWidth { view_box } => usvg::Size::from_wh(
+            tree_size.width(),
+            tree_size.width() * view_box.height() / view_box.width(),
+        ),
+        RootSizing::AbsoluteHeight { view_box } => usvg::Size::from_wh(
+            tree_size.height() * view_box.width() / view_box.height(),
+            tree_size.height(),
+        ),
+        RootSizing::Relative { view_box } => view_box,
+    };
+
+    Ok(ParsedSvg { tree, size })
+}
+
+/// How the root `<svg>` element specifies its size.
+enum RootSizing {
+    /// Both `width` and `height` are absolute lengths.
+    Absolute,
+    /// Only `width` is an absolute length, so the height has to be derived from the `viewBox`.
+    AbsoluteWidth { view_box: usvg::Size },
+    /// Only `height` is an absolute length, so the width has to be derived from the `viewBox`.
+    AbsoluteHeight { view_box: usvg::Size },
+    /// Neither `width` nor `height` is an absolute length, or there's no valid `viewBox` to
+    /// derive the other from, so the size has to come from the `viewBox`, if there is one.
+    Relative { view_box: Option<usvg::Size> },
+}
+
+fn decompress(bytes: &[u8]) -> Result<Cow<[u8]>, usvg::Error> {
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        Ok(Cow::Owned(usvg::decompress_svgz(bytes)?))
+    } else {
+        Ok(Cow::Borrowed(bytes))
+    }
+}
+
+const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+
+/// usvg only reads the first run of text in a `<style>` element, and skips elements whose
+/// `type` has parameters, like `text/css; charset=utf-8`. Both are common in exported SVGs,
+/// and leave their shapes with the default black fill, so rewrite such elements as a single
+/// CDATA section. Returns `None` if nothing needed rewriting.
+fn normalize_style_blocks(bytes: &[u8]) -> Option<String> {
+    let text = std::str::from_utf8(bytes).ok()?;
+    let document = usvg::roxmltree::Document::parse_with_options(
+        text,
+        usvg::roxmltree::ParsingOptions {
+            allow_dtd: true,
+            ..Default::default()
+        },
+    )
+    .ok()?;
+
+    let mut replacements = Vec::new();
+    for style in document
+        .descendants()
+        .filter(|node| node.has_tag_name((SVG_NAMESPACE, "style")))
+    {
+        let is_css = style.attribute("type").map_or(true, |media_type| {
+            let essence = media_type.split(';').next().unwrap_or_default().trim();
+            essence.eq_ignore_ascii_case("text/css")
+        });
+        let needs_rewrite = style
+            .attribute("type")
+            .is_some_and(|media_type| media_type != "text/css")
+            || style.children().count() > 1;
+        if !is_css || !needs_rewrite {
+            continue;
+        }
+
+        let css = style
+            .children()
+            .filter(|child| child.is_text())
+            .filter_map(|child| child.text())
+            .collect::<String>()
+            .replace("]]>", "]]]]><![CDATA[>");
+        let tag = &text[style.range()];
+        let tag_name = &tag[1..tag.find(|c: char| c.is_whitespace() || c == '>' || c == '/')?];
+        replacements.push((
+            style.range(),
+            format!("<{tag_name}><![CDATA[{css}]]></{tag_name}>"),
+        ));
+    }
+    if replacements.is_empty() {
+        return None;
+    }
+
+    let mut normalized = text.to_string();
+    for (range, replacement) in replacements.into_iter().rev() {
+        normalized.replace_range(range, &replacement);
+    }
+    Some(normalized)
+}
+
+fn root_sizing(bytes: &[u8]) -> Result<RootSizing, usvg::Error> {
+    let bytes = decompress(bytes)?;
+
+    let text = std::str::from_utf8(&bytes).map_err(|_| usvg::Error::NotAnUtf8Str)?;
+    let document = usvg::roxmltree::Document::parse_with_options(
+        text,
+        usvg::roxmltree::ParsingOptions {
+            allow_dtd: true,
+            ..Default::default()
+        },
+    )
+    .map_err(usvg::Error::ParsingFailed)?;
+    let root = document.root_element();
+
+    let is_relative = |name| {
+        root.attribute(name)
+            .map_or(true, |value: &str| value.trim_end().ends_with('%'))
+    };
+    let (relative_width, relative_height) = (is_relative("width"), is_relative("height"));
+    if !relative_width && !relative_height {
+        return Ok(RootSizing::Absolute);
+    }
+
+    let view_box = root.attribute("viewBox").and_then(|view_box| {
+        let values = view_box
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|value| !value.is_empty())
+            .map(|value| value.parse::<f32>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        match values.as_slice() {
+            [_, _, width, height] => usvg::Size::from_wh(*width, *height),
+            _ => None,
+        }
+    });
+    Ok(match view_box {
+        Some(view_box) if !relative_width => RootSizing::AbsoluteWidth { view_box },
+        Some(view_box) if !relative_height => RootSizing::AbsoluteHeight { view_box },
+        view_box => RootSizing::Relative { view_box },
+    })
+}
+
+fn render_tree(
+    svg: &ParsedSvg,
+    size: SvgSize,
+    options: &RenderRgbaOptions,
+) -> Result<Pixmap, usvg::Error> {
+    let size = match size {
+        SvgSize::Size(size) => size,
+        // Round rather than truncate, so that a scaled dimension like 23.999
+        // doesn't shave the right or bottom edge off the rendered shape.
+        SvgSize::ScaleFactor(scale) => {
+            let svg_size = svg.size()?;
+            crate::size(
+                DevicePixels((svg_size.width() * scale).round() as i32),
+                DevicePixels((svg_size.height() * scale).round() as i32),
+            )
+        }
+    };
+
+    let transform =
+        fit_transform(&svg.tree, size)?.post_concat(options.orientation.transform(size));
+    rasterize(
+        &svg.tree,
+        options.orientation.rotated_size(size),
+        transform,
+        options.background,
+    )
+}
+
+/// Returns the transform that fits the tree's view box into the given size.
+fn fit_transform(
+    tree: &usvg::Tree,
+    size: Size<DevicePixels>,
+) -> Result<resvg::tiny_skia::Transform, usvg::Error> {
+    let size = resvg::tiny_skia::Size::from_wh(size.width.0 as f32, size.height.0 as f32)
+        .ok_or(usvg::Error::InvalidSize)?;
+    user_space_transform(tree, tree.view_box().to_transform(size))
+}
+
+/// `resvg::render` maps the view box onto the tree's size before applying its transform, so
+/// undo that mapping to make the given transform apply to SVG user units.
+fn user_space_transform(
+    tree: &usvg::Tree,
+    transform: resvg::tiny_skia::Transform,
+) -> Result<resvg::tiny_skia::Transform, usvg::Error> {
+    let view_box_transform = tree.view_box().to_transform(tree.size());
+    let inverse = view_box_transform
+        .invert()
+        .ok_or(usvg::Error::InvalidSize)?;
+    Ok(transform.pre_concat(inverse))
+}
+
+fn rasterize(
+    tree: &usvg::Tree,
+    size: Size<DevicePixels>,
+    transform: resvg::tiny_skia::Transform,
+    background: Option<Rgba>,
+) -> Result<Pixmap, usvg::Error> {
+    // Render the SVG to a pixmap with the specified width and height.
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(size.width.into(), size.height.into())
+        .ok_or(usvg::Error::InvalidSize)?;
+    if let Some(background) = background {
+        pixmap.fill(tiny_skia_color(background));
+    }
+
+    resvg::render(tree, transform, &mut pixmap.as_mut());
+
+    Ok(pixmap)
+}
+
+/// Returns whether [`render_outlined`] can draw every node in the group the way resvg would.
+fn supports_stroke_outlines(group: &usvg::Group) -> bool {
+    let has_solid_paints = |path: &usvg::Path| {
+        let fill = path.fill().map(|fill| fill.paint());
+        let stroke = path.stroke().map(|stroke| stroke.paint());
+        fill.into_iter()
+            .chain(stroke)
+            .all(|paint| matches!(paint, usvg::Paint::Color(_)))
+    };
+
+    group.clip_path().is_none()
+        && group.mask().is_none()
+        && group.filters().is_empty()
+        && group.blend_mode() == usvg::BlendMode::Normal
+        && group.children().iter().all(|node| match node {
+            usvg::Node::Group(group) => supports_stroke_outlines(group),
+            usvg::Node::Path(path) => has_solid_paints(path),
+            usvg::Node::Image(_) | usvg::Node::Text(_) => false,
+        })
+}
+
+/// Renders the group's paths like `resvg::render`, except that strokes are converted to
+/// outlines and filled. The group must satisfy [`supports_stroke_outlines`].
+fn render_outlined(
+    group: &usvg::Group,
+    transform: resvg::tiny_skia::Transform,
+    pixmap: &mut resvg::tiny_skia::PixmapMut,
+) {
+    for node in group.children() {
+        match node {
+            usvg::Node::Group(group) => {
+                let transform = transform.pre_concat(group.transform());
+                if group.opacity() == usvg::Opacity::ONE {
+                    render_outlined(group, transform, pixmap);
+                    continue;
+                }
+
+                // Draw translucent groups into a layer, so that overlapping children
+                // don't show through one another.
+                let Some(mut layer) = Pixmap::new(pixmap.width(), pixmap.height()) else {
+                    continue;
+                };
+                render_outlined(group, transform, &mut layer.as_mut());
+                pixmap.draw_pixmap(
+                    0,
+                    0,
+                    layer.as_ref(),
+                    &resvg::tiny_skia::PixmapPaint {
+                        opacity: group.opacity().get(),
+                        ..Default::default()
+                    },
+                    resvg::tiny_skia::Transform::identity(),
+                    None,
+                );
+            }
+            usvg::Node::Path(path) => {
+                if path.visibility() != usvg::Visibility::Visible {
+                    continue;
+                }
+                if path.paint_order() == usvg::PaintOrder::FillAndStroke {
+                    fill_path(path, transform, pixmap);
+                    fill_stroke_outline(path, transform, pixmap);
+                } else {
+                    fill_stroke_outline(path, transform, pixmap);
+                    fill_path(path, transform, pixmap);
+                }
+            }
+            usvg::Node::Image(_) | usvg::Node::Text(_) => {}
+        }
+    }
+}
+
+fn fill_path(
+    path: &usvg::Path,
+    transform: resvg::tiny_skia::Transform,
+    pixmap: &mut resvg::tiny_skia::PixmapMut,
+) {
+    let Some(fill) = path.fill() else {
+        return;
+    };
+    // Like resvg, skip horizontal and vertical lines, which have nothing to fill.
+    let bounds = path.data().bounds();
+    if bounds.width() == 0. || bounds.height() == 0. {
+        return;
+    }
+
+    let rule = match fill.rule() {
+        usvg::FillRule::NonZero => resvg::tiny_skia::FillRule::Winding,
+        usvg::FillRule::EvenOdd => resvg::tiny_skia::FillRule::EvenOdd,
+    };
+    let paint = solid_paint(path, fill.paint(), fill.opacity());
+    pixmap.fill_path(path.data(), &paint, rule, transform, None);
+}
+
+fn fill_stroke_outline(
+    path: &usvg::Path,
+    transform: resvg::tiny_skia::Transform,
+    pixmap: &mut resvg::tiny_skia::PixmapMut,
+) {
+    let Some(stroke) = path.stroke() else {
+        return;
+    };
+    let resolution_scale = resvg::tiny_skia::PathStroker::compute_resolution_scale(&transform);
+    let Some(outline) = path.data().stroke(&stroke.to_tiny_skia(), resolution_scale) else {
+        return;
+    };
+
+    let paint = solid_paint(path, stroke.paint(), stroke.opacity());
+    pixmap.fill_path(
+        &outline,
+        &paint,
+        resvg::tiny_skia::FillRule::Winding,
+        transform,
+        None,
+    );
+}
+
+fn solid_paint(
+    path: &usvg::Path,
+    paint: &usvg::Paint,
+    opacity: usvg::Opacity,
+) -> resvg::tiny_skia::Paint<'static> {
+    let mut solid_paint = resvg::tiny_skia::Paint::default();
+    if let usvg::Paint::Color(color) = paint {
+        solid_paint.set_color_rgba8(color.red, color.green, color.blue, opacity.to_u8());
+    }
+    solid_paint.anti_alias = path.rendering_mode().use_shape_antialiasing();
+    solid_paint
+}
+
+/// The maximum number of idle pixmaps of a single size kept by a [`PixmapPool`].
+const MAX_POOLED_PIXMAPS_PER_SIZE: usize = 4;
+/// The maximum number of idle pixmaps kept by a [`PixmapPool`] across all sizes.
+const MAX_POOLED_PIXMAPS: usize = 32;
+/// The maximum total size in bytes of the idle pixmaps kept by a [`PixmapPool`].
+const MAX_POOLED_PIXMAP_BYTES: usize = 8 * 1024 * 1024;
+
+/// A pool of scratch pixmaps, so that rendering the same icons over and over doesn't thrash
+/// the allocator. The least recently returned pixmaps are dropped to keep the pool bounded.
+#[derive(Clone, Default)]
+struct PixmapPool(Arc<Mutex<PixmapPoolState>>);
+
+#[derive(Default)]
+struct PixmapPoolState {
+    /// Idle pixmaps, from least to most recently returned.
+    pixmaps: VecDeque<Pixmap>,
+    bytes: usize,
+}
+
+impl PixmapPool {
+    /// Returns a cleared pixmap of the given size, reusing a pooled one if possible.
+    fn checkout(&self, size: Size<DevicePixels>) -> Result<Pixmap, usvg::Error> {
+        let key = (u32::from(size.width), u32::from(size.height));
+        let pooled = {
+            let mut state = self.0.lock();
+            let pooled = state
+                .pixmaps
+                .iter()
+                .rposition(|pixmap| (pixmap.width(), pixmap.height()) == key)
+                .and_then(|ix| state.pixmaps.remove(ix));
+            if let Some(pixmap) = &pooled {
+                state.bytes -= pixmap.data().len();
+            }
+            pooled
+        };
+        match pooled {
+            Some(mut pixmap) => {
+                pixmap.fill(resvg::tiny_skia::Color::TRANSPARENT);
+                Ok(pixmap)
+            }
+            None => Pixmap::new(key.0, key.1).ok_or(usvg::Error::InvalidSize),
+        }
+    }
+
+    /// Returns a pixmap to the pool once it's no longer needed.
+    fn checkin(&self, pixmap: Pixmap) {
+        let len = pixmap.data().len();
+        if len > MAX_POOLED_PIXMAP_BYTES {
+            return;
+        }
+
+        let key = (pixmap.width(), pixmap.height());
+        let mut state = self.0.lock();
+        let same_size_count = state
+            .pixmaps
+            .iter()
+            .filter(|pixmap| (pixmap.width(), pixmap.height()) == key)
+            .count();
+        if same_size_count >= MAX_POOLED_PIXMAPS_PER_SIZE {
+            return;
+        }
+
+        state.bytes += len;
+        state.pixmaps.push_back(pixmap);
+        while state.pixmaps.len() > MAX_POOLED_PIXMAPS || state.bytes > MAX_POOLED_PIXMAP_BYTES {
+            let Some(evicted) = state.pixmaps.pop_front() else {
+                break;
+            };
+            state.bytes -= evicted.data().len();
+        }
+    }
+}
+
+/// A least recently used cache of rendered alpha masks, bounded by both the number of masks
+/// and their total size in bytes. A cache with no room for any masks stores nothing.
+#[derive(Clone, Default)]
+struct MaskCache(Arc<Mutex<MaskCacheState>>);
+
+#[derive(Default)]
+struct MaskCacheState {
+    masks: FxHashMap<RenderSvgParams, CachedMask>,
+    bytes: usize,
+    max_bytes: usize,
+    max_entries: usize,
+    /// Incremented on every access, to order masks by when they were last used.
+    clock: u64,
+    stats: SvgCacheStats,
+}
+
+struct CachedMask {
+    size: Size<DevicePixels>,
+    alpha_mask: Vec<u8>,
+    last_used: u64,
+}
+
+impl MaskCache {
+    fn new(max_bytes: usize, max_entries: usize) -> Self {
+        Self(Arc::new(Mutex::new(MaskCacheState {
+            max_bytes,
+            max_entries,
+            ..Default::default()
+        })))
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.0.lock().max_entries > 0
+    }
+
+    /// Copies the cached mask for the given parameters into `out`, returning its size.
+    fn get(&self, params: &RenderSvgParams, out: &mut Vec<u8>) -> Option<Size<DevicePixels>> {
+        let mut state = self.0.lock();
+        if state.max_entries == 0 {
+            return None;
+        }
+
+        state.clock += 1;
+        let clock = state.clock;
+        let Some(mask) = state.masks.get_mut(params) else {
+            state.stats.misses += 1;
+            return None;
+        };
+        mask.last_used = clock;
+        out.clear();
+        out.extend_from_slice(&mask.alpha_mask);
+        let size = mask.size;
+        state.stats.hits += 1;
+        Some(size)
+    }
+
+    /// Caches a rendered mask, evicting the least recently used masks until it fits. Masks
+    /// larger than the whole cache are skipped, rather than evicting everything else.
+    fn insert(&self, params: &RenderSvgParams, size: Size<DevicePixels>, alpha_mask: &[u8]) {
+        let mut state = self.0.lock();
+        if state.max_entries == 0 || alpha_mask.len() > state.max_bytes {
+            return;
+        }
+
+        if let Some(previous) = state.masks.remove(params) {
+            state.bytes -= previous.alpha_mask.len();
+        }
+        while state.masks.len() >= state.max_entries
+            || state.bytes + alpha_mask.len() > state.max_bytes
+        {
+            let Some(oldest) = state
+                .masks
+                .iter()
+                .min_by_key(|(_, mask)| mask.last_used)
+                .map(|(params, _)| params.clone())
+            else {
+                break;
+            };
+            // Stop rather than spin if the oldest key can't be found again.
+            let Some(evicted) = state.masks.remove(&oldest) else {
+                break;
+            };
+            state.bytes -= evicted.alpha_mask.len();
+            state.stats.evictions += 1;
+        }
+
+        state.clock += 1;
+        let last_used = state.clock;
+        state.bytes += alpha_mask.len();
+        state.masks.insert(
+            params.clone(),
+            CachedMask {
+                size,
+                alpha_mask: alpha_mask.to_vec(),
+                last_used,
+            },
+        );
+    }
+
+    /// Removes every cached mask whose parameters match the predicate.
+    fn remove_where(&self, mut predicate: impl FnMut(&RenderSvgParams) -> bool) {
+        let mut state = self.0.lock();
+        let MaskCacheState {
+            masks,
+            bytes,
+            stats,
+            ..
+        } = &mut *state;
+        masks.retain(|params, mask| {
+            if predicate(params) {
+                *bytes -= mask.alpha_mask.len();
+                stats.evictions += 1;
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+/// Applies the options that adjust an RGBA pixmap after it has been rendered.
+fn apply_rgba_options(pixmap: &mut Pixmap, options: &RenderRgbaOptions) {
+    // Scaling every channel of a premultiplied pixel keeps it premultiplied.
+    apply_opacity(pixmap.data_mut(), options.opacity);
+    if options.straight_alpha {
+        unpremultiply(pixmap);
+    }
+}
+
+/// Scales the given alpha or premultiplied bytes by the opacity.
+fn apply_opacity(bytes: &mut [u8], opacity: f32) {
+    let opacity = sanitize_opacity(opacity);
+    if opacity == 1. {
+        return;
+    }
+    for byte in bytes {
+        *byte = (*byte as f32 * opacity).round() as u8;
+    }
+}
+
+/// Clamps an opacity to `0..=1`, mapping NaN to 1 and -0 to 0 so that equal opacities
+/// always have equal bits.
+fn sanitize_opacity(opacity: f32) -> f32 {
+    if opacity.is_nan() {
+        1.
+    } else {
+        // Adding zero turns -0 into +0.
+        opacity.clamp(0., 1.) + 0.
+    }
+}
+
+/// Converts the pixmap's premultiplied pixels to straight alpha in place.
+fn unpremultiply(pixmap: &mut Pixmap) {
+    for pixel in pixmap.data_mut().chunks_exact_mut(4) {
+        let alpha = pixel[3] as u32;
+        if alpha == 0 || alpha == 255 {
+            continue;
+        }
+        for channel in &mut pixel[..3] {
+            *channel = ((*channel as u32 * 255 + alpha / 2) / alpha).min(255) as u8;
+        }
+    }
+}
+
+fn tiny_skia_color(color: Rgba) -> resvg::tiny_skia::Color {
+    let channel = |value: f32| (value.clamp(0., 1.) * 255.).round() as u8;
+    resvg::tiny_skia::Color::from_rgba8(
+        channel(color.r),
+        channel(color.g),
+        channel(color.b),
+        channel(color.a),
+    )
+}
+
+/// Renders a bordered box into the given alpha mask buffer, used in place of SVGs that
+/// can't be parsed.
+fn placeholder_alpha_mask(size: Size<DevicePixels>, alpha_mask: &mut Vec<u8>) {
+    let width = usize::from(size.width);
+    let height = usize::from(size.height);
+    let border = (width.min(height) / 16).max(1);
+
+    alpha_mask.clear();
+    alpha_mask.resize(width * height, 0);
+    for y in 0..height {
+        for x in 0..width {
+            if x < border || y < border || x >= width - border || y >= height - border {
+                alpha_mask[y * width + x] = 255;
+            }
+        }
+    }
+}
+
+fn is_valid_dimension(value: f32) -> bool {
+    value.is_finite() && value > 0.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TestAppContext;
+    use anyhow::anyhow;
+    use std::{
+        borrow::Cow,
+        sync::atomic::{AtomicUsize, Ordering::SeqCst},
+    };
+
+    struct TestAssets {
+        assets: Vec<(&'static str, Vec<u8>)>,
+        loads: AtomicUsize,
+    }
+
+    impl TestAssets {
+        fn new(assets: Vec<(&'static str, &'static str)>) -> Self {
+            Self {
+                assets: assets
+                    .into_iter()
+                    .map(|(path, svg)| (path, svg.as_bytes().to_vec()))
+                    .collect(),
+                loads: AtomicUsize::new(0),
+            }
+        }
+
+        fn with_asset(mut self, path: &'static str, bytes: Vec<u8>) -> Self {
+            self.assets.push((path, bytes));
+            self
+        }
+    }
+
+    impl AssetSource for TestAssets {
+        fn load(&self, path: &str) -> Result<Cow<'static, [u8]>> {
+            self.loads.fetch_add(1, SeqCst);
+            self.assets
+                .iter()
+                .find(|(asset_path, _)| *asset_path == path)
+                .map(|(_, bytes)| Cow::Owned(bytes.clone()))
+                .ok_or_else(|| anyhow!("no asset at path {:?}", path))
+        }
+
+        fn list(&self, _path: &str) -> Result<Vec<SharedString>> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[test]
+    fn test_render_pixmap_rejects_zero_width_tree() {
+        let renderer = SvgRenderer::new(Arc::new(()));
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="0" height="16"></svg>"#;
+
+        let result = renderer.render_pixmap(svg, SvgSize::ScaleFactor(2.));
+        assert!(matches!(result, Err(SvgError::InvalidSourceSize)));
+
+        let result = renderer.render_pixmap(svg, SvgSize::Size(crate::size(16.into(), 16.into())));
+        assert!(matches!(result, Err(SvgError::InvalidSourceSize)));
+    }
+
+    #[test]
+    fn test_render_pixmap_rounds_fractional_scaled_size() {
+        let renderer = SvgRenderer::new(Arc::new(()));
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="7" height="7">
+            <rect width="7" height="7" fill="black"/>
+        </svg>"#;
+
+        // 7 * 3.428571 = 23.999997, which used to be truncated to 23.
+        let pixmap = renderer
+            .render_pixmap(svg, SvgSize::ScaleFactor(3.428571))
+            .unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), (24, 24));
+
+        let last_pixel = pixmap.pixel(23, 23).unwrap();
+        assert_eq!(last_pixel.alpha(), 255);
+    }
+
+    #[test]
+    fn test_render_parse_fallback() {
+        let assets = Arc::new(TestAssets::new(vec![("icons/corrupt.svg", "<svg")]));
+        let params = RenderSvgParams {
+            path: "icons/corrupt.svg".into(),
+            size: crate::size(8.into(), 8.into()),
+            quality: SvgQuality::default(),
+            opacity: 1.,
+        };
+
+        let renderer = SvgRenderer::new(assets.clone());
+        assert!(matches!(renderer.render(&params), Err(SvgError::Parse(_))));
+
+        let renderer = SvgRenderer::new(assets).with_parse_fallback(true);
+        let (_, alpha_mask) = renderer.render(&params).unwrap();
+        assert_eq!(alpha_mask.len(), 64);
+        assert_eq!(alpha_mask[0], 255);
+        assert_eq!(alpha_mask[63], 255);
+        assert_eq!(alpha_mask[3 * 8 + 3], 0);
+
+        // Assets that fail to load still produce an error.
+        let params = RenderSvgParams {
+            path: "icons/missing.svg".into(),
+            size: crate::size(8.into(), 8.into()),
+            quality: SvgQuality::default(),
+            opacity: 1.,
+        };
+        assert!(matches!(
+            renderer.render(&params),
+            Err(SvgError::AssetLoad(_))
+        ));
+    }
+
+    #[test]
+    fn test_render_stroke_outlines() {
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="8">
+            <path d="M1 1 L7 5" stroke="black" stroke-width="0.5"/>
+        </svg>"#;
+        let size = crate::size(8.into(), 8.into());
+
+        // Thin strokes are drawn as hairlines by default, which spreads them out thinly.
+        let hairline = SvgRenderer::new(Arc::new(()))
+            .render_bytes(svg, size)
+            .unwrap();
+        let outlined = SvgRenderer::new(Arc::new(()))
+            .with_stroke_outlines(true)
+            .render_bytes(svg, size)
+            .unwrap();
+        assert_eq!(hairline[8 + 1], 106);
+        assert_eq!(outlined[8 + 1], 160);
+        assert_eq!(hairline[8 + 3], 0);
+        assert_eq!(outlined[8 + 3], 0);
+
+        // SVGs the outliner can't draw faithfully are left to resvg.
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="8">
+            <linearGradient id="gradient">
+                <stop offset="0" stop-color="black"/>
+                <stop offset="1" stop-color="white"/>
+            </linearGradient>
+            <path d="M1 1 L7 5" stroke="url(#gradient)" stroke-width="0.5"/>
+        </svg>"#;
+        let outlined = SvgRenderer::new(Arc::new(()))
+            .with_stroke_outlines(true)
+            .render_bytes(svg, size)
+            .unwrap();
+        assert_eq!(outlined[8 + 1], 106);
+    }
+
+    #[test]
+    fn test_render_rgba_style_blocks() {
+        let renderer = SvgRenderer::new(Arc::new(()));
+        let red = |style: &str| {
+            let svg = format!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4">
+                    {style}
+                    <rect class="cls-1" width="4" height="4"/>
+                </svg>"#
+            );
+            let pixmap = renderer
+                .render_rgba(
+                    svg.as_bytes(),
+                    SvgSize::ScaleFactor(1.),
+                    &Default::default(),
+                )
+                .unwrap();
+            let pixel = pixmap.pixel(1, 1).unwrap();
+            (pixel.red(), pixel.green(), pixel.blue(), pixel.alpha()) == (255, 0, 0, 255)
+        };
+
+        assert!(red(
+            "<defs><style>rect { fill: blue } .cls-1 { fill: #f00 }</style></defs>"
+        ));
+        assert!(red(
+            "<style type=\"text/css\"><![CDATA[ .cls-1 { fill: #f00 } ]]></style>"
+        ));
+        // Exporters often leave comments in style sheets, which split them into several
+        // runs of text.
+        assert!(red(
+            "<style type=\"text/css\">\n<!-- Generator: Example -->\n.cls-1 { fill: #f00 }</style>"
+        ));
+        assert!(red(
+            "<style type=\"text/css; charset=utf-8\">.cls-1 { fill: #f00 }</style>"
+        ));
+        // Style sheets in other languages are still ignored.
+        assert!(!red(
+            "<style type=\"text/plain\">.cls-1 { fill: #f00 }</style>"
+        ));
+    }
+
+    #[test]
+    fn test_render_scaled() {
+        let assets = Arc::new(TestAssets::new(vec![(
+            "icons/square.svg",
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4">
+                <rect width="4" height="4" fill="black"/>
+            </svg>"#,
+        )]));
+        let renderer = SvgRenderer::new(assets);
+        let path = SharedString::from("icons/square.svg");
+        let logical_size = crate::size(11., 7.);
+
+        let (size, alpha_mask) = renderer.render_scaled(&path, logical_size, 1.).unwrap();
+        assert_eq!(size, crate::size(11.into(), 7.into()));
+        assert_eq!(alpha_mask.len(), 11 * 7);
+
+        // 16.5 by 10.5 device pixels rounds up rather than being truncated.
+        let (size, alpha_mask) = renderer.render_scaled(&path, logical_size, 1.5).unwrap();
+        assert_eq!(size, crate::size(17.into(), 11.into()));
+        assert_eq!(alpha_mask.len(), 17 * 11);
+
+        let (size, alpha_mask) = renderer.render_scaled(&path, logical_size, 2.).unwrap();
+        assert_eq!(size, crate::size(22.into(), 14.into()));
+        assert_eq!(alpha_mask.len(), 22 * 14);
+        assert_eq!(alpha_mask[7 * 22 + 7], 255);
+    }
+
+    #[test]
+    fn test_render_bytes() {
+        let renderer = SvgRenderer::new(Arc::new(()));
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4">
+            <rect width="4" height="4" fill="black"/>
+        </svg>"#;
+
+        let alpha_mask = renderer
+            .render_bytes(svg, crate::size(8.into(), 8.into()))
+            .unwrap();
+        assert_eq!(alpha_mask, vec![255; 64]);
+
+        assert!(matches!(
+            renderer.render_bytes(svg, crate::size(0.into(), 8.into())),
+            Err(SvgError::ZeroSize)
+        ));
+    }
+
+    #[test]
+    fn test_intrinsic_size() {
+        let assets = Arc::new(TestAssets::new(vec![(
+            "icons/wide.svg",
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="12"></svg>"#,
+        )]));
+        let renderer = SvgRenderer::new(assets.clone());
+        let path = SharedString::from("icons/wide.svg");
+
+        assert_eq!(
+            renderer.intrinsic_size(&path).unwrap(),
+            crate::size(24., 12.)
+        );
+        assert_eq!(
+            renderer.intrinsic_size(&path).unwrap(),
+            crate::size(24., 12.)
+        );
+        assert_eq!(assets.loads.load(SeqCst), 1);
+    }
+
+    #[test]
+    fn test_render_image_hrefs() {
+        let image = image::RgbaImage::from_pixel(2, 2, image::Rgba([255, 0, 0, 255]));
+        let mut png = Vec::new();
+        image::DynamicImage::ImageRgba8(image)
+            .write_to(&mut png, image::ImageOutputFormat::Png)
+            .unwrap();
+
+        let assets = TestAssets::new(vec![(
+            "icons/composite.svg",
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4">
+                <image href="dot.png" width="4" height="4"/>
+            </svg>"#,
+        )])
+        .with_asset("icons/dot.png", png);
+        let renderer = SvgRenderer::new(Arc::new(assets));
+
+        let (_, alpha_mask) = renderer
+            .render(&RenderSvgParams {
+                path: "icons/composite.svg".into(),
+                size: crate::size(4.into(), 4.into()),
+                quality: SvgQuality::default(),
+                opacity: 1.,
+            })
+            .unwrap();
+        assert_eq!(alpha_mask, vec![255; 16]);
+
+        // Data URIs don't go through the asset source.
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4">
+            <image width="4" height="4" href="data:image/svg+xml;utf8,&lt;svg xmlns='http://www.w3.org/2000/svg' width='1' height='1'&gt;&lt;rect width='1' height='1'/&gt;&lt;/svg&gt;"/>
+        </svg>"#;
+        let alpha_mask = renderer
+            .render_bytes(svg, crate::size(4.into(), 4.into()))
+            .unwrap();
+        assert_eq!(alpha_mask, vec![255; 16]);
+    }
+
+    #[test]
+    fn test_render_many() {
+        let assets = Arc::new(TestAssets::new(vec![(
+            "icons/square.svg",
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4">
+                <rect width="4" height="4" fill="black"/>
+            </svg>"#,
+        )]));
+        let renderer = SvgRenderer::new(assets.clone());
+
+        let alpha_masks = renderer
+            .render_many(
+                &"icons/square.svg".into(),
+                &[
+                    crate::size(4.into(), 4.into()),
+                    crate::size(8.into(), 8.into()),
+                ],
+            )
+            .unwrap();
+        assert_eq!(alpha_masks, vec![vec![255; 16], vec![255; 64]]);
+        assert_eq!(assets.loads.load(SeqCst), 1);
+    }
+
+    // For compatibility with the test macro
+    use crate as gpui;
+
+    #[crate::test]
+    async fn test_render_async(cx: &mut TestAppContext) {
+        let assets = Arc::new(TestAssets::new(vec![(
+            "icons/square.svg",
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4">
+                <rect width="4" height="4" fill="black"/>
+            </svg>"#,
+        )]));
+        let renderer = SvgRenderer::new(assets.clone());
+        let params = RenderSvgParams {
+            path: "icons/square.svg".into(),
+            size: crate::size(4.into(), 4.into()),
+            quality: SvgQuality::default(),
+            opacity: 1.,
+        };
+
+        let (_, alpha_mask) = renderer
+            .render_async(params.clone(), &cx.executor())
+            .await
+            .unwrap();
+        assert_eq!(alpha_mask, vec![255; 16]);
+
+        // The tree parsed on the background thread is reused.
+        assert_eq!(renderer.render(&params).unwrap().1, vec![255; 16]);
+        assert_eq!(assets.loads.load(SeqCst), 1);
+    }
+
+    #[crate::test]
+    async fn test_prewarm(cx: &mut TestAppContext) {
+        let renderer = test_renderer().with_mask_cache(1024, 8);
+        let size = crate::size(4.into(), 4.into());
+        renderer
+            .prewarm_async(
+                vec![
+                    ("icons/square.svg".into(), size),
+                    ("icons/missing.svg".into(), size),
+                ],
+                &cx.executor(),
+            )
+            .await;
+        renderer.prewarm(&[("icons/half.svg".into(), size)]);
+        renderer.reset_cache_stats();
+
+        for path in ["icons/square.svg", "icons/half.svg"] {
+            renderer
+                .render(&RenderSvgParams {
+                    path: path.into(),
+                    size,
+                    quality: SvgQuality::default(),
+                    opacity: 1.,
+                })
+                .unwrap();
+        }
+        assert_eq!(
+            renderer.mask_cache_stats(),
+            SvgCacheStats {
+                hits: 2,
+                misses: 0,
+                evictions: 0,
+            }
+        );
+
+        // Without a mask cache, prewarming still parses the SVGs.
+        let renderer = test_renderer();
+        renderer.prewarm(&[("icons/square.svg".into(), size)]);
+        renderer.intrinsic_size(&"icons/square.svg".into()).unwrap();
+        assert_eq!(renderer.cache_stats().hits, 1);
+    }
+
+    #[test]
+    fn test_render_rgba_background() {
+        let renderer = SvgRenderer::new(Arc::new(()));
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4">
+            <rect width="2" height="4" fill="black"/>
+        </svg>"#;
+
+        let pixmap = renderer
+            .render_pixmap(svg, SvgSize::Size(crate::size(4.into(), 4.into())))
+            .unwrap();
+        assert_eq!(pixmap.pixel(3, 0).unwrap().alpha(), 0);
+
+        let options = RenderRgbaOptions {
+            background: Some(crate::rgb(0xffffff)),
+            ..Default::default()
+        };
+        let pixmap = renderer
+            .render_rgba(
+                svg,
+                SvgSize::Size(crate::size(4.into(), 4.into())),
+                &options,
+            )
+            .unwrap();
+        let shape = pixmap.pixel(0, 0).unwrap();
+        let background = pixmap.pixel(3, 0).unwrap();
+        assert_eq!((shape.red(), shape.alpha()), (0, 255));
+        assert_eq!((background.red(), background.alpha()), (255, 255));
+    }
+
+    #[test]
+    fn test_render_region() {
+        let renderer = SvgRenderer::new(Arc::new(()));
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="20">
+            <rect x="0" y="0" width="10" height="10" fill="red"/>
+            <rect x="10" y="0" width="10" height="10" fill="lime"/>
+            <rect x="0" y="10" width="10" height="10" fill="blue"/>
+            <rect x="10" y="10" width="10" height="10" fill="white"/>
+        </svg>"#;
+
+        let lower_right = Bounds::new(crate::point(10., 10.), crate::size(10., 10.));
+        let pixmap = renderer
+            .render_region(
+                svg,
+                lower_right,
+                crate::size(4.into(), 4.into()),
+                &RenderRgbaOptions::default(),
+            )
+            .unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), (4, 4));
+        for pixel in pixmap.pixels() {
+            assert_eq!(
+                (pixel.red(), pixel.green(), pixel.blue(), pixel.alpha()),
+                (255, 255, 255, 255)
+            );
+        }
+
+        let outside = Bounds::new(crate::point(20., 0.), crate::size(10., 10.));
+        assert!(matches!(
+            renderer.render_region(
+                svg,
+                outside,
+                crate::size(4.into(), 4.into()),
+                &RenderRgbaOptions::default(),
+            ),
+            Err(SvgError::RegionOutOfBounds(_))
+        ));
+    }
+
+    fn test_renderer() -> SvgRenderer {
+        SvgRenderer::new(Arc::new(TestAssets::new(vec![
+            (
+                "icons/square.svg",
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="8">
+                    <rect width="8" height="8" fill="black"/>
+                </svg>"#,
+            ),
+            (
+                "icons/translucent.svg",
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="8">
+                    <rect width="8" height="8" fill="black" fill-opacity="0.5"/>
+                </svg>"#,
+            ),
+            (
+                "icons/half.svg",
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+                    <rect width="5" height="10" fill="black"/>
+                </svg>"#,
+            ),
+        ])))
+    }
+
+    #[test]
+    fn test_render_solid_square() {
+        let (size, alpha_mask) = test_renderer()
+            .render(&RenderSvgParams {
+                path: "icons/square.svg".into(),
+                size: crate::size(8.into(), 8.into()),
+                quality: SvgQuality::default(),
+                opacity: 1.,
+            })
+            .unwrap();
+        assert_eq!(size, crate::size(8.into(), 8.into()));
+        assert_eq!(alpha_mask, vec![255; 64]);
+    }
+
+    #[test]
+    fn test_render_into() {
+        let renderer = test_renderer();
+        let mut alpha_mask = vec![7; 256];
+
+        // Larger buffers are shrunk to fit the mask, and none of their old contents remain.
+        let size = renderer
+            .render_into(
+                &RenderSvgParams {
+                    path: "icons/half.svg".into(),
+                    size: crate::size(4.into(), 2.into()),
+                    quality: SvgQuality::default(),
+                    opacity: 1.,
+                },
+                &mut alpha_mask,
+            )
+            .unwrap();
+        assert_eq!(size, crate::size(4.into(), 2.into()));
+        assert_eq!(alpha_mask, vec![0, 255, 0, 0, 0, 255, 0, 0]);
+
+        // Smaller buffers grow to fit the mask.
+        let size = renderer
+            .render_into(
+                &RenderSvgParams {
+                    path: "icons/square.svg".into(),
+                    size: crate::size(8.into(), 8.into()),
+                    quality: SvgQuality::default(),
+                    opacity: 0.5,
+                },
+                &mut alpha_mask,
+            )
+            .unwrap();
+        assert_eq!(size, crate::size(8.into(), 8.into()));
+        assert_eq!(alpha_mask, vec![128; 64]);
+
+        assert!(matches!(
+            renderer.render_into(
+                &RenderSvgParams {
+                    path: "icons/square.svg".into(),
+                    size: crate::size(0.into(), 8.into()),
+                    quality: SvgQuality::default(),
+                    opacity: 1.,
+                },
+                &mut alpha_mask,
+            ),
+            Err(SvgError::ZeroSize)
+        ));
+    }
+
+    #[test]
+    fn test_render_translucent_shape() {
+        let (_, alpha_mask) = test_renderer()
+            .render(&RenderSvgParams {
+                path: "icons/translucent.svg".into(),
+                size: crate::size(8.into(), 8.into()),
+                quality: SvgQuality::default(),
+                opacity: 1.,
+            })
+            .unwrap();
+        assert_eq!(alpha_mask.len(), 64);
+        for alpha in alpha_mask {
+            assert!((127..=128).contains(&alpha), "unexpected alpha {alpha}");
+        }
+    }
+
+    #[test]
+    fn test_render_zero_size() {
+        let renderer = test_renderer();
+        for size in [
+            crate::size(0.into(), 8.into()),
+            crate::size(8.into(), 0.into()),
+        ] {
+            assert!(matches!(
+                renderer.render(&RenderSvgParams {
+                    path: "icons/square.svg".into(),
+                    size,
+                    quality: SvgQuality::default(),
+                    opacity: 1.,
+                }),
+                Err(SvgError::ZeroSize)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_render_scaling() {
+        let renderer = test_renderer();
+
+        // The left half of the source is filled, so the left half of the scaled mask should be too.
+        let (_, alpha_mask) = renderer
+            .render(&RenderSvgParams {
+                path: "icons/half.svg".into(),
+                size: crate::size(20.into(), 20.into()),
+                quality: SvgQuality::default(),
+                opacity: 1.,
+            })
+            .unwrap();
+        assert_eq!(alpha_mask.len(), 400);
+        for row in alpha_mask.chunks(20) {
+            assert_eq!(&row[..10], &[255; 10]);
+            assert_eq!(&row[10..], &[0; 10]);
+        }
+
+        // Sizes with a different aspect ratio center the source rather than stretching it.
+        let (_, alpha_mask) = renderer
+            .render(&RenderSvgParams {
+                path: "icons/half.svg".into(),
+                size: crate::size(4.into(), 2.into()),
+                quality: SvgQuality::default(),
+                opacity: 1.,
+            })
+            .unwrap();
+        assert_eq!(alpha_mask, vec![0, 255, 0, 0, 0, 255, 0, 0]);
+
+        let pixmap = renderer
+            .render_pixmap(
+                br#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="6"></svg>"#,
+                SvgSize::ScaleFactor(1.5),
+            )
+            .unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), (15, 9));
+    }
+
+    #[test]
+    fn test_pooled_pixmaps_are_cleared() {
+        let renderer = SvgRenderer::new(Arc::new(()));
+        let size = crate::size(4.into(), 4.into());
+
+        let filled = br#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4">
+            <rect width="4" height="4" fill="black"/>
+        </svg>"#;
+        assert_eq!(renderer.render_bytes(filled, size).unwrap(), vec![255; 16]);
+
+        // The pixmap used above is reused here, and must not leak its pixels.
+        let empty = br#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4"></svg>"#;
+        assert_eq!(renderer.render_bytes(empty, size).unwrap(), vec![0; 16]);
+    }
+
+    #[test]
+    fn test_render_quality() {
+        let renderer = SvgRenderer::new(Arc::new(TestAssets::new(vec![(
+            "icons/triangle.svg",
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="8">
+                <path d="M0 0L8 8L0 8Z" fill="black"/>
+            </svg>"#,
+        )])));
+        let is_partial = |alpha: &u8| *alpha != 0 && *alpha != 255;
+
+        let (_, smooth) = renderer
+            .render(&RenderSvgParams {
+                path: "icons/triangle.svg".into(),
+                size: crate::size(8.into(), 8.into()),
+                quality: SvgQuality::Smooth,
+                opacity: 1.,
+            })
+            .unwrap();
+        assert!(smooth.iter().any(is_partial));
+
+        let (_, crisp) = renderer
+            .render(&RenderSvgParams {
+                path: "icons/triangle.svg".into(),
+                size: crate::size(8.into(), 8.into()),
+                quality: SvgQuality::Crisp,
+                opacity: 1.,
+            })
+            .unwrap();
+        assert!(!crisp.iter().any(is_partial));
+        assert!(crisp.contains(&255));
+    }
+
+    #[test]
+    fn test_render_percentage_sized_svg() {
+        let renderer = SvgRenderer::new(Arc::new(()));
+        let svg =
+            br#"<svg xmlns="http://www.w3.org/2000/svg" width="50%" height="50%" viewBox="0 0 8 4">
+            <rect x="4" width="4" height="4" fill="black"/>
+        </svg>"#;
+
+        let pixmap = renderer
+            .render_pixmap(svg, SvgSize::Size(crate::size(16.into(), 8.into())))
+            .unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), (16, 8));
+        let row = pixmap.pixels()[..16]
+            .iter()
+            .map(|pixel| pixel.alpha())
+            .collect::<Vec<_>>();
+        assert_eq!(row, [[0; 8], [255; 8]].concat());
+
+        let pixmap = renderer
+            .render_pixmap(svg, SvgSize::ScaleFactor(2.))
+            .unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), (16, 8));
+
+        // Without a view box, there's nothing to derive the scaled size from.
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="100%">
+            <rect width="4" height="4" fill="black"/>
+        </svg>"#;
+        assert!(matches!(
+            renderer.render_pixmap(svg, SvgSize::ScaleFactor(2.)),
+            Err(SvgError::Parse(usvg::Error::InvalidSize))
+        ));
+        assert!(renderer
+            .render_pixmap(svg, SvgSize::Size(crate::size(8.into(), 8.into())))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_render_partially_sized_svg() {
+        let renderer = SvgRenderer::new(Arc::new(()));
+        let scaled_size = |root_attributes: &str| {
+            let svg = format!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" {root_attributes}>
+                    <rect x="4" width="4" height="4" fill="black"/>
+                </svg>"#
+            );
+            let pixmap = renderer
+                .render_pixmap(svg.as_bytes(), SvgSize::ScaleFactor(1.))
+                .unwrap();
+            (pixmap.width(), pixmap.height())
+        };
+
+        assert_eq!(scaled_size(r#"width="16" viewBox="0 0 8 4""#), (16, 8));
+        assert_eq!(scaled_size(r#"height="16" viewBox="0 0 8 4""#), (32, 16));
+        assert_eq!(
+            scaled_size(r#"width="16" height="50%" viewBox="0 0 8 4""#),
+            (16, 8)
+        );
+        assert_eq!(
+            scaled_size(r#"width="50%" height="16" viewBox="0 0 8 4""#),
+            (32, 16)
+        );
+        assert_eq!(scaled_size(r#"viewBox="0 0 8 4""#), (8, 4));
+        // Explicit dimensions still win over the view box's aspect ratio.
+        assert_eq!(
+            scaled_size(r#"width="16" height="16" viewBox="0 0 8 4""#),
+            (16, 16)
+        );
+
+        // Without a view box, the missing dimension can't be derived.
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" height="16">
+            <rect width="4" height="4" fill="black"/>
+        </svg>"#;
+        assert!(matches!(
+            renderer.render_pixmap(svg, SvgSize::ScaleFactor(1.)),
+            Err(SvgError::Parse(usvg::Error::InvalidSize))
+        ));
+
+        // Rendering at an exact size fits the view box, whichever dimension is given.
+        for root_attributes in [
+            r#"width="16" viewBox="0 0 8 4""#,
+            r#"height="16" viewBox="0 0 8 4""#,
+            r#"viewBox="0 0 8 4""#,
+        ] {
+            let svg = format!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" {root_attributes}>
+                    <rect x="4" width="4" height="4" fill="black"/>
+                </svg>"#
+            );
+            let alpha_mask = renderer
+                .render_bytes(svg.as_bytes(), crate::size(8.into(), 4.into()))
+                .unwrap();
+            for row in alpha_mask.chunks(8) {
+                assert_eq!(row, [[0; 4], [255; 4]].concat(), "{root_attributes}");
+            }
+        }
+
+        let assets = Arc::new(TestAssets::new(vec![(
+            "icons/tall.svg",
+            r#"<svg xmlns="http://www.w3.org/2000/svg" height="24" viewBox="0 0 8 4"></svg>"#,
+        )]));
+        assert_eq!(
+            SvgRenderer::new(assets)
+                .intrinsic_size(&"icons/tall.svg".into())
+                .unwrap(),
+            crate::size(48., 24.)
+        );
+    }
+
+    #[test]
+    fn test_render_rgba_orientation() {
+        let renderer = SvgRenderer::new(Arc::new(()));
+        // An L shape: a full-height bar on the left and a foot along the bottom.
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="2">
+            <rect width="1" height="2" fill="black"/>
+            <rect y="1" width="4" height="1" fill="black"/>
+        </svg>"#;
+        let render = |flip_x, flip_y, rotation| {
+            let pixmap = renderer
+                .render_rgba(
+                    svg,
+                    SvgSize::ScaleFactor(1.),
+                    &RenderRgbaOptions {
+                        orientation: SvgOrientation {
+                            flip_x,
+                            flip_y,
+                            rotation,
+                        },
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            let rows = pixmap
+                .pixels()
+                .chunks(pixmap.width() as usize)
+                .map(|row| {
+                    row.iter()
+                        .map(|pixel| if pixel.alpha() == 255 { '#' } else { '.' })
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>();
+            rows.join("\n")
+        };
+
+        assert_eq!(render(false, false, SvgRotation::None), "#...\n####");
+        assert_eq!(render(true, false, SvgRotation::None), "...#\n####");
+        assert_eq!(render(false, true, SvgRotation::None), "####\n#...");
+        assert_eq!(render(true, true, SvgRotation::None), "####\n...#");
+        assert_eq!(
+            render(false, false, SvgRotation::Clockwise90),
+            "##\n#.\n#.\n#."
+        );
+        assert_eq!(
+            render(false, false, SvgRotation::Clockwise180),
+            "####\n...#"
+        );
+        assert_eq!(
+            render(false, false, SvgRotation::Clockwise270),
+            ".#\n.#\n.#\n##"
+        );
+        // Flips are applied before the rotation.
+        assert_eq!(
+            render(true, false, SvgRotation::Clockwise90),
+            "#.\n#.\n#.\n##"
+        );
+
+        // Exact sizes are rotated along with the SVG, and so are regions.
+        let pixmap = renderer
+            .render_rgba(
+                svg,
+                SvgSize::Size(crate::size(8.into(), 4.into())),
+                &RenderRgbaOptions {
+                    orientation: SvgOrientation {
+                        rotation: SvgRotation::Clockwise270,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), (4, 8));
+        let pixmap = renderer
+            .render_region(
+                svg,
+                Bounds::new(crate::point(0., 0.), crate::size(2., 2.)),
+                crate::size(2.into(), 2.into()),
+                &RenderRgbaOptions {
+                    orientation: SvgOrientation {
+                        flip_x: true,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let alphas = pixmap
             .pixels()
             .iter()
             .map(|p| p.alpha())
             .collect::<Vec<_>>();
-        Ok(alpha_mask)
+        assert_eq!(alphas, [0, 255, 255, 255]);
     }
 
-    pub fn render_pixmap(&self, bytes: &[u8], size: SvgSize) -> Result<Pixmap, usvg::Error> {
-        let tree = usvg::Tree::from_data(&bytes, &usvg::Options::default())?;
+    #[test]
+    fn test_render_rgba_straight_alpha() {
+        let renderer = SvgRenderer::new(Arc::new(()));
+        let svg = br##"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4">
+            <rect width="4" height="4" fill="#ff8000" fill-opacity="0.5"/>
+        </svg>"##;
+        let size = || SvgSize::Size(crate::size(4.into(), 4.into()));
 
-        let size = match size {
-            SvgSize::Size(size) => size,
-            SvgSize::ScaleFactor(scale) => crate::size(
-                DevicePixels((tree.size().width() * scale) as i32),
-                DevicePixels((tree.size().height() * scale) as i32),
+        let premultiplied = renderer
+            .render_rgba(svg, size(), &RenderRgbaOptions::default())
+            .unwrap();
+        assert_eq!(&premultiplied.data()[..4], &[128, 64, 0, 128]);
+
+        let straight = renderer
+            .render_rgba(
+                svg,
+                size(),
+                &RenderRgbaOptions {
+                    straight_alpha: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(&straight.data()[..4], &[255, 128, 0, 128]);
+    }
+
+    #[test]
+    fn test_invalidate_cache() {
+        let assets = Arc::new(TestAssets::new(vec![
+            (
+                "icons/square.svg",
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4"></svg>"#,
+            ),
+            (
+                "icons/wide.svg",
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="4"></svg>"#,
             ),
+        ]));
+        let renderer = SvgRenderer::new(assets.clone());
+        let square = SharedString::from("icons/square.svg");
+        let wide = SharedString::from("icons/wide.svg");
+
+        renderer.intrinsic_size(&square).unwrap();
+        renderer.intrinsic_size(&wide).unwrap();
+        assert_eq!(assets.loads.load(SeqCst), 2);
+
+        renderer.invalidate(&square);
+        renderer.intrinsic_size(&square).unwrap();
+        renderer.intrinsic_size(&wide).unwrap();
+        assert_eq!(assets.loads.load(SeqCst), 3);
+
+        renderer.clear_cache();
+        renderer.intrinsic_size(&square).unwrap();
+        renderer.intrinsic_size(&wide).unwrap();
+        assert_eq!(assets.loads.load(SeqCst), 5);
+    }
+
+    #[test]
+    fn test_cache_stats() {
+        let renderer = test_renderer();
+        let square = SharedString::from("icons/square.svg");
+        let half = SharedString::from("icons/half.svg");
+
+        renderer.intrinsic_size(&square).unwrap();
+        renderer.intrinsic_size(&square).unwrap();
+        renderer.intrinsic_size(&half).unwrap();
+        renderer
+            .render_many(&square, &[crate::size(4.into(), 4.into())])
+            .unwrap();
+        assert_eq!(
+            renderer.cache_stats(),
+            SvgCacheStats {
+                hits: 2,
+                misses: 2,
+                evictions: 0,
+            }
+        );
+
+        // Missing assets count as misses, but aren't cached.
+        renderer
+            .intrinsic_size(&"icons/missing.svg".into())
+            .unwrap_err();
+        renderer.invalidate(&square);
+        renderer.invalidate(&square);
+        assert_eq!(
+            renderer.cache_stats(),
+            SvgCacheStats {
+                hits: 2,
+                misses: 3,
+                evictions: 1,
+            }
+        );
+
+        renderer.reset_cache_stats();
+        assert_eq!(renderer.cache_stats(), SvgCacheStats::default());
+
+        renderer.intrinsic_size(&square).unwrap();
+        renderer.clear_cache();
+        assert_eq!(
+            renderer.cache_stats(),
+            SvgCacheStats {
+                hits: 0,
+                misses: 1,
+                evictions: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_mask_cache_entry_limit() {
+        let renderer = test_renderer().with_mask_cache(1024, 2);
+        let params = |path: &str| RenderSvgParams {
+            path: SharedString::from(path.to_string()),
+            size: crate::size(4.into(), 4.into()),
+            quality: SvgQuality::default(),
+            opacity: 1.,
         };
+        let (square, half, translucent) = (
+            params("icons/square.svg"),
+            params("icons/half.svg"),
+            params("icons/translucent.svg"),
+        );
 
-        // Render the SVG to a pixmap with the specified width and height.
-        let mut pixmap =
-            resvg::tiny_skia::Pixmap::new(size.width.into(), size.height.into()).unwrap();
+        renderer.render(&square).unwrap();
+        renderer.render(&half).unwrap();
+        // Using the square makes the half the least recently used mask, so it's evicted first.
+        assert_eq!(renderer.render(&square).unwrap().1, vec![255; 16]);
+        renderer.render(&translucent).unwrap();
+        assert_eq!(
+            renderer.mask_cache_stats(),
+            SvgCacheStats {
+                hits: 1,
+                misses: 3,
+                evictions: 1,
+            }
+        );
 
-        let transform = tree.view_box().to_transform(
-            resvg::tiny_skia::Size::from_wh(size.width.0 as f32, size.height.0 as f32).unwrap(),
+        renderer.reset_cache_stats();
+        renderer.render(&square).unwrap();
+        renderer.render(&translucent).unwrap();
+        renderer.render(&half).unwrap();
+        assert_eq!(
+            renderer.mask_cache_stats(),
+            SvgCacheStats {
+                hits: 2,
+                misses: 1,
+                evictions: 1,
+            }
         );
 
-        resvg::render(&tree, transform, &mut pixmap.as_mut());
+        // Invalidated paths are re-rendered.
+        renderer.invalidate(&"icons/half.svg".into());
+        renderer.reset_cache_stats();
+        renderer.render(&half).unwrap();
+        assert_eq!(renderer.mask_cache_stats().misses, 1);
+    }
 
-        Ok(pixmap)
+    #[test]
+    fn test_mask_cache_byte_limit() {
+        let renderer = test_renderer().with_mask_cache(100, 10);
+        let params = |side: i32| RenderSvgParams {
+            path: "icons/square.svg".into(),
+            size: crate::size(side.into(), side.into()),
+            quality: SvgQuality::default(),
+            opacity: 1.,
+        };
+
+        renderer.render(&params(4)).unwrap();
+        renderer.render(&params(6)).unwrap();
+        // 16 + 36 + 64 bytes exceeds the budget, so the oldest mask makes room.
+        renderer.render(&params(8)).unwrap();
+        assert_eq!(renderer.mask_cache_stats().evictions, 1);
+
+        renderer.reset_cache_stats();
+        renderer.render(&params(6)).unwrap();
+        renderer.render(&params(8)).unwrap();
+        renderer.render(&params(4)).unwrap();
+        assert_eq!(
+            renderer.mask_cache_stats(),
+            SvgCacheStats {
+                hits: 2,
+                misses: 1,
+                evictions: 1,
+            }
+        );
+
+        // A mask larger than the whole budget bypasses the cache instead of emptying it.
+        renderer.reset_cache_stats();
+        let (_, alpha_mask) = renderer.render(&params(11)).unwrap();
+        assert_eq!(alpha_mask, vec![255; 121]);
+        renderer.render(&params(11)).unwrap();
+        renderer.render(&params(8)).unwrap();
+        renderer.render(&params(4)).unwrap();
+        assert_eq!(
+            renderer.mask_cache_stats(),
+            SvgCacheStats {
+                hits: 2,
+                misses: 2,
+                evictions: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_pixmap_pool_is_bounded() {
+        let pool = PixmapPool::default();
+        let checkin = |side: i32| {
+            let pixmap = pool
+                .checkout(crate::size(side.into(), side.into()))
+                .unwrap();
+            pool.checkin(pixmap);
+        };
+
+        for side in 1..=(MAX_POOLED_PIXMAPS as i32 * 2) {
+            checkin(side);
+        }
+        let state = pool.0.lock();
+        assert_eq!(state.pixmaps.len(), MAX_POOLED_PIXMAPS);
+        assert_eq!(
+            state.bytes,
+            state
+                .pixmaps
+                .iter()
+                .map(|pixmap| pixmap.data().len())
+                .sum::<usize>()
+        );
+        // The least recently returned sizes are evicted first.
+        assert_eq!(
+            state.pixmaps.front().unwrap().width(),
+            MAX_POOLED_PIXMAPS as u32 + 1
+        );
+        drop(state);
+
+        // Pixmaps that are larger than the whole budget aren't kept.
+        checkin(2048);
+        assert!(pool
+            .0
+            .lock()
+            .pixmaps
+            .iter()
+            .all(|pixmap| pixmap.width() != 2048));
+
+        // Large pixmaps push smaller ones out to stay within the byte budget.
+        checkin(1024);
+        checkin(1024 + 1);
+        let state = pool.0.lock();
+        assert!(state.bytes <= MAX_POOLED_PIXMAP_BYTES);
+        assert_eq!(state.pixmaps.back().unwrap().width(), 1025);
+    }
+
+    #[test]
+    fn test_render_opacity() {
+        let renderer = test_renderer();
+        let (_, alpha_mask) = renderer
+            .render(&RenderSvgParams {
+                path: "icons/square.svg".into(),
+                size: crate::size(8.into(), 8.into()),
+                quality: SvgQuality::default(),
+                opacity: 0.5,
+            })
+            .unwrap();
+        assert_eq!(alpha_mask, vec![128; 64]);
+
+        let svg = br##"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4">
+            <rect width="4" height="4" fill="#ff8000"/>
+        </svg>"##;
+        let pixmap = renderer
+            .render_rgba(
+                svg,
+                SvgSize::Size(crate::size(4.into(), 4.into())),
+                &RenderRgbaOptions {
+                    opacity: 0.5,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(&pixmap.data()[..4], &[128, 64, 0, 128]);
+    }
+
+    #[test]
+    fn test_render_nan_opacity() {
+        let renderer = test_renderer().with_mask_cache(1024, 1);
+        let params = |path: &str, opacity: f32| RenderSvgParams {
+            path: SharedString::from(path.to_string()),
+            size: crate::size(4.into(), 4.into()),
+            quality: SvgQuality::default(),
+            opacity,
+        };
+        let nan = params("icons/square.svg", f32::NAN);
+        assert!(nan == nan.clone());
+        assert!(params("icons/square.svg", -0.) == params("icons/square.svg", 0.));
+
+        // A NaN opacity renders opaque, and its mask can be cached and evicted like any other.
+        assert_eq!(renderer.render(&nan).unwrap().1, vec![255; 16]);
+        assert_eq!(renderer.render(&nan).unwrap().1, vec![255; 16]);
+        renderer
+            .render(&params("icons/half.svg", f32::NAN))
+            .unwrap();
+        assert_eq!(
+            renderer.mask_cache_stats(),
+            SvgCacheStats {
+                hits: 1,
+                misses: 2,
+                evictions: 1,
+            }
+        );
     }
 }
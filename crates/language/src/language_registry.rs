@@ -506,41 +506,41 @@ impl LanguageRegistry {
         content: Option<&Rope>,
         user_file_types: Option<&HashMap<Arc<str>, Vec<String>>>,
     ) -> impl Future<Output = Result<Arc<Language>>> {
-        let filename = path.file_name().and_then(|name| name.to_str());
-        let extension = path.extension_or_hidden_file_name();
-        let path_suffixes = [extension, filename];
-        let empty = Vec::new();
-
-        let rx = self.get_or_load_language(move |language_name, config| {
-            let path_matches_default_suffix = config
-                .path_suffixes
-                .iter()
-                .any(|suffix| path_suffixes.contains(&Some(suffix.as_str())));
-            let path_matches_custom_suffix = user_file_types
-                .and_then(|types| types.get(language_name))
-                .unwrap_or(&empty)
-                .iter()
-                .any(|suffix| path_suffixes.contains(&Some(suffix.as_str())));
-            let content_matches = content.zip(config.first_line_pattern.as_ref()).map_or(
-                false,
-                |(content, pattern)| {
-                    let end = content.clip_point(Point::new(0, 256), Bias::Left);
-                    let end = content.point_to_offset(end);
-                    let text = content.chunks_in_range(0..end).collect::<String>();
-                    pattern.is_match(&text)
-                },
-            );
-            if path_matches_custom_suffix {
-                2
-            } else if path_matches_default_suffix || content_matches {
-                1
-            } else {
-                0
-            }
+        let first_line = content.map(|content| {
+            let end = content.clip_point(Point::new(0, 256), Bias::Left);
+            let end = content.point_to_offset(end);
+            content.chunks_in_range(0..end).collect::<String>()
         });
+        let rx = self.get_or_load_language(file_match_score(
+            path,
+            first_line.as_deref(),
+            user_file_types,
+        ));
         async move { rx.await? }
     }
 
+    /// Returns the name of the language that a file with the given path and first line would be
+    /// detected as, without loading that language.
+    pub fn language_name_for_file(
+        &self,
+        path: &Path,
+        first_line: Option<&str>,
+        user_file_types: Option<&HashMap<Arc<str>, Vec<String>>>,
+    ) -> Option<Arc<str>> {
+        let file_match_score = file_match_score(path, first_line, user_file_types);
+        let state = self.state.read();
+        state
+            .available_languages
+            .iter()
+            .map(|language| {
+                let score = file_match_score(&language.name, &language.matcher);
+                (language, score)
+            })
+            .filter(|(_, score)| *score > 0)
+            .max_by_key(|(_, score)| *score)
+            .map(|(language, _)| language.name.clone())
+    }
+
     fn get_or_load_language(
         self: &Arc<Self>,
         callback: impl Fn(&str, &LanguageMatcher) -> usize,
@@ -891,6 +891,41 @@ impl LanguageRegistry {
     }
 }
 
+/// Scores how well a language matches a file, by its path and first line, with zero meaning
+/// that it doesn't match at all.
+fn file_match_score<'a>(
+    path: &'a Path,
+    first_line: Option<&'a str>,
+    user_file_types: Option<&'a HashMap<Arc<str>, Vec<String>>>,
+) -> impl Fn(&str, &LanguageMatcher) -> usize + 'a {
+    let filename = path.file_name().and_then(|name| name.to_str());
+    let extension = path.extension_or_hidden_file_name();
+    let path_suffixes = [extension, filename];
+    move |language_name, config| {
+        let path_matches_default_suffix = config
+            .path_suffixes
+            .iter()
+            .any(|suffix| path_suffixes.contains(&Some(suffix.as_str())));
+        let path_matches_custom_suffix = user_file_types
+            .and_then(|types| types.get(language_name))
+            .map_or(false, |suffixes| {
+                suffixes
+                    .iter()
+                    .any(|suffix| path_suffixes.contains(&Some(suffix.as_str())))
+            });
+        let content_matches = first_line
+            .zip(config.first_line_pattern.as_ref())
+            .map_or(false, |(first_line, pattern)| pattern.is_match(first_line));
+        if path_matches_custom_suffix {
+            2
+        } else if path_matches_default_suffix || content_matches {
+            1
+        } else {
+            0
+        }
+    }
+}
+
 impl LanguageRegistryState {
     fn next_language_server_id(&mut self) -> LanguageServerId {
         LanguageServerId(post_inc(&mut self.next_language_server_id))
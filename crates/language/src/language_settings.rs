@@ -466,6 +466,11 @@ impl InlayHintSettings {
 }
 
 impl AllLanguageSettings {
+    /// Returns the file suffixes that are configured to be detected as each language.
+    pub fn file_types(&self) -> &HashMap<Arc<str>, Vec<String>> {
+        &self.file_types
+    }
+
     /// Returns the [`LanguageSettings`] for the language with the specified name.
     pub fn language<'a>(&'a self, language_name: Option<&str>) -> &'a LanguageSettings {
         if let Some(name) = language_name {
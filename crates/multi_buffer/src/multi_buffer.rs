@@ -45,6 +45,14 @@ use gpui::Context;
 
 const NEWLINES: &[u8] = &[b'\n'; u8::MAX as usize];
 
+/// When excerpts window long lines, matched lines longer than this are excerpted as a window
+/// around their matches instead of in full, so that a huge line, such as in minified code,
+/// doesn't stall layout.
+pub const MAX_EXCERPTED_LINE_LEN: u32 = 1024;
+
+/// How many columns around the matches on an overly long line are kept in its excerpt.
+pub const LONG_LINE_CONTEXT_LEN: u32 = 128;
+
 #[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ExcerptId(usize);
 
@@ -915,11 +923,15 @@ impl MultiBuffer {
         }
     }
 
+    /// Pushes excerpts of the given ranges with some lines of context around them. With
+    /// `window_long_lines`, ranges on lines longer than [`MAX_EXCERPTED_LINE_LEN`] only get a
+    /// window of their line around them.
     pub fn stream_excerpts_with_context_lines(
         &mut self,
         buffer: Model<Buffer>,
         ranges: Vec<Range<text::Anchor>>,
         context_line_count: u32,
+        window_long_lines: bool,
         cx: &mut ModelContext<Self>,
     ) -> mpsc::Receiver<Range<Anchor>> {
         let (buffer_id, buffer_snapshot) =
@@ -932,8 +944,12 @@ impl MultiBuffer {
             cx.background_executor()
                 .scoped(|scope| {
                     scope.spawn(async {
-                        let (ranges, counts) =
-                            build_excerpt_ranges(&buffer_snapshot, &ranges, context_line_count);
+                        let (ranges, counts) = build_excerpt_ranges(
+                            &buffer_snapshot,
+                            &ranges,
+                            context_line_count,
+                            window_long_lines,
+                        );
                         excerpt_ranges = ranges;
                         range_counts = counts;
                     });
@@ -994,15 +1010,44 @@ impl MultiBuffer {
         context_line_count: u32,
         cx: &mut ModelContext<Self>,
     ) -> Vec<Range<Anchor>>
+    where
+        O: text::ToPoint + text::ToOffset,
+    {
+        self.insert_excerpts_with_context_lines_after(
+            ExcerptId::max(),
+            buffer,
+            ranges,
+            context_line_count,
+            false,
+            cx,
+        )
+    }
+
+    /// Like [`Self::push_excerpts_with_context_lines`], but the excerpts are inserted after the
+    /// given excerpt rather than at the end, and long lines can be windowed like in
+    /// [`Self::stream_excerpts_with_context_lines`].
+    pub fn insert_excerpts_with_context_lines_after<O>(
+        &mut self,
+        prev_excerpt_id: ExcerptId,
+        buffer: Model<Buffer>,
+        ranges: Vec<Range<O>>,
+        context_line_count: u32,
+        window_long_lines: bool,
+        cx: &mut ModelContext<Self>,
+    ) -> Vec<Range<Anchor>>
     where
         O: text::ToPoint + text::ToOffset,
     {
         let buffer_id = buffer.read(cx).remote_id();
         let buffer_snapshot = buffer.read(cx).snapshot();
-        let (excerpt_ranges, range_counts) =
-            build_excerpt_ranges(&buffer_snapshot, &ranges, context_line_count);
+        let (excerpt_ranges, range_counts) = build_excerpt_ranges(
+            &buffer_snapshot,
+            &ranges,
+            context_line_count,
+            window_long_lines,
+        );
 
-        let excerpt_ids = self.push_excerpts(buffer, excerpt_ranges, cx);
+        let excerpt_ids = self.insert_excerpts_after(prev_excerpt_id, buffer, excerpt_ranges, cx);
 
         let mut anchor_ranges = Vec::new();
         let mut ranges = ranges.into_iter();
@@ -1584,6 +1629,37 @@ impl MultiBuffer {
         if line_count == 0 {
             return;
         }
+        self.resize_excerpts(ids, cx, |buffer, context| {
+            let start = Point::new(context.start.row.saturating_sub(line_count), 0);
+            let end = buffer.clip_point(context.end + Point::new(line_count, 0), Bias::Left);
+            start..end
+        });
+    }
+
+    /// Expands the given excerpts to span the whole lines they start and end in, e.g. to show
+    /// all of a long line that an excerpt only shows a window of.
+    pub fn expand_excerpts_to_whole_lines(
+        &mut self,
+        ids: impl IntoIterator<Item = ExcerptId>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.resize_excerpts(ids, cx, |buffer, context| {
+            let start = Point::new(context.start.row, 0);
+            let end = if context.end.column == 0 {
+                context.end
+            } else {
+                Point::new(context.end.row, buffer.line_len(context.end.row))
+            };
+            start..end
+        });
+    }
+
+    fn resize_excerpts(
+        &mut self,
+        ids: impl IntoIterator<Item = ExcerptId>,
+        cx: &mut ModelContext<Self>,
+        new_context: impl Fn(&BufferSnapshot, Range<Point>) -> Range<Point>,
+    ) {
         self.sync(cx);
 
         let snapshot = self.snapshot(cx);
@@ -1599,20 +1675,10 @@ impl MultiBuffer {
             let mut excerpt = cursor.item().unwrap().clone();
             let old_text_len = excerpt.text_summary.len;
 
-            let start_row = excerpt
-                .range
-                .context
-                .start
-                .to_point(&excerpt.buffer)
-                .row
-                .saturating_sub(line_count);
-            let start_point = Point::new(start_row, 0);
+            let context = excerpt.range.context.to_point(&excerpt.buffer);
+            let new_context = new_context(&excerpt.buffer, context);
+            let (start_point, end_point) = (new_context.start, new_context.end);
             excerpt.range.context.start = excerpt.buffer.anchor_before(start_point);
-
-            let end_point = excerpt.buffer.clip_point(
-                excerpt.range.context.end.to_point(&excerpt.buffer) + Point::new(line_count, 0),
-                Bias::Left,
-            );
             excerpt.range.context.end = excerpt.buffer.anchor_after(end_point);
             excerpt.max_buffer_row = end_point.row;
 
@@ -4401,28 +4467,81 @@ fn build_excerpt_ranges<T>(
     buffer: &BufferSnapshot,
     ranges: &[Range<T>],
     context_line_count: u32,
+    window_long_lines: bool,
 ) -> (Vec<ExcerptRange<Point>>, Vec<usize>)
 where
     T: text::ToPoint,
 {
     let max_point = buffer.max_point();
+    let is_long_line_range = |range: &Range<Point>| {
+        window_long_lines
+            && range.start.row == range.end.row
+            && buffer.line_len(range.start.row) > MAX_EXCERPTED_LINE_LEN
+    };
     let mut range_counts = Vec::new();
     let mut excerpt_ranges = Vec::new();
+    // Context lines never reach back into the window of a preceding long line.
+    let mut min_excerpt_start = Point::zero();
     let mut range_iter = ranges
         .iter()
         .map(|range| range.start.to_point(buffer)..range.end.to_point(buffer))
         .peekable();
     while let Some(range) = range_iter.next() {
-        let excerpt_start = Point::new(range.start.row.saturating_sub(context_line_count), 0);
+        let mut ranges_in_excerpt = 1;
+
+        if is_long_line_range(&range) {
+            let row = range.start.row;
+            let mut end_column = range.end.column;
+            while let Some(next_range) = range_iter.peek() {
+                if is_long_line_range(next_range)
+                    && next_range.start.row == row
+                    && next_range.start.column <= end_column + 2 * LONG_LINE_CONTEXT_LEN
+                {
+                    end_column = end_column.max(next_range.end.column);
+                    ranges_in_excerpt += 1;
+                    range_iter.next();
+                } else {
+                    break;
+                }
+            }
+
+            let excerpt_start = buffer.clip_point(
+                Point::new(
+                    row,
+                    range.start.column.saturating_sub(LONG_LINE_CONTEXT_LEN),
+                ),
+                Bias::Left,
+            );
+            let excerpt_end = buffer.clip_point(
+                Point::new(row, end_column.saturating_add(LONG_LINE_CONTEXT_LEN)),
+                Bias::Right,
+            );
+            excerpt_ranges.push(ExcerptRange {
+                context: excerpt_start..excerpt_end,
+                primary: Some(range),
+            });
+            range_counts.push(ranges_in_excerpt);
+            min_excerpt_start = Point::new(row + 1, 0).min(max_point);
+            continue;
+        }
+
+        let excerpt_start = Point::new(range.start.row.saturating_sub(context_line_count), 0)
+            .max(min_excerpt_start);
         // These + 1s ensure that we select the whole next line
         let mut excerpt_end = Point::new(range.end.row + 1 + context_line_count, 0).min(max_point);
-
-        let mut ranges_in_excerpt = 1;
+        let mut last_row = range.end.row;
 
         while let Some(next_range) = range_iter.peek() {
-            if next_range.start.row <= excerpt_end.row + context_line_count {
+            if is_long_line_range(next_range) {
+                // Stop the context lines before the long line, which gets an excerpt of its own.
+                if next_range.start.row > last_row {
+                    excerpt_end = excerpt_end.min(Point::new(next_range.start.row, 0));
+                }
+                break;
+            } else if next_range.start.row <= excerpt_end.row + context_line_count {
                 excerpt_end =
                     Point::new(next_range.end.row + 1 + context_line_count, 0).min(max_point);
+                last_row = next_range.end.row;
                 ranges_in_excerpt += 1;
                 range_iter.next();
             } else {
@@ -4964,6 +5083,69 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_excerpts_with_windowed_long_lines(cx: &mut AppContext) {
+        let padding = "x".repeat(MAX_EXCERPTED_LINE_LEN as usize);
+        let text = format!("aaa\n{padding}needle{padding}\nccc\nddd");
+        let buffer = cx.new_model(|cx| Buffer::local(text.clone(), cx));
+        let needle_column = MAX_EXCERPTED_LINE_LEN;
+        let ranges = vec![
+            Point::new(0, 0)..Point::new(0, 3),
+            Point::new(1, needle_column)..Point::new(1, needle_column + 6),
+            Point::new(3, 0)..Point::new(3, 3),
+        ];
+
+        // Long lines are excerpted in full unless windowing is asked for.
+        let multibuffer = cx.new_model(|_| MultiBuffer::new(0, Capability::ReadWrite));
+        multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.push_excerpts_with_context_lines(buffer.clone(), ranges.clone(), 1, cx)
+        });
+        assert_eq!(multibuffer.read(cx).snapshot(cx).text(), text);
+
+        let multibuffer = cx.new_model(|_| MultiBuffer::new(0, Capability::ReadWrite));
+        let anchor_ranges = multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.insert_excerpts_with_context_lines_after(
+                ExcerptId::max(),
+                buffer.clone(),
+                ranges,
+                1,
+                true,
+                cx,
+            )
+        });
+
+        // The long line is cut down to a window around its match, and the context lines of
+        // the other matches stop short of it.
+        let window = "x".repeat(LONG_LINE_CONTEXT_LEN as usize);
+        let snapshot = multibuffer.read(cx).snapshot(cx);
+        assert_eq!(
+            snapshot.text(),
+            format!("aaa\n\n{window}needle{window}\nccc\nddd")
+        );
+        assert_eq!(
+            anchor_ranges
+                .iter()
+                .map(|range| range.to_point(&snapshot))
+                .collect::<Vec<_>>(),
+            vec![
+                Point::new(0, 0)..Point::new(0, 3),
+                Point::new(2, LONG_LINE_CONTEXT_LEN)..Point::new(2, LONG_LINE_CONTEXT_LEN + 6),
+                Point::new(4, 0)..Point::new(4, 3),
+            ]
+        );
+
+        // Expanding the window to whole lines brings back the rest of the long line, while the
+        // excerpts of whole lines stay as they are.
+        multibuffer.update(cx, |multibuffer, cx| {
+            let excerpt_ids = multibuffer.excerpt_ids();
+            multibuffer.expand_excerpts_to_whole_lines(excerpt_ids, cx)
+        });
+        assert_eq!(
+            multibuffer.read(cx).snapshot(cx).text(),
+            format!("aaa\n\n{padding}needle{padding}\nccc\nddd")
+        );
+    }
+
     #[gpui::test]
     async fn test_stream_excerpts_with_context_lines(cx: &mut TestAppContext) {
         let buffer = cx.new_model(|cx| Buffer::local(sample_text(20, 3, 'a'), cx));
@@ -4976,7 +5158,7 @@ mod tests {
                 snapshot.anchor_before(Point::new(15, 0))
                     ..snapshot.anchor_before(Point::new(15, 0)),
             ];
-            multibuffer.stream_excerpts_with_context_lines(buffer.clone(), ranges, 2, cx)
+            multibuffer.stream_excerpts_with_context_lines(buffer.clone(), ranges, 2, false, cx)
         });
 
         let anchor_ranges = anchor_ranges.collect::<Vec<_>>().await;
@@ -41,7 +41,9 @@ use gpui::{
 };
 use itertools::Itertools;
 use language::{
-    language_settings::{language_settings, FormatOnSave, Formatter, InlayHintKind},
+    language_settings::{
+        language_settings, AllLanguageSettings, FormatOnSave, Formatter, InlayHintKind,
+    },
     markdown, point_to_lsp, prepare_completion_documentation,
     proto::{
         deserialize_anchor, deserialize_line_ending, deserialize_version, serialize_anchor,
@@ -65,13 +67,13 @@ use node_runtime::NodeRuntime;
 use parking_lot::{Mutex, RwLock};
 use postage::watch;
 use prettier_support::{DefaultPrettier, PrettierInstance};
-use project_settings::{LspSettings, ProjectSettings};
+use project_settings::{LspSettings, ProjectSettings, SearchFilesSettings};
 use rand::prelude::*;
 use search_history::SearchHistory;
 use worktree::LocalSnapshot;
 
 use rpc::{ErrorCode, ErrorExt as _};
-use search::SearchQuery;
+use search::{MatchTimedOut, SearchQuery};
 use serde::Serialize;
 use settings::{watch_config_file, Settings, SettingsLocation, SettingsStore};
 use sha2::{Digest, Sha256};
@@ -84,7 +86,8 @@ use std::{
     env,
     ffi::OsStr,
     hash::Hash,
-    io, iter, mem,
+    io::{self, Read as _},
+    iter, mem,
     num::NonZeroU32,
     ops::Range,
     path::{self, Component, Path, PathBuf},
@@ -308,6 +311,8 @@ pub enum Event {
     Notification(String),
     LanguageServerPrompt(LanguageServerPromptRequest),
     LanguageNotFound(Model<Buffer>),
+    /// One of the project's buffers was saved.
+    BufferSaved(Model<Buffer>),
     ActiveEntryChanged(Option<ProjectEntryId>),
     ActivateProjectPanel,
     WorktreeAdded,
@@ -608,13 +613,61 @@ pub enum SearchResult {
         buffer: Model<Buffer>,
         ranges: Vec<Range<Anchor>>,
     },
+    /// A file that was left out of the search, either without being scanned or because
+    /// matching it took too long.
+    Skipped {
+        path: ProjectPath,
+        reason: SearchSkipReason,
+    },
+    /// How many files passed the query's include and exclude filters, skipped ones included.
+    /// Reported after all files were checked.
+    FilesMatchedFilters {
+        count: usize,
+    },
     LimitReached,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchSkipReason {
+    /// The file is larger than the `search.max_file_size` setting allows.
+    FileTooLarge { len: u64 },
+    /// The file couldn't be read, e.g. because its permissions don't allow it.
+    Unreadable,
+    /// Matching the query against the file took longer than the `search.file_match_timeout_ms`
+    /// setting allows, which usually means the regex is pathologically slow.
+    MatchTimedOut,
+}
+
+impl SearchSkipReason {
+    fn to_proto(self, path: &ProjectPath) -> proto::SkippedSearchFile {
+        let (reason, len) = match self {
+            Self::FileTooLarge { len } => (proto::skipped_search_file::Reason::FileTooLarge, len),
+            Self::Unreadable => (proto::skipped_search_file::Reason::Unreadable, 0),
+            Self::MatchTimedOut => (proto::skipped_search_file::Reason::MatchTimedOut, 0),
+        };
+        proto::SkippedSearchFile {
+            path: Some(path.to_proto()),
+            reason: reason.into(),
+            len,
+        }
+    }
+
+    fn from_proto(skipped_file: &proto::SkippedSearchFile) -> Self {
+        match proto::skipped_search_file::Reason::from_i32(skipped_file.reason) {
+            Some(proto::skipped_search_file::Reason::FileTooLarge) => Self::FileTooLarge {
+                len: skipped_file.len,
+            },
+            Some(proto::skipped_search_file::Reason::MatchTimedOut) => Self::MatchTimedOut,
+            Some(proto::skipped_search_file::Reason::Unreadable) | None => Self::Unreadable,
+        }
+    }
+}
+
 impl Project {
     pub fn init_settings(cx: &mut AppContext) {
         WorktreeSettings::register(cx);
         ProjectSettings::register(cx);
+        SearchFilesSettings::register(cx);
     }
 
     pub fn init(client: &Arc<Client>, cx: &mut AppContext) {
@@ -2716,6 +2769,7 @@ impl Project {
             }
 
             BufferEvent::Saved => {
+                cx.emit(Event::BufferSaved(buffer.clone()));
                 let file = File::from_dyn(buffer.read(cx).file())?;
                 let worktree_id = file.worktree_id(cx);
                 let abs_path = file.as_local()?.abs_path(cx);
@@ -6553,6 +6607,23 @@ impl Project {
                     let _ = tx.send(SearchResult::Buffer { buffer, ranges }).await;
                 }
 
+                for skipped_file in response.skipped_files {
+                    let reason = SearchSkipReason::from_proto(&skipped_file);
+                    let path = skipped_file
+                        .path
+                        .map(ProjectPath::from_proto)
+                        .ok_or_else(|| anyhow!("missing skipped file path"))?;
+                    let _ = tx.send(SearchResult::Skipped { path, reason }).await;
+                }
+
+                if let Some(count) = response.files_matched_filters {
+                    let _ = tx
+                        .send(SearchResult::FilesMatchedFilters {
+                            count: count as usize,
+                        })
+                        .await;
+                }
+
                 if response.limit_reached {
                     let _ = tx.send(SearchResult::LimitReached).await;
                 }
@@ -6599,6 +6670,10 @@ impl Project {
         // This isn't as straightforward as running an insertion sort sadly, and would also mean that it would have to care about maintaining match index
         // in face of constantly updating list of sorted matches.
         // Meanwhile, this implementation offers index stability, since the matches are already reported in a sorted order.
+        if query.git_tracked_only() && query.tracked_files().is_none() {
+            return self.search_git_tracked_files(query, cx);
+        }
+
         let snapshots = self
             .visible_worktrees(cx)
             .filter_map(|tree| {
@@ -6624,7 +6699,14 @@ impl Project {
             return rx;
         }
         let workers = background.num_cpus().min(path_count);
+        let max_file_size = SearchFilesSettings::get_global(cx).max_file_size;
+        // Only regexes can be slow enough on some input to stall the search.
+        let file_match_timeout = query.is_regex().then(|| {
+            Duration::from_millis(SearchFilesSettings::get_global(cx).file_match_timeout_ms)
+        });
         let (matching_paths_tx, matching_paths_rx) = smol::channel::bounded(1024);
+        let (skipped_paths_tx, skipped_paths_rx) = smol::channel::unbounded();
+        let filtered_file_count = Arc::new(AtomicUsize::new(0));
         let mut unnamed_files = vec![];
         let opened_buffers = self
             .opened_buffers
@@ -6641,6 +6723,8 @@ impl Project {
                 if is_ignored && !query.include_ignored() {
                     return None;
                 } else if let Some(file) = snapshot.file() {
+                    // Opened buffers are kept here even when their language isn't searched,
+                    // so that their files aren't searched from disk instead.
                     let matched_path = if include_root {
                         query.file_matches(Some(&file.full_path(cx)))
                     } else {
@@ -6653,11 +6737,28 @@ impl Project {
                         None
                     }
                 } else {
-                    unnamed_files.push(buffer);
+                    let language_name = snapshot.language().map(|language| language.name());
+                    if query.language_matches(language_name.as_deref()) {
+                        unnamed_files.push(buffer);
+                    }
                     None
                 }
             })
             .collect();
+        let language_detector = (!query.languages().is_empty()).then(|| SearchLanguageDetector {
+            languages: self.languages.clone(),
+            file_types: snapshots
+                .iter()
+                .map(|snapshot| {
+                    let location = SettingsLocation {
+                        worktree_id: snapshot.id().to_proto() as usize,
+                        path: Path::new(""),
+                    };
+                    let settings = AllLanguageSettings::get(Some(location), cx);
+                    (snapshot.id(), settings.file_types().clone())
+                })
+                .collect(),
+        });
         cx.background_executor()
             .spawn(Self::background_search(
                 unnamed_files,
@@ -6669,7 +6770,12 @@ impl Project {
                 include_root,
                 path_count,
                 snapshots,
+                language_detector,
+                max_file_size,
+                file_match_timeout,
                 matching_paths_tx,
+                skipped_paths_tx,
+                filtered_file_count.clone(),
             ))
             .detach();
 
@@ -6719,24 +6825,43 @@ impl Project {
                         let ranges = cx
                             .background_executor()
                             .spawn(async move {
-                                query
-                                    .search(&snapshot, None)
-                                    .await
-                                    .iter()
-                                    .map(|range| {
-                                        snapshot.anchor_before(range.start)
-                                            ..snapshot.anchor_after(range.end)
-                                    })
-                                    .collect::<Vec<_>>()
+                                let ranges = query
+                                    .search_until(&snapshot, None, file_match_timeout)
+                                    .await?;
+                                Ok::<_, MatchTimedOut>(
+                                    ranges
+                                        .iter()
+                                        .map(|range| {
+                                            snapshot.anchor_before(range.start)
+                                                ..snapshot.anchor_after(range.end)
+                                        })
+                                        .collect::<Vec<_>>(),
+                                )
                             })
                             .await;
-                        anyhow::Ok((buffer, ranges))
+                        anyhow::Ok(Some((buffer, ranges)))
                     }));
                 }
 
                 let chunk_results = futures::future::join_all(chunk_results).await;
                 for result in chunk_results {
-                    if let Some((buffer, ranges)) = result.log_err() {
+                    if let Some((buffer, ranges)) = result.log_err().flatten() {
+                        let ranges = match ranges {
+                            Ok(ranges) => ranges,
+                            Err(MatchTimedOut) => {
+                                let path =
+                                    buffer.read_with(&cx, |buffer, cx| buffer.project_path(cx))?;
+                                if let Some(path) = path {
+                                    result_tx
+                                        .send(SearchResult::Skipped {
+                                            path,
+                                            reason: SearchSkipReason::MatchTimedOut,
+                                        })
+                                        .await?;
+                                }
+                                continue;
+                            }
+                        };
                         range_count += ranges.len();
                         result_tx
                             .send(SearchResult::Buffer { buffer, ranges })
@@ -6749,6 +6874,17 @@ impl Project {
                 }
             }
 
+            while let Ok((path, reason)) = skipped_paths_rx.try_recv() {
+                result_tx
+                    .send(SearchResult::Skipped { path, reason })
+                    .await?;
+            }
+            result_tx
+                .send(SearchResult::FilesMatchedFilters {
+                    count: filtered_file_count.load(SeqCst),
+                })
+                .await?;
+
             if limit_reached {
                 result_tx.send(SearchResult::LimitReached).await?;
             }
@@ -6760,6 +6896,138 @@ impl Project {
         result_rx
     }
 
+    /// Looks up the files git tracks in the visible worktrees on the background executor, then
+    /// searches them.
+    fn search_git_tracked_files(
+        &self,
+        query: SearchQuery,
+        cx: &mut ModelContext<Self>,
+    ) -> Receiver<SearchResult> {
+        let tracked_files = self.git_tracked_files(cx);
+        let (result_tx, result_rx) = smol::channel::bounded(1024);
+        cx.spawn(|this, mut cx| async move {
+            let query = query.with_tracked_files(tracked_files.await);
+            let mut results = this.update(&mut cx, |this, cx| this.search_local(query, cx))?;
+            while let Some(result) = results.next().await {
+                result_tx.send(result).await?;
+            }
+            anyhow::Ok(())
+        })
+        .detach();
+        result_rx
+    }
+
+    /// Reads the files git tracks in the visible worktrees on the background executor, with
+    /// paths named the way [`SearchQuery::file_matches`] is passed them.
+    fn git_tracked_files(&self, cx: &AppContext) -> Task<HashSet<PathBuf>> {
+        let include_root = self.visible_worktrees(cx).count() > 1;
+        let mut repositories = Vec::new();
+        for worktree in self.visible_worktrees(cx) {
+            let Some(worktree) = worktree.read(cx).as_local() else {
+                continue;
+            };
+            let snapshot = worktree.snapshot();
+            let root_name = include_root.then(|| PathBuf::from(snapshot.root_name()));
+            for (work_directory, repository) in snapshot.repositories() {
+                if let Some(local_repository) = snapshot.get_local_repo(repository) {
+                    repositories.push((
+                        root_name.clone(),
+                        work_directory.clone(),
+                        local_repository.repo().clone(),
+                    ));
+                }
+            }
+        }
+
+        cx.background_executor().spawn(async move {
+            let mut tracked_files = HashSet::default();
+            for (root_name, work_directory, repository) in repositories {
+                let tracked_paths = repository.lock().tracked_paths();
+                tracked_files.extend(tracked_paths.into_iter().map(|repo_path| {
+                    let path = work_directory.join(&*repo_path);
+                    match &root_name {
+                        Some(root_name) => root_name.join(path),
+                        None => path,
+                    }
+                }));
+            }
+            tracked_files
+        })
+    }
+
+    /// Searches a single buffer for the query again, applying the same filters and match
+    /// timeout as [`Self::search`], so that the results of a search can be refreshed for
+    /// that buffer. Resolves to `None` when the buffer shouldn't be searched, or matching it
+    /// was skipped, in which case its results should be left as they are.
+    pub fn search_buffer(
+        &self,
+        buffer: &Model<Buffer>,
+        query: SearchQuery,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Option<Vec<Range<Anchor>>>> {
+        let snapshot = buffer.read(cx).snapshot();
+        let Some(file) = snapshot.file() else {
+            return Task::ready(None);
+        };
+        let project_path = ProjectPath {
+            worktree_id: file.worktree_id(cx),
+            path: file.path().clone(),
+        };
+        let is_ignored = self
+            .entry_for_path(&project_path, cx)
+            .map_or(false, |entry| entry.is_ignored);
+        let path = if self.visible_worktrees(cx).count() > 1 {
+            file.full_path(cx)
+        } else {
+            file.path().to_path_buf()
+        };
+        let language_name = snapshot.language().map(|language| language.name());
+        if is_ignored && !query.include_ignored()
+            || !query.file_matches(Some(&path))
+            || !query.language_matches(language_name.as_deref())
+        {
+            return Task::ready(None);
+        }
+
+        let settings = SearchFilesSettings::get_global(cx);
+        if search_skip_reason(&path, snapshot.len() as u64, settings.max_file_size).is_some() {
+            return Task::ready(None);
+        }
+        let file_match_timeout = query
+            .is_regex()
+            .then(|| Duration::from_millis(settings.file_match_timeout_ms));
+        let tracked_files = (query.git_tracked_only() && query.tracked_files().is_none())
+            .then(|| self.git_tracked_files(cx));
+
+        cx.background_executor().spawn(async move {
+            if let Some(tracked_files) = tracked_files {
+                if !tracked_files.await.contains(&path) {
+                    return None;
+                }
+            }
+            match query
+                .search_until(&snapshot, None, file_match_timeout)
+                .await
+            {
+                Ok(ranges) => Some(
+                    ranges
+                        .into_iter()
+                        .map(|range| {
+                            snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end)
+                        })
+                        .collect(),
+                ),
+                Err(MatchTimedOut) => {
+                    log::info!(
+                        "not searching {path:?} again: matching took longer than {:?}",
+                        file_match_timeout.unwrap_or_default()
+                    );
+                    None
+                }
+            }
+        })
+    }
+
     /// Pick paths that might potentially contain a match of a given search query.
     #[allow(clippy::too_many_arguments)]
     async fn background_search(
@@ -6772,12 +7040,21 @@ impl Project {
         include_root: bool,
         path_count: usize,
         snapshots: Vec<LocalSnapshot>,
+        language_detector: Option<SearchLanguageDetector>,
+        max_file_size: u64,
+        file_match_timeout: Option<Duration>,
         matching_paths_tx: Sender<SearchMatchCandidate>,
+        skipped_paths_tx: Sender<(ProjectPath, SearchSkipReason)>,
+        filtered_file_count: Arc<AtomicUsize>,
     ) {
         let fs = &fs;
         let query = &query;
         let matching_paths_tx = &matching_paths_tx;
+        let skipped_paths_tx = &skipped_paths_tx;
+        let filtered_file_count = &*filtered_file_count;
         let snapshots = &snapshots;
+        let language_detector = language_detector.as_ref();
+        filtered_file_count.fetch_add(unnamed_buffers.len(), SeqCst);
         for buffer in unnamed_buffers {
             matching_paths_tx
                 .send(SearchMatchCandidate::OpenBuffer {
@@ -6787,7 +7064,12 @@ impl Project {
                 .await
                 .log_err();
         }
-        for (path, (buffer, _)) in opened_buffers.iter() {
+        for (path, (buffer, snapshot)) in opened_buffers.iter() {
+            let language_name = snapshot.language().map(|language| language.name());
+            if !query.language_matches(language_name.as_deref()) {
+                continue;
+            }
+            filtered_file_count.fetch_add(1, SeqCst);
             matching_paths_tx
                 .send(SearchMatchCandidate::OpenBuffer {
                     buffer: buffer.clone(),
@@ -6816,7 +7098,12 @@ impl Project {
                                 worker_start_ix,
                                 worker_end_ix,
                                 query,
+                                language_detector,
+                                max_file_size,
+                                file_match_timeout,
                                 matching_paths_tx,
+                                skipped_paths_tx,
+                                filtered_file_count,
                                 &opened_buffers,
                                 include_root,
                                 fs,
@@ -6837,7 +7124,12 @@ impl Project {
                                     ignored_entry,
                                     fs,
                                     query,
+                                    language_detector,
+                                    max_file_size,
+                                    file_match_timeout,
                                     matching_paths_tx,
+                                    skipped_paths_tx,
+                                    filtered_file_count,
                                 )
                                 .await;
                             });
@@ -9287,6 +9579,8 @@ impl Project {
 
         cx.spawn(move |mut cx| async move {
             let mut locations = Vec::new();
+            let mut skipped_files = Vec::new();
+            let mut files_matched_filters = None;
             let mut limit_reached = false;
             while let Some(result) = result.next().await {
                 match result {
@@ -9304,12 +9598,20 @@ impl Project {
                             });
                         }
                     }
+                    SearchResult::Skipped { path, reason } => {
+                        skipped_files.push(reason.to_proto(&path));
+                    }
+                    SearchResult::FilesMatchedFilters { count } => {
+                        files_matched_filters = Some(count as u64);
+                    }
                     SearchResult::LimitReached => limit_reached = true,
                 }
             }
             Ok(proto::SearchProjectResponse {
                 locations,
                 limit_reached,
+                skipped_files,
+                files_matched_filters,
             })
         })
         .await
@@ -10225,7 +10527,12 @@ async fn search_snapshots(
     worker_start_ix: usize,
     worker_end_ix: usize,
     query: &SearchQuery,
+    language_detector: Option<&SearchLanguageDetector>,
+    max_file_size: u64,
+    file_match_timeout: Option<Duration>,
     results_tx: &Sender<SearchMatchCandidate>,
+    skipped_paths_tx: &Sender<(ProjectPath, SearchSkipReason)>,
+    filtered_file_count: &AtomicUsize,
     opened_buffers: &HashMap<Arc<Path>, (Model<Buffer>, BufferSnapshot)>,
     include_root: bool,
     fs: &Arc<dyn Fs>,
@@ -10268,14 +10575,43 @@ async fn search_snapshots(
                     query.file_matches(Some(&entry.path))
                 };
 
-                let matches = if matched_path {
+                let matched_path = if matched_path {
                     abs_path.clear();
                     abs_path.push(&snapshot.abs_path());
                     abs_path.push(&entry.path);
-                    if let Some(file) = fs.open_sync(&abs_path).await.log_err() {
-                        query.detect(file).unwrap_or(false)
-                    } else {
+                    match language_detector {
+                        Some(detector) => {
+                            detector
+                                .language_matches(fs, snapshot.id(), &abs_path, query)
+                                .await
+                        }
+                        None => true,
+                    }
+                } else {
+                    false
+                };
+
+                let matches = if matched_path {
+                    filtered_file_count.fetch_add(1, SeqCst);
+                    if let Some(reason) = search_skip_reason(&abs_path, entry.size, max_file_size) {
+                        let project_path = ProjectPath {
+                            worktree_id: snapshot.id(),
+                            path: entry.path.clone(),
+                        };
+                        skipped_paths_tx.send((project_path, reason)).await.ok();
                         false
+                    } else {
+                        match detect_in_file(fs, &abs_path, query, file_match_timeout).await {
+                            Ok(matches) => matches,
+                            Err(reason) => {
+                                let project_path = ProjectPath {
+                                    worktree_id: snapshot.id(),
+                                    path: entry.path.clone(),
+                                };
+                                skipped_paths_tx.send((project_path, reason)).await.ok();
+                                false
+                            }
+                        }
                     }
                 } else {
                     false
@@ -10298,12 +10634,113 @@ async fn search_snapshots(
     }
 }
 
+fn search_skip_reason(abs_path: &Path, len: u64, max_file_size: u64) -> Option<SearchSkipReason> {
+    if len > max_file_size {
+        log::info!(
+            "skipping {abs_path:?} in project search: {len} bytes exceeds the max file size of {max_file_size} bytes"
+        );
+        Some(SearchSkipReason::FileTooLarge { len })
+    } else {
+        None
+    }
+}
+
+/// Detects the languages of files being searched, so that files in languages the query
+/// doesn't search are left out before they're opened.
+struct SearchLanguageDetector {
+    languages: Arc<LanguageRegistry>,
+    /// The file types configured for each worktree.
+    file_types: HashMap<WorktreeId, HashMap<Arc<str>, Vec<String>>>,
+}
+
+impl SearchLanguageDetector {
+    /// How much of a file is read to match its first line, e.g. a shebang, against languages.
+    const FIRST_LINE_LEN: u64 = 256;
+
+    /// Detects the file's language from its path and first line, like opening it would.
+    async fn language_matches(
+        &self,
+        fs: &Arc<dyn Fs>,
+        worktree_id: WorktreeId,
+        abs_path: &Path,
+        query: &SearchQuery,
+    ) -> bool {
+        let mut start = Vec::new();
+        if let Some(file) = fs.open_sync(abs_path).await.log_err() {
+            file.take(Self::FIRST_LINE_LEN)
+                .read_to_end(&mut start)
+                .log_err();
+        }
+        let start = String::from_utf8_lossy(&start);
+        let language_name = self.languages.language_name_for_file(
+            abs_path,
+            start.lines().next(),
+            self.file_types.get(&worktree_id),
+        );
+        query.language_matches(language_name.as_deref())
+    }
+}
+
+/// Returns whether the file at the given path contains a match for the query, or why it
+/// couldn't be searched.
+async fn detect_in_file(
+    fs: &Arc<dyn Fs>,
+    abs_path: &Path,
+    query: &SearchQuery,
+    timeout: Option<Duration>,
+) -> Result<bool, SearchSkipReason> {
+    let mut file = fs
+        .open_sync(abs_path)
+        .await
+        .with_context(|| format!("opening {abs_path:?} for search"))
+        .warn_on_err()
+        .ok_or(SearchSkipReason::Unreadable)?;
+    let result = if timeout.is_some() {
+        // Load the file first, so that only matching counts towards the timeout.
+        let mut content = Vec::new();
+        match file.read_to_end(&mut content) {
+            Ok(_) => query.detect_until(content.as_slice(), timeout),
+            Err(error) => Err(error.into()),
+        }
+    } else {
+        query.detect(file)
+    };
+    match result {
+        Ok(matches) => Ok(matches),
+        Err(error) if error.is::<MatchTimedOut>() => {
+            log::info!(
+                "skipping {abs_path:?} in project search: matching took longer than {:?}",
+                timeout.unwrap_or_default()
+            );
+            Err(SearchSkipReason::MatchTimedOut)
+        }
+        // Regexes only match UTF-8 text, so binary files are simply not matches.
+        Err(error)
+            if error
+                .downcast_ref::<io::Error>()
+                .is_some_and(|err| err.kind() == io::ErrorKind::InvalidData) =>
+        {
+            Ok(false)
+        }
+        Err(error) => {
+            log::warn!("reading {abs_path:?} for search: {error:?}");
+            Err(SearchSkipReason::Unreadable)
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn search_ignored_entry(
     snapshot: &LocalSnapshot,
     ignored_entry: &Entry,
     fs: &Arc<dyn Fs>,
     query: &SearchQuery,
+    language_detector: Option<&SearchLanguageDetector>,
+    max_file_size: u64,
+    file_match_timeout: Option<Duration>,
     counter_tx: &Sender<SearchMatchCandidate>,
+    skipped_paths_tx: &Sender<(ProjectPath, SearchSkipReason)>,
+    filtered_file_count: &AtomicUsize,
 ) {
     let mut ignored_paths_to_process =
         VecDeque::from([snapshot.abs_path().join(&ignored_entry.path)]);
@@ -10337,25 +10774,51 @@ async fn search_ignored_entry(
                 {
                     continue;
                 }
-                let matches = if let Some(file) = fs
-                    .open_sync(&ignored_abs_path)
-                    .await
-                    .with_context(|| format!("Opening ignored path {ignored_abs_path:?}"))
-                    .log_err()
+                if let Some(detector) = language_detector {
+                    if !detector
+                        .language_matches(fs, snapshot.id(), &ignored_abs_path, query)
+                        .await
+                    {
+                        continue;
+                    }
+                }
+                filtered_file_count.fetch_add(1, SeqCst);
+                if let Some(reason) =
+                    search_skip_reason(&ignored_abs_path, fs_metadata.len, max_file_size)
                 {
-                    query.detect(file).unwrap_or(false)
-                } else {
-                    false
-                };
-
-                if matches {
-                    let project_path = SearchMatchCandidate::Path {
+                    let project_path = ProjectPath {
                         worktree_id: snapshot.id(),
                         path: Arc::from(
                             ignored_abs_path
                                 .strip_prefix(snapshot.abs_path())
                                 .expect("scanning worktree-related files"),
                         ),
+                    };
+                    skipped_paths_tx.send((project_path, reason)).await.ok();
+                    continue;
+                }
+                let path: Arc<Path> = Arc::from(
+                    ignored_abs_path
+                        .strip_prefix(snapshot.abs_path())
+                        .expect("scanning worktree-related files"),
+                );
+                let matches =
+                    match detect_in_file(fs, &ignored_abs_path, query, file_match_timeout).await {
+                        Ok(matches) => matches,
+                        Err(reason) => {
+                            let project_path = ProjectPath {
+                                worktree_id: snapshot.id(),
+                                path,
+                            };
+                            skipped_paths_tx.send((project_path, reason)).await.ok();
+                            continue;
+                        }
+                    };
+
+                if matches {
+                    let project_path = SearchMatchCandidate::Path {
+                        worktree_id: snapshot.id(),
+                        path,
                         is_ignored: true,
                     };
                     if counter_tx.send(project_path).await.is_err() {
@@ -115,3 +115,38 @@ impl Settings for ProjectSettings {
         sources.json_merge()
     }
 }
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SearchFilesSettings {
+    pub max_file_size: u64,
+    pub file_match_timeout_ms: u64,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SearchFilesSettingsContent {
+    /// Files larger than this many bytes are skipped when searching the project.
+    /// Buffers that are already open are always searched.
+    ///
+    /// Default: 10000000
+    pub max_file_size: Option<u64>,
+    /// How many milliseconds matching a regex against a single file may take before the file
+    /// is skipped. Some regexes are slow enough on some input to stall the whole search.
+    /// The time is checked once the first 64KB of text have been searched, so even a single
+    /// long line can be interrupted.
+    ///
+    /// Default: 2000
+    pub file_match_timeout_ms: Option<u64>,
+}
+
+impl Settings for SearchFilesSettings {
+    const KEY: Option<&'static str> = Some("search");
+
+    type FileContent = SearchFilesSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Event, *};
+use crate::{search::Inversion, Event, *};
 use fs::FakeFs;
 use futures::{future, StreamExt};
 use gpui::AppContext;
@@ -4470,6 +4470,389 @@ async fn test_search_in_gitignored_dirs(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_search_skips_files_above_max_file_size(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let max_file_size = cx.update(|cx| SearchFilesSettings::get_global(cx).max_file_size);
+    let large_text = format!(
+        "const TWO: usize = 2;\n{}",
+        " ".repeat(max_file_size as usize)
+    );
+    let large_len = large_text.len() as u64;
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "small.rs": "const TWO: usize = 2;",
+            "large.rs": large_text,
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees().next().unwrap().read(cx).id()
+    });
+    let query = SearchQuery::text("TWO", false, true, false, Vec::new(), Vec::new()).unwrap();
+
+    assert_eq!(
+        search(&project, query.clone(), cx).await.unwrap(),
+        HashMap::from_iter([("dir/small.rs".to_string(), vec![6..9])]),
+        "Files above the max file size should not be searched by default"
+    );
+    assert_eq!(
+        skipped_search_paths(&project, query.clone(), cx).await,
+        vec![(
+            ProjectPath {
+                worktree_id,
+                path: Path::new("large.rs").into(),
+            },
+            SearchSkipReason::FileTooLarge { len: large_len },
+        )],
+        "Skipped files should be reported along with the reason they were skipped"
+    );
+
+    cx.update(|cx| {
+        cx.update_global(|settings: &mut SettingsStore, cx| {
+            settings.update_user_settings::<SearchFilesSettings>(cx, |settings| {
+                settings.max_file_size = Some(large_len);
+            });
+        })
+    });
+    assert_eq!(
+        search(&project, query.clone(), cx).await.unwrap(),
+        HashMap::from_iter([
+            ("dir/small.rs".to_string(), vec![6..9]),
+            ("dir/large.rs".to_string(), vec![6..9]),
+        ]),
+        "Raising the max file size should include the large file again"
+    );
+    assert_eq!(skipped_search_paths(&project, query, cx).await, Vec::new());
+}
+
+#[gpui::test]
+async fn test_search_skips_files_that_take_too_long_to_match(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    // A long run of `a`s that doesn't end the line makes the regex below backtrack through
+    // every way of splitting it up.
+    let redos_text = format!("{}b", "a".repeat(200_000));
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "redos.txt": redos_text,
+            "plain.txt": "aaa",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees().next().unwrap().read(cx).id()
+    });
+    let query = SearchQuery::regex("(a+)+$", false, true, false, Vec::new(), Vec::new()).unwrap();
+
+    cx.update(|cx| {
+        cx.update_global(|settings: &mut SettingsStore, cx| {
+            settings.update_user_settings::<SearchFilesSettings>(cx, |settings| {
+                settings.file_match_timeout_ms = Some(0);
+            });
+        })
+    });
+    assert_eq!(
+        search(&project, query.clone(), cx).await.unwrap(),
+        HashMap::from_iter([("dir/plain.txt".to_string(), vec![0..3])]),
+        "Files that match before running out of time should still have results"
+    );
+    assert_eq!(
+        skipped_search_paths(&project, query.clone(), cx).await,
+        vec![(
+            ProjectPath {
+                worktree_id,
+                path: Path::new("redos.txt").into(),
+            },
+            SearchSkipReason::MatchTimedOut,
+        )],
+        "Files that take too long to match should be reported as skipped"
+    );
+
+    // Once open, the file's buffer is searched instead, which is timed the same way.
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/redos.txt", cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        skipped_search_paths(&project, query.clone(), cx).await,
+        vec![(
+            ProjectPath {
+                worktree_id,
+                path: Path::new("redos.txt").into(),
+            },
+            SearchSkipReason::MatchTimedOut,
+        )],
+        "Open buffers that take too long to match should be reported as skipped"
+    );
+    drop(buffer);
+
+    cx.update(|cx| {
+        cx.update_global(|settings: &mut SettingsStore, cx| {
+            settings.update_user_settings::<SearchFilesSettings>(cx, |settings| {
+                settings.file_match_timeout_ms = None;
+            });
+        })
+    });
+    assert_eq!(
+        search(&project, query.clone(), cx).await.unwrap(),
+        HashMap::from_iter([("dir/plain.txt".to_string(), vec![0..3])]),
+        "With the default timeout, every file should be matched in full"
+    );
+    assert_eq!(skipped_search_paths(&project, query, cx).await, Vec::new());
+}
+
+#[gpui::test]
+async fn test_search_inverted(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "one.rs": "const ONE: usize = 1;\n\nfn one() {}\n",
+            "two.rs": "use one::ONE;\nconst TWO: usize = ONE + 1;\nfn two() {}",
+            "three.rs": "const THREE: usize = 3;",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let query = SearchQuery::text("one", false, false, false, Vec::new(), Vec::new()).unwrap();
+
+    assert_eq!(
+        search(&project, query.clone().with_inversion(Inversion::Lines), cx)
+            .await
+            .unwrap(),
+        HashMap::from_iter([
+            ("dir/two.rs".to_string(), vec![42..53]),
+            ("dir/three.rs".to_string(), vec![0..23]),
+        ]),
+        "Inverting by line should find the non-blank lines without a match"
+    );
+    assert_eq!(
+        search(&project, query.with_inversion(Inversion::Files), cx)
+            .await
+            .unwrap(),
+        HashMap::from_iter([("dir/three.rs".to_string(), vec![0..23])]),
+        "Inverting by file should find the first line of each file without a match"
+    );
+}
+
+#[gpui::test]
+async fn test_search_with_languages(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "one.rs": "const ONE: usize = 1;",
+            "two.ts": "const TWO = one.ONE;",
+            "script": "#!/usr/bin/env node\nconst three = one.ONE;",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    language_registry.add(typescript_lang());
+    language_registry.add(Arc::new(Language::new(
+        LanguageConfig {
+            name: "JavaScript".into(),
+            matcher: LanguageMatcher {
+                path_suffixes: vec!["js".to_string()],
+                first_line_pattern: Some(regex::Regex::new(r"\bnode\b").unwrap()),
+            },
+            ..Default::default()
+        },
+        None,
+    )));
+    let query = SearchQuery::text("ONE", true, true, false, Vec::new(), Vec::new()).unwrap();
+
+    assert_eq!(
+        search(
+            &project,
+            query.clone().with_languages(vec!["rust".into()]),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([("dir/one.rs".to_string(), vec![6..9])]),
+        "Only Rust files should be searched, matching the language name case-insensitively"
+    );
+    assert_eq!(
+        search(
+            &project,
+            query
+                .clone()
+                .with_languages(vec!["TypeScript".into(), "JavaScript".into()]),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([
+            ("dir/two.ts".to_string(), vec![16..19]),
+            ("dir/script".to_string(), vec![38..41]),
+        ]),
+        "Languages should be detected from the first line of files without an extension"
+    );
+    assert_eq!(
+        search(
+            &project,
+            query.clone().with_languages(vec!["JSON".into()]),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::default(),
+        "No files of the language means no results"
+    );
+
+    // Files are left out by language before they're searched, so they aren't counted.
+    let rust_query = query.clone().with_languages(vec!["Rust".into()]);
+    let script_query = query.clone().with_languages(vec!["JavaScript".into()]);
+    assert_eq!(
+        files_matched_filters(&project, rust_query.clone(), cx).await,
+        Some(1)
+    );
+    assert_eq!(
+        files_matched_filters(&project, script_query.clone(), cx).await,
+        Some(1),
+        "Files without an extension should be counted by the language of their first line"
+    );
+    assert_eq!(
+        files_matched_filters(&project, query.with_languages(vec!["JSON".into()]), cx).await,
+        Some(0)
+    );
+
+    // Opened buffers are filtered by their language too, without searching their files.
+    let _buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/one.rs", cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(
+        search(&project, script_query.clone(), cx).await.unwrap(),
+        HashMap::from_iter([("dir/script".to_string(), vec![38..41])]),
+    );
+    assert_eq!(
+        files_matched_filters(&project, script_query, cx).await,
+        Some(1)
+    );
+    assert_eq!(
+        search(&project, rust_query.clone(), cx).await.unwrap(),
+        HashMap::from_iter([("dir/one.rs".to_string(), vec![6..9])]),
+    );
+    assert_eq!(
+        files_matched_filters(&project, rust_query, cx).await,
+        Some(1)
+    );
+}
+
+#[gpui::test]
+async fn test_search_reports_unreadable_files(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "one.rs": "const ONE: usize = 1;",
+            "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+        }),
+    )
+    .await;
+    fs.insert_file("/dir/three.bin", vec![0xff, 0xfe, 0x00])
+        .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees().next().unwrap().read(cx).id()
+    });
+    let query = SearchQuery::text("ONE", false, true, false, Vec::new(), Vec::new()).unwrap();
+    assert_eq!(
+        skipped_search_paths(&project, query.clone(), cx).await,
+        Vec::new()
+    );
+    let regex_query =
+        SearchQuery::regex("ONE", false, true, false, Vec::new(), Vec::new()).unwrap();
+    assert_eq!(
+        skipped_search_paths(&project, regex_query, cx).await,
+        Vec::new(),
+        "Files that aren't valid UTF-8 can't match a regex, and shouldn't be reported"
+    );
+
+    // Remove the file without the worktree noticing, so that it can't be read when searched.
+    fs.pause_events();
+    fs.remove_file("/dir/two.rs".as_ref(), Default::default())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        search(&project, query.clone(), cx).await.unwrap(),
+        HashMap::from_iter([("dir/one.rs".to_string(), vec![6..9])]),
+    );
+    assert_eq!(
+        skipped_search_paths(&project, query, cx).await,
+        vec![(
+            ProjectPath {
+                worktree_id,
+                path: Path::new("two.rs").into(),
+            },
+            SearchSkipReason::Unreadable,
+        )],
+        "Files that can't be read should be reported rather than silently left out"
+    );
+}
+
+#[gpui::test]
+async fn test_search_reports_files_matched_filters(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "one.rs": "const ONE: usize = 1;",
+            "two.rs": "const TWO: usize = 2;",
+            "one.ts": "const ONE: number = 1;",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let query = |included_glob: &str| {
+        SearchQuery::text(
+            "ONE",
+            false,
+            true,
+            false,
+            vec![PathMatcher::new(included_glob).unwrap()],
+            Vec::new(),
+        )
+        .unwrap()
+    };
+
+    assert_eq!(
+        files_matched_filters(&project, query("*.rs"), cx).await,
+        Some(2),
+        "Files without matches should be counted when they pass the filters"
+    );
+    assert_eq!(
+        files_matched_filters(&project, query("*.md"), cx).await,
+        Some(0)
+    );
+}
+
 #[test]
 fn test_glob_literal_prefix() {
     assert_eq!(glob_literal_prefix("**/*.js"), "");
@@ -4938,7 +5321,9 @@ async fn search(
             SearchResult::Buffer { buffer, ranges } => {
                 results.entry(buffer).or_insert(ranges);
             }
-            SearchResult::LimitReached => {}
+            SearchResult::Skipped { .. }
+            | SearchResult::FilesMatchedFilters { .. }
+            | SearchResult::LimitReached => {}
         }
     }
     Ok(results
@@ -4961,6 +5346,36 @@ async fn search(
         .collect())
 }
 
+async fn skipped_search_paths(
+    project: &Model<Project>,
+    query: SearchQuery,
+    cx: &mut gpui::TestAppContext,
+) -> Vec<(ProjectPath, SearchSkipReason)> {
+    let mut search_rx = project.update(cx, |project, cx| project.search(query, cx));
+    let mut skipped = Vec::new();
+    while let Some(search_result) = search_rx.next().await {
+        if let SearchResult::Skipped { path, reason } = search_result {
+            skipped.push((path, reason));
+        }
+    }
+    skipped
+}
+
+async fn files_matched_filters(
+    project: &Model<Project>,
+    query: SearchQuery,
+    cx: &mut gpui::TestAppContext,
+) -> Option<usize> {
+    let mut search_rx = project.update(cx, |project, cx| project.search(query, cx));
+    let mut files_matched_filters = None;
+    while let Some(search_result) = search_rx.next().await {
+        if let SearchResult::FilesMatchedFilters { count } = search_result {
+            files_matched_filters = Some(count);
+        }
+    }
+    files_matched_filters
+}
+
 fn init_test(cx: &mut gpui::TestAppContext) {
     if std::env::var("RUST_LOG").is_ok() {
         env_logger::try_init().ok();
@@ -1,16 +1,19 @@
 use aho_corasick::{AhoCorasick, AhoCorasickBuilder};
 use anyhow::{Context, Result};
 use client::proto;
+use collections::HashSet;
 use itertools::Itertools;
-use language::{char_kind, BufferSnapshot};
+use language::{char_kind, BufferSnapshot, Point, Rope};
+use parking_lot::Mutex;
 use regex::{Captures, Regex, RegexBuilder};
 use smol::future::yield_now;
 use std::{
     borrow::Cow,
-    io::{BufRead, BufReader, Read},
+    io::{self, BufRead, BufReader, Read},
     ops::Range,
-    path::Path,
+    path::{Path, PathBuf},
     sync::{Arc, OnceLock},
+    time::{Duration, Instant},
 };
 use util::paths::PathMatcher;
 
@@ -21,6 +24,31 @@ pub struct SearchInputs {
     query: Arc<str>,
     files_to_include: Vec<PathMatcher>,
     files_to_exclude: Vec<PathMatcher>,
+    inversion: Option<Inversion>,
+    languages: Vec<Arc<str>>,
+    git_tracked_only: bool,
+    tracked_files: Option<Arc<HashSet<PathBuf>>>,
+}
+
+/// The error for a query that took longer to match a file than it was allowed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MatchTimedOut;
+
+impl std::fmt::Display for MatchTimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "matching took longer than allowed")
+    }
+}
+
+impl std::error::Error for MatchTimedOut {}
+
+/// What an inverted query finds: the parts of the project that *don't* match its pattern.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Inversion {
+    /// Every non-blank line without a match is a result.
+    Lines,
+    /// Every file without a match is a result, reported as the range of its first line.
+    Files,
 }
 
 impl SearchInputs {
@@ -33,6 +61,15 @@ impl SearchInputs {
     pub fn files_to_exclude(&self) -> &[PathMatcher] {
         &self.files_to_exclude
     }
+    pub fn languages(&self) -> &[Arc<str>] {
+        &self.languages
+    }
+    pub fn git_tracked_only(&self) -> bool {
+        self.git_tracked_only
+    }
+    pub fn tracked_files(&self) -> Option<&HashSet<PathBuf>> {
+        self.tracked_files.as_deref()
+    }
 }
 #[derive(Clone, Debug)]
 pub enum SearchQuery {
@@ -73,6 +110,10 @@ impl SearchQuery {
             query: query.into(),
             files_to_exclude,
             files_to_include,
+            inversion: None,
+            languages: Vec::new(),
+            git_tracked_only: false,
+            tracked_files: None,
         };
         Ok(Self::Text {
             search: Arc::new(search),
@@ -102,7 +143,8 @@ impl SearchQuery {
             query = word_query
         }
 
-        let multiline = query.contains('\n') || query.contains("\\n");
+        let multiline =
+            query.contains('\n') || query.contains("\\n") || enables_multiline_flags(&query);
         let regex = RegexBuilder::new(&query)
             .case_insensitive(!case_sensitive)
             .multi_line(multiline)
@@ -111,6 +153,10 @@ impl SearchQuery {
             query: initial_query,
             files_to_exclude,
             files_to_include,
+            inversion: None,
+            languages: Vec::new(),
+            git_tracked_only: false,
+            tracked_files: None,
         };
         Ok(Self::Regex {
             regex,
@@ -124,7 +170,14 @@ impl SearchQuery {
     }
 
     pub fn from_proto(message: proto::SearchProject) -> Result<Self> {
-        if message.regex {
+        let inversion = if message.invert_files {
+            Some(Inversion::Files)
+        } else if message.invert_lines {
+            Some(Inversion::Lines)
+        } else {
+            None
+        };
+        let query = if message.regex {
             Self::regex(
                 message.query,
                 message.whole_word,
@@ -142,7 +195,16 @@ impl SearchQuery {
                 deserialize_path_matches(&message.files_to_include)?,
                 deserialize_path_matches(&message.files_to_exclude)?,
             )
+        }?;
+        let mut query =
+            query.with_languages(message.languages.into_iter().map(Arc::from).collect());
+        if message.git_tracked_only {
+            query = query.with_git_tracked_only();
         }
+        Ok(match inversion {
+            Some(inversion) => query.with_inversion(inversion),
+            None => query,
+        })
     }
     pub fn with_replacement(mut self, new_replacement: String) -> Self {
         match self {
@@ -159,6 +221,44 @@ impl SearchQuery {
             }
         }
     }
+    /// Makes the query find what doesn't match its pattern, instead of the matches.
+    pub fn with_inversion(mut self, inversion: Inversion) -> Self {
+        match self {
+            Self::Text { ref mut inner, .. } | Self::Regex { ref mut inner, .. } => {
+                inner.inversion = Some(inversion);
+                self
+            }
+        }
+    }
+    /// Restricts the query to files of the given languages, named as in the language registry.
+    pub fn with_languages(mut self, languages: Vec<Arc<str>>) -> Self {
+        match self {
+            Self::Text { ref mut inner, .. } | Self::Regex { ref mut inner, .. } => {
+                inner.languages = languages;
+                self
+            }
+        }
+    }
+    /// Restricts the query to the files git tracks. The project looks up which files those are
+    /// when it runs the search, so that reading the git index doesn't block the caller.
+    pub fn with_git_tracked_only(mut self) -> Self {
+        match self {
+            Self::Text { ref mut inner, .. } | Self::Regex { ref mut inner, .. } => {
+                inner.git_tracked_only = true;
+                self
+            }
+        }
+    }
+    /// Restricts the query to the given files, named the way [`Self::file_matches`] is passed them.
+    /// Set by the project to the files git tracks, for queries made with [`Self::with_git_tracked_only`].
+    pub(crate) fn with_tracked_files(mut self, tracked_files: HashSet<PathBuf>) -> Self {
+        match self {
+            Self::Text { ref mut inner, .. } | Self::Regex { ref mut inner, .. } => {
+                inner.tracked_files = Some(Arc::new(tracked_files));
+                self
+            }
+        }
+    }
     pub fn to_proto(&self, project_id: u64) -> proto::SearchProject {
         proto::SearchProject {
             project_id,
@@ -167,6 +267,9 @@ impl SearchQuery {
             whole_word: self.whole_word(),
             case_sensitive: self.case_sensitive(),
             include_ignored: self.include_ignored(),
+            git_tracked_only: self.git_tracked_only(),
+            invert_lines: self.inversion() == Some(Inversion::Lines),
+            invert_files: self.inversion() == Some(Inversion::Files),
             files_to_include: self
                 .files_to_include()
                 .iter()
@@ -177,20 +280,59 @@ impl SearchQuery {
                 .iter()
                 .map(|matcher| matcher.to_string())
                 .join(","),
+            languages: self
+                .languages()
+                .iter()
+                .map(|language| language.to_string())
+                .collect(),
         }
     }
 
+    /// Returns whether the file may have results for this query. Matches are only checked
+    /// for whole words when searching a buffer, so this can be true for files without results.
     pub fn detect<T: Read>(&self, stream: T) -> Result<bool> {
+        self.detect_until(stream, None)
+    }
+
+    /// Like [`Self::detect`], but fails with [`MatchTimedOut`] once matching has taken longer
+    /// than `timeout`. Regexes search growing windows of each line, or of the whole text when
+    /// multiline, and check the time between windows, so the first window is always searched.
+    /// Text queries check it once per window read from the stream. Reading the stream counts
+    /// towards the time, so files should be loaded before they're matched.
+    pub fn detect_until<T: Read>(&self, stream: T, timeout: Option<Duration>) -> Result<bool> {
         if self.as_str().is_empty() {
             return Ok(false);
         }
 
+        let timer = MatchTimer::new(timeout);
+        let timer = &timer;
+        match self.inversion() {
+            None => self.detect_match(stream, timer),
+            Some(_) if self.whole_word() => Ok(true),
+            Some(Inversion::Files) => Ok(!self.detect_match(stream, timer)?),
+            Some(Inversion::Lines) => match self {
+                Self::Regex {
+                    multiline: true, ..
+                } => Ok(true),
+                Self::Text { search, .. } => {
+                    has_unmatched_line(stream, timer, |line| Ok(search.is_match(line)))
+                }
+                Self::Regex { regex, .. } => has_unmatched_line(stream, timer, |line| {
+                    Ok(find_until(regex, line, 0, timer)?.is_some())
+                }),
+            },
+        }
+    }
+
+    fn detect_match<T: Read>(&self, stream: T, timer: &MatchTimer) -> Result<bool> {
         match self {
             Self::Text { search, .. } => {
-                let mat = search.stream_find_iter(stream).next();
+                let mat = search
+                    .stream_find_iter(TimedReader::new(stream, timer))
+                    .next();
                 match mat {
                     Some(Ok(_)) => Ok(true),
-                    Some(Err(err)) => Err(err.into()),
+                    Some(Err(err)) => Err(timed_out_or(err)),
                     None => Ok(false),
                 }
             }
@@ -203,12 +345,15 @@ impl SearchQuery {
                     if let Err(err) = reader.read_to_string(&mut text) {
                         Err(err.into())
                     } else {
-                        Ok(regex.find(&text).is_some())
+                        Ok(find_until(regex, &text, 0, timer)?.is_some())
                     }
                 } else {
-                    for line in reader.lines() {
+                    for (ix, line) in reader.lines().enumerate() {
+                        if ix > 0 && timer.is_expired() {
+                            return Err(MatchTimedOut.into());
+                        }
                         let line = line?;
-                        if regex.find(&line).is_some() {
+                        if find_until(regex, &line, 0, timer)?.is_some() {
                             return Ok(true);
                         }
                     }
@@ -226,9 +371,13 @@ impl SearchQuery {
         }
     }
     /// Replaces search hits if replacement is set. `text` is assumed to be a string that matches this `SearchQuery` exactly, without any leftovers on either side.
+    /// `$0` in the replacement stands for the whole match, for text queries as well as regexes.
+    /// Text queries expand nothing else, so any other `$`, `$$` included, is kept as written.
     pub fn replacement_for<'a>(&self, text: &'a str) -> Option<Cow<'a, str>> {
         match self {
-            SearchQuery::Text { replacement, .. } => replacement.clone().map(Cow::from),
+            SearchQuery::Text { replacement, .. } => replacement
+                .as_deref()
+                .map(|replacement| expand_whole_match_references(replacement, text).into()),
             SearchQuery::Regex {
                 regex, replacement, ..
             } => {
@@ -255,10 +404,25 @@ impl SearchQuery {
         buffer: &BufferSnapshot,
         subrange: Option<Range<usize>>,
     ) -> Vec<Range<usize>> {
+        self.search_until(buffer, subrange, None)
+            .await
+            .unwrap_or_default()
+    }
+
+    /// Like [`Self::search`], but gives up once matching has taken longer than `timeout`. As
+    /// in [`Self::detect_until`], regexes check the time between the windows they search, and
+    /// text queries between the windows they read. Time spent yielding to other tasks doesn't
+    /// count.
+    pub async fn search_until(
+        &self,
+        buffer: &BufferSnapshot,
+        subrange: Option<Range<usize>>,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Range<usize>>, MatchTimedOut> {
         const YIELD_INTERVAL: usize = 20000;
 
         if self.as_str().is_empty() {
-            return Default::default();
+            return Ok(Default::default());
         }
 
         let range_offset = subrange.as_ref().map(|r| r.start).unwrap_or(0);
@@ -268,20 +432,23 @@ impl SearchQuery {
             buffer.as_rope().clone()
         };
 
+        let timer = MatchTimer::new(timeout);
+        let timer = &timer;
         let mut matches = Vec::new();
         match self {
             Self::Text {
                 search, whole_word, ..
             } => {
-                for (ix, mat) in search
-                    .stream_find_iter(rope.bytes_in_range(0..rope.len()))
-                    .enumerate()
-                {
+                let stream = TimedReader::new(rope.bytes_in_range(0..rope.len()), timer);
+                for (ix, mat) in search.stream_find_iter(stream).enumerate() {
                     if (ix + 1) % YIELD_INTERVAL == 0 {
-                        yield_now().await;
+                        timer.yield_now().await;
                     }
 
-                    let mat = mat.unwrap();
+                    // Reading the rope only fails when the time runs out.
+                    let Ok(mat) = mat else {
+                        return Err(MatchTimedOut);
+                    };
                     if *whole_word {
                         let scope = buffer.language_scope_at(range_offset + mat.start());
                         let kind = |c| char_kind(&scope, c);
@@ -303,27 +470,29 @@ impl SearchQuery {
             } => {
                 if *multiline {
                     let text = rope.to_string();
-                    for (ix, mat) in regex.find_iter(&text).enumerate() {
-                        if (ix + 1) % YIELD_INTERVAL == 0 {
-                            yield_now().await;
+                    let mut text_matches = TimedMatches::new(regex, &text);
+                    while let Some(mat) = text_matches.next(timer)? {
+                        if (matches.len() + 1) % YIELD_INTERVAL == 0 {
+                            timer.yield_now().await;
                         }
-
-                        matches.push(mat.start()..mat.end());
+                        matches.push(mat);
                     }
                 } else {
                     let mut line = String::new();
                     let mut line_offset = 0;
                     for (chunk_ix, chunk) in rope.chunks().chain(["\n"]).enumerate() {
                         if (chunk_ix + 1) % YIELD_INTERVAL == 0 {
-                            yield_now().await;
+                            timer.yield_now().await;
                         }
 
                         for (newline_ix, text) in chunk.split('\n').enumerate() {
                             if newline_ix > 0 {
-                                for mat in regex.find_iter(&line) {
-                                    let start = line_offset + mat.start();
-                                    let end = line_offset + mat.end();
-                                    matches.push(start..end);
+                                if line_offset > 0 && timer.is_expired() {
+                                    return Err(MatchTimedOut);
+                                }
+                                let mut line_matches = TimedMatches::new(regex, &line);
+                                while let Some(mat) = line_matches.next(timer)? {
+                                    matches.push(line_offset + mat.start..line_offset + mat.end);
                                 }
 
                                 line_offset += line.len() + 1;
@@ -336,7 +505,14 @@ impl SearchQuery {
             }
         }
 
-        matches
+        Ok(match self.inversion() {
+            None => matches,
+            Some(Inversion::Lines) => unmatched_line_ranges(&rope, &matches),
+            Some(Inversion::Files) if matches.is_empty() => {
+                vec![0..rope.line_len(0) as usize]
+            }
+            Some(Inversion::Files) => Vec::new(),
+        })
     }
 
     pub fn is_empty(&self) -> bool {
@@ -376,6 +552,36 @@ impl SearchQuery {
         matches!(self, Self::Regex { .. })
     }
 
+    pub fn inversion(&self) -> Option<Inversion> {
+        self.as_inner().inversion
+    }
+
+    /// The pattern a regex query actually runs, after wrapping it for whole word matching,
+    /// with the flags it was built with spelled out as a leading inline group.
+    pub fn regex_source(&self) -> Option<String> {
+        let Self::Regex {
+            regex,
+            multiline,
+            case_sensitive,
+            ..
+        } = self
+        else {
+            return None;
+        };
+        let mut flags = String::new();
+        if !case_sensitive {
+            flags.push('i');
+        }
+        if *multiline {
+            flags.push('m');
+        }
+        Some(if flags.is_empty() {
+            regex.as_str().to_string()
+        } else {
+            format!("(?{flags}){}", regex.as_str())
+        })
+    }
+
     pub fn files_to_include(&self) -> &[PathMatcher] {
         self.as_inner().files_to_include()
     }
@@ -384,9 +590,38 @@ impl SearchQuery {
         self.as_inner().files_to_exclude()
     }
 
+    pub fn languages(&self) -> &[Arc<str>] {
+        self.as_inner().languages()
+    }
+
+    pub fn git_tracked_only(&self) -> bool {
+        self.as_inner().git_tracked_only()
+    }
+
+    pub fn tracked_files(&self) -> Option<&HashSet<PathBuf>> {
+        self.as_inner().tracked_files()
+    }
+
+    /// Whether files of the given language are searched, which is all of them unless the query
+    /// was restricted to some languages.
+    pub fn language_matches(&self, language_name: Option<&str>) -> bool {
+        self.languages().is_empty()
+            || language_name.is_some_and(|language_name| {
+                self.languages()
+                    .iter()
+                    .any(|language| language.eq_ignore_ascii_case(language_name))
+            })
+    }
+
     pub fn file_matches(&self, file_path: Option<&Path>) -> bool {
         match file_path {
             Some(file_path) => {
+                if self
+                    .tracked_files()
+                    .is_some_and(|tracked_files| !tracked_files.contains(file_path))
+                {
+                    return false;
+                }
                 let mut path = file_path.to_path_buf();
                 loop {
                     if self
@@ -407,7 +642,7 @@ impl SearchQuery {
                     }
                 }
             }
-            None => self.files_to_include().is_empty(),
+            None => self.files_to_include().is_empty() && !self.git_tracked_only(),
         }
     }
     pub fn as_inner(&self) -> &SearchInputs {
@@ -429,10 +664,386 @@ fn deserialize_path_matches(glob_set: &str) -> anyhow::Result<Vec<PathMatcher>>
         .collect()
 }
 
+/// Expands `$0` and `${0}` to the whole match. Unlike in the replacement of a regex query,
+/// every other `$` is kept as written, so that `$$` doesn't turn into `$`.
+fn expand_whole_match_references(replacement: &str, text: &str) -> String {
+    let mut expanded = String::with_capacity(replacement.len());
+    let mut rest = replacement;
+    while let Some(ix) = rest.find('$') {
+        expanded.push_str(&rest[..ix]);
+        rest = &rest[ix..];
+        if let Some(after) = rest.strip_prefix("${0}").or_else(|| {
+            rest.strip_prefix("$0")
+                .filter(|after| !after.starts_with(|c: char| c == '_' || c.is_alphanumeric()))
+        }) {
+            expanded.push_str(text);
+            rest = after;
+        } else {
+            expanded.push('$');
+            rest = &rest[1..];
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+/// Measures how long a query has been matching, leaving out the time spent yielding to
+/// other tasks.
+struct MatchTimer {
+    timeout: Option<Duration>,
+    /// When matching started, moved later by the time spent yielding.
+    started_at: Mutex<Instant>,
+}
+
+impl MatchTimer {
+    fn new(timeout: Option<Duration>) -> Self {
+        Self {
+            timeout,
+            started_at: Mutex::new(Instant::now()),
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.timeout
+            .is_some_and(|timeout| self.started_at.lock().elapsed() >= timeout)
+    }
+
+    async fn yield_now(&self) {
+        let yielded_at = Instant::now();
+        yield_now().await;
+        *self.started_at.lock() += yielded_at.elapsed();
+    }
+}
+
+/// How much text a regex searches before the time is first checked. The window doubles each
+/// time it has no match, so a search without matches is at most about twice as slow.
+const MATCH_WINDOW_LEN: usize = 64 * 1024;
+
+/// Finds the regex's first match in `text` at or after `start`, like [`Regex::find_at`], but
+/// searching windows of the text that grow until a match is found, checking the time between
+/// them. A match ending before the edge of a window sees the same text around it as it would
+/// in the whole text, so it's a match there too. It can only differ from the match in the
+/// whole text when that one is longer than the window.
+fn find_until<'t>(
+    regex: &Regex,
+    text: &'t str,
+    start: usize,
+    timer: &MatchTimer,
+) -> Result<Option<regex::Match<'t>>, MatchTimedOut> {
+    let mut window_len = MATCH_WINDOW_LEN;
+    loop {
+        let mut end = start.saturating_add(window_len);
+        if end >= text.len() {
+            return Ok(regex.find_at(text, start));
+        }
+        while !text.is_char_boundary(end) {
+            end += 1;
+        }
+        if let Some(mat) = regex.find_at(&text[..end], start) {
+            if mat.end() < end {
+                return Ok(Some(mat));
+            }
+        }
+        if timer.is_expired() {
+            return Err(MatchTimedOut);
+        }
+        window_len = window_len.saturating_mul(2);
+    }
+}
+
+/// Iterates over the matches of a regex like [`Regex::find_iter`], but finding each one with
+/// [`find_until`].
+struct TimedMatches<'a> {
+    regex: &'a Regex,
+    text: &'a str,
+    offset: usize,
+    last_match_end: Option<usize>,
+}
+
+impl<'a> TimedMatches<'a> {
+    fn new(regex: &'a Regex, text: &'a str) -> Self {
+        Self {
+            regex,
+            text,
+            offset: 0,
+            last_match_end: None,
+        }
+    }
+
+    fn next(&mut self, timer: &MatchTimer) -> Result<Option<Range<usize>>, MatchTimedOut> {
+        while self.offset <= self.text.len() {
+            let Some(mat) = find_until(self.regex, self.text, self.offset, timer)? else {
+                break;
+            };
+            // Step past empty matches so that searching always moves forward.
+            self.offset = if mat.is_empty() {
+                let next_char = self.text[mat.end()..].chars().next();
+                mat.end() + next_char.map_or(1, |c| c.len_utf8())
+            } else {
+                mat.end()
+            };
+            // Like `find_iter`, leave out empty matches right where the previous match ended.
+            if mat.is_empty() && self.last_match_end == Some(mat.end()) {
+                continue;
+            }
+            self.last_match_end = Some(mat.end());
+            return Ok(Some(mat.range()));
+        }
+        Ok(None)
+    }
+}
+
+/// Reads from the stream until the timer runs out, after which reading fails with
+/// [`MatchTimedOut`].
+/// Like regexes, it checks the time once per [`MATCH_WINDOW_LEN`] bytes, so the first window
+/// is always read.
+struct TimedReader<'a, T> {
+    stream: T,
+    timer: &'a MatchTimer,
+    unchecked_len: usize,
+}
+
+impl<'a, T> TimedReader<'a, T> {
+    fn new(stream: T, timer: &'a MatchTimer) -> Self {
+        Self {
+            stream,
+            timer,
+            unchecked_len: 0,
+        }
+    }
+}
+
+impl<T: Read> Read for TimedReader<'_, T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.unchecked_len >= MATCH_WINDOW_LEN {
+            if self.timer.is_expired() {
+                return Err(io::Error::new(io::ErrorKind::Other, MatchTimedOut));
+            }
+            self.unchecked_len = 0;
+        }
+        let len = self.stream.read(buf)?;
+        self.unchecked_len += len;
+        Ok(len)
+    }
+}
+
+/// Turns a read error from a [`TimedReader`] back into [`MatchTimedOut`] when that's its cause.
+fn timed_out_or(error: io::Error) -> anyhow::Error {
+    if error
+        .get_ref()
+        .is_some_and(|inner| inner.is::<MatchTimedOut>())
+    {
+        MatchTimedOut.into()
+    } else {
+        error.into()
+    }
+}
+
+/// Whether the stream has a non-blank line without a match, checking the time between lines.
+fn has_unmatched_line<T: Read>(
+    stream: T,
+    timer: &MatchTimer,
+    is_match: impl Fn(&str) -> Result<bool, MatchTimedOut>,
+) -> Result<bool> {
+    for (ix, line) in BufReader::new(stream).lines().enumerate() {
+        if ix > 0 && timer.is_expired() {
+            return Err(MatchTimedOut.into());
+        }
+        let line = line?;
+        if !is_blank(&line) && !is_match(&line)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// The ranges of the non-blank lines in the rope that no match starts in or spans into.
+fn unmatched_line_ranges(rope: &Rope, matches: &[Range<usize>]) -> Vec<Range<usize>> {
+    let mut matches = matches.iter().peekable();
+    let mut ranges = Vec::new();
+    for row in 0..=rope.max_point().row {
+        let line_start = rope.point_to_offset(Point::new(row, 0));
+        let line_end = line_start + rope.line_len(row) as usize;
+        while matches
+            .peek()
+            .is_some_and(|mat| mat.start < line_start && mat.end <= line_start)
+        {
+            matches.next();
+        }
+        let is_matched = matches.peek().is_some_and(|mat| mat.start <= line_end);
+        let line = rope
+            .chunks_in_range(line_start..line_end)
+            .collect::<String>();
+        if !is_matched && !is_blank(&line) {
+            ranges.push(line_start..line_end);
+        }
+    }
+    ranges
+}
+
+/// Whether the pattern turns on the `m` or `s` flag inline, e.g. `(?s)` or `(?ms:...)`.
+/// Matches of such patterns may span lines, so the text can't be searched line by line.
+fn enables_multiline_flags(pattern: &str) -> bool {
+    pattern.match_indices("(?").any(|(ix, _)| {
+        let group = &pattern[ix + 2..];
+        let Some(flags_end) = group.find([':', ')']) else {
+            return false;
+        };
+        let flags = &group[..flags_end];
+        let enabled_flags = flags.split('-').next().unwrap_or_default();
+        flags.chars().all(|c| "imsRUux-".contains(c)) && enabled_flags.contains(['m', 's'])
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn regex_with_inline_multiline_flags() {
+        for (pattern, expected) in [
+            ("one.two", false),
+            ("(?s)one.two", true),
+            ("(?is:ONE.TWO)", true),
+            ("(?-s)one.two", false),
+            ("(?m)^two$", true),
+            ("(?P<s>one)", false),
+        ] {
+            assert_eq!(
+                enables_multiline_flags(pattern),
+                expected,
+                "unexpected multiline flags for {pattern}"
+            );
+        }
+
+        let query =
+            SearchQuery::regex("(?s)one.two", false, false, false, Vec::new(), Vec::new()).unwrap();
+        assert!(query.detect("one\ntwo".as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn replacement_whole_match_references() {
+        let text_query = SearchQuery::text("TODO", false, true, false, Vec::new(), Vec::new())
+            .unwrap()
+            .with_replacement("// $0 (${0}, $0s, $1, $$)".to_string());
+        assert_eq!(
+            text_query.replacement_for("TODO").as_deref(),
+            Some("// TODO (TODO, $0s, $1, $$)"),
+            "Only whole match references should be expanded in text mode"
+        );
+
+        let regex_query = SearchQuery::regex("TO(DO)", false, true, false, Vec::new(), Vec::new())
+            .unwrap()
+            .with_replacement("// $0 ($1)".to_string());
+        assert_eq!(
+            regex_query.replacement_for("TODO").as_deref(),
+            Some("// TODO (DO)")
+        );
+    }
+
+    #[test]
+    fn inverted_queries() {
+        let text = "one two\n\nthree\nfour one\nfive";
+        let query = SearchQuery::text("one", false, false, false, Vec::new(), Vec::new()).unwrap();
+        let lines = query.clone().with_inversion(Inversion::Lines);
+        let files = query.with_inversion(Inversion::Files);
+        assert!(lines.detect(text.as_bytes()).unwrap());
+        assert!(!lines.detect("one\n\nONE".as_bytes()).unwrap());
+        assert!(!files.detect(text.as_bytes()).unwrap());
+        assert!(files.detect("two".as_bytes()).unwrap());
+
+        let rope = Rope::from(text);
+        assert_eq!(
+            unmatched_line_ranges(&rope, &[0..3, 20..23]),
+            vec![9..14, 24..28],
+            "Blank lines and lines with a match should be left out"
+        );
+        assert_eq!(
+            unmatched_line_ranges(&rope, &[4..11]),
+            vec![15..23, 24..28],
+            "Lines that a match spans into should be left out"
+        );
+    }
+
+    #[test]
+    fn expired_timeouts_stop_matching_after_the_first_line() {
+        let timeout = Some(Duration::ZERO);
+        let regex =
+            SearchQuery::regex("three", false, false, false, Vec::new(), Vec::new()).unwrap();
+        let lines = SearchQuery::text("one", false, false, false, Vec::new(), Vec::new())
+            .unwrap()
+            .with_inversion(Inversion::Lines);
+        for query in [regex, lines] {
+            assert!(query
+                .detect_until("one\ntwo\nthree".as_bytes(), timeout)
+                .unwrap_err()
+                .is::<MatchTimedOut>());
+        }
+        assert!(
+            SearchQuery::regex("one", false, false, false, Vec::new(), Vec::new())
+                .unwrap()
+                .detect_until("one\ntwo".as_bytes(), timeout)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn expired_timeouts_stop_matching_within_long_texts() {
+        let timeout = Some(Duration::ZERO);
+        // Far longer than a single match window, ending in a character that makes the pattern
+        // backtrack through every run of `a`s.
+        let text = format!("{}b", "a".repeat(200_000));
+        for pattern in ["(a+)+$", "(?m)(a+)+$\\n"] {
+            let query =
+                SearchQuery::regex(pattern, false, true, false, Vec::new(), Vec::new()).unwrap();
+            assert!(
+                query
+                    .detect_until(text.as_bytes(), timeout)
+                    .unwrap_err()
+                    .is::<MatchTimedOut>(),
+                "{pattern} should time out"
+            );
+            assert!(!query.detect_until(text.as_bytes(), None).unwrap());
+        }
+
+        let query = SearchQuery::text("b", false, true, false, Vec::new(), Vec::new()).unwrap();
+        assert!(query
+            .detect_until(text.as_bytes(), timeout)
+            .unwrap_err()
+            .is::<MatchTimedOut>());
+        assert!(query.detect_until(text.as_bytes(), None).unwrap());
+    }
+
+    #[test]
+    fn regex_source_includes_transforms() {
+        let query = |pattern: &str, whole_word, case_sensitive| {
+            SearchQuery::regex(
+                pattern,
+                whole_word,
+                case_sensitive,
+                false,
+                Vec::new(),
+                Vec::new(),
+            )
+            .unwrap()
+            .regex_source()
+        };
+        assert_eq!(query("a|b", false, true).as_deref(), Some("a|b"));
+        assert_eq!(query("a|b", true, false).as_deref(), Some("(?i)\\ba|b\\b"));
+        assert_eq!(
+            query("one\\ntwo", false, true).as_deref(),
+            Some("(?m)one\\ntwo")
+        );
+
+        let text_query =
+            SearchQuery::text("a|b", false, false, false, Vec::new(), Vec::new()).unwrap();
+        assert_eq!(text_query.regex_source(), None);
+    }
+
     #[test]
     fn path_matcher_creation_for_valid_paths() {
         for valid_path in [
@@ -35,6 +35,7 @@ use util::{maybe, NumericPrefixWithSuffix, ResultExt, TryFutureExt};
 use workspace::{
     dock::{DockPosition, Panel, PanelEvent},
     notifications::DetachAndPromptErr,
+    searchable::SearchableItemHandle,
     OpenInTerminal, Workspace,
 };
 
@@ -59,6 +60,9 @@ pub struct ProjectPanel {
     workspace: WeakView<Workspace>,
     width: Option<Pixels>,
     pending_serialization: Task<Option<()>>,
+    /// The number of matches under each directory for the workspace's active project search.
+    search_match_counts: HashMap<ProjectPath, usize>,
+    _project_search_subscription: Option<Subscription>,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -103,6 +107,7 @@ pub struct EntryDetails {
     is_cut: bool,
     git_status: Option<GitFileStatus>,
     is_dotenv: bool,
+    search_match_count: Option<usize>,
 }
 
 #[derive(PartialEq, Clone, Default, Debug, Deserialize)]
@@ -138,6 +143,8 @@ actions!(
         OpenPermanent,
         ToggleFocus,
         NewSearchInDirectory,
+        NewSearchInDirectoryForSelection,
+        NewSearchInFile,
         UnfoldDirectory,
         FoldDirectory,
         SelectParent,
@@ -187,10 +194,18 @@ struct DraggedProjectEntryView {
 impl ProjectPanel {
     fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
         let project = workspace.project().clone();
+        let workspace_handle = cx.view().clone();
         let project_panel = cx.new_view(|cx: &mut ViewContext<Self>| {
             let focus_handle = cx.focus_handle();
             cx.on_focus(&focus_handle, Self::focus_in).detach();
 
+            cx.subscribe(&workspace_handle, |this, workspace, event, cx| {
+                if let workspace::Event::ActiveItemChanged = event {
+                    this.observe_project_search(workspace, cx);
+                }
+            })
+            .detach();
+
             cx.subscribe(&project, |this, project, event, cx| match event {
                 project::Event::ActiveEntryChanged(Some(entry_id)) => {
                     if ProjectPanelSettings::get_global(cx).auto_reveal_entries {
@@ -271,6 +286,8 @@ impl ProjectPanel {
                 workspace: workspace.weak_handle(),
                 width: None,
                 pending_serialization: Task::ready(None),
+                search_match_counts: HashMap::default(),
+                _project_search_subscription: None,
             };
             this.update_visible_entries(None, cx);
 
@@ -439,6 +456,9 @@ impl ProjectPanel {
                             .when(is_dir, |menu| {
                                 menu.action("Search Inside", Box::new(NewSearchInDirectory))
                             })
+                            .when(!is_dir, |menu| {
+                                menu.action("Search Inside", Box::new(NewSearchInFile))
+                            })
                     },
                     |menu| {
                         menu.action("New File", Box::new(NewFile))
@@ -449,6 +469,14 @@ impl ProjectPanel {
                             .when(is_dir, |menu| {
                                 menu.separator()
                                     .action("Find in Folder…", Box::new(NewSearchInDirectory))
+                                    .action(
+                                        "Find Selection in Folder",
+                                        Box::new(NewSearchInDirectoryForSelection),
+                                    )
+                            })
+                            .when(!is_dir, |menu| {
+                                menu.separator()
+                                    .action("Find in File…", Box::new(NewSearchInFile))
                             })
                             .when(is_unfoldable, |menu| {
                                 menu.action("Unfold Directory", Box::new(UnfoldDirectory))
@@ -1200,21 +1228,61 @@ impl ProjectPanel {
         _: &NewSearchInDirectory,
         cx: &mut ViewContext<Self>,
     ) {
+        self.search_in_selected_directory(false, cx);
+    }
+
+    fn new_search_in_directory_for_selection(
+        &mut self,
+        _: &NewSearchInDirectoryForSelection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.search_in_selected_directory(true, cx);
+    }
+
+    fn new_search_in_file(&mut self, _: &NewSearchInFile, cx: &mut ViewContext<Self>) {
+        if let Some((worktree, entry)) = self.selected_entry(cx) {
+            if entry.is_file() {
+                let file_path = self.search_path(worktree, entry, cx);
+                self.workspace
+                    .update(cx, |workspace, cx| {
+                        search::ProjectSearchView::new_search_in_file(workspace, &file_path, cx);
+                    })
+                    .ok();
+            }
+        }
+    }
+
+    /// Returns the path to filter a project search by to search within the given entry,
+    /// which includes the worktree's root name when there are several worktrees.
+    fn search_path(&self, worktree: &Worktree, entry: &Entry, cx: &AppContext) -> Arc<Path> {
+        let include_root = self.project.read(cx).visible_worktrees(cx).count() > 1;
+        if include_root {
+            let mut full_path = PathBuf::from(worktree.root_name());
+            full_path.push(&entry.path);
+            Arc::from(full_path)
+        } else {
+            entry.path.clone()
+        }
+    }
+
+    fn search_in_selected_directory(&mut self, query_selection: bool, cx: &mut ViewContext<Self>) {
         if let Some((worktree, entry)) = self.selected_entry(cx) {
             if entry.is_dir() {
-                let include_root = self.project.read(cx).visible_worktrees(cx).count() > 1;
-                let dir_path = if include_root {
-                    let mut full_path = PathBuf::from(worktree.root_name());
-                    full_path.push(&entry.path);
-                    Arc::from(full_path)
-                } else {
-                    entry.path.clone()
-                };
+                let dir_path = self.search_path(worktree, entry, cx);
 
                 self.workspace
                     .update(cx, |workspace, cx| {
-                        search::ProjectSearchView::new_search_in_directory(
-                            workspace, &dir_path, cx,
+                        let query = workspace
+                            .active_item(cx)
+                            .filter(|_| query_selection)
+                            .and_then(|item| item.act_as::<Editor>(cx))
+                            .map(|editor| editor.query_suggestion(cx))
+                            .filter(|query| !query.is_empty());
+                        search::ProjectSearchView::new_search_in_directory_with_query(
+                            workspace,
+                            &dir_path,
+                            query.as_deref(),
+                            cx,
                         );
                     })
                     .ok();
@@ -1387,6 +1455,7 @@ impl ProjectPanel {
                         path: entry.path.join("\0").into(),
                         inode: 0,
                         mtime: entry.mtime,
+                        size: 0,
                         is_symlink: false,
                         is_ignored: entry.is_ignored,
                         is_external: false,
@@ -1589,6 +1658,16 @@ impl ProjectPanel {
                             .map_or(false, |e| e.is_cut() && e.entry_id() == entry.id),
                         git_status: status,
                         is_dotenv: entry.is_private,
+                        search_match_count: if entry.is_dir() {
+                            self.search_match_counts
+                                .get(&ProjectPath {
+                                    worktree_id: snapshot.id(),
+                                    path: entry.path.clone(),
+                                })
+                                .copied()
+                        } else {
+                            None
+                        },
                     };
 
                     if let Some(edit_state) = &self.edit_state {
@@ -1676,6 +1755,7 @@ impl ProjectPanel {
         let file_name = details.filename.clone();
         let icon = details.icon.clone();
         let depth = details.depth;
+        let search_match_count = details.search_match_count;
         div()
             .id(entry_id.to_proto() as usize)
             .on_drag(entry_id, move |entry_id, cx| {
@@ -1716,6 +1796,11 @@ impl ProjectPanel {
                         }
                         .ml_1(),
                     )
+                    .end_slot::<Label>(search_match_count.map(|count| {
+                        Label::new(count.to_string())
+                            .size(LabelSize::XSmall)
+                            .color(Color::Muted)
+                    }))
                     .on_click(cx.listener(move |this, event: &gpui::ClickEvent, cx| {
                         if event.down.button == MouseButton::Right || event.down.first_mouse {
                             return;
@@ -1749,6 +1834,33 @@ impl ProjectPanel {
             )
     }
 
+    /// Follows the matches of the workspace's active item, if it's a project search.
+    fn observe_project_search(&mut self, workspace: View<Workspace>, cx: &mut ViewContext<Self>) {
+        let search_view = workspace
+            .read(cx)
+            .active_item_as::<search::ProjectSearchView>(cx);
+        self._project_search_subscription = search_view.as_ref().map(|search_view| {
+            cx.observe(search_view, |this, search_view, cx| {
+                this.update_search_match_counts(Some(&search_view), cx);
+            })
+        });
+        self.update_search_match_counts(search_view.as_ref(), cx);
+    }
+
+    fn update_search_match_counts(
+        &mut self,
+        search_view: Option<&View<search::ProjectSearchView>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let search_match_counts = search_view
+            .map(|search_view| search_view.read(cx).match_counts_by_directory(cx))
+            .unwrap_or_default();
+        if self.search_match_counts != search_match_counts {
+            self.search_match_counts = search_match_counts;
+            cx.notify();
+        }
+    }
+
     fn dispatch_context(&self, cx: &ViewContext<Self>) -> KeyContext {
         let mut dispatch_context = KeyContext::new_with_defaults();
         dispatch_context.add("ProjectPanel");
@@ -1816,6 +1928,8 @@ impl Render for ProjectPanel {
                 .on_action(cx.listener(Self::copy_path))
                 .on_action(cx.listener(Self::copy_relative_path))
                 .on_action(cx.listener(Self::new_search_in_directory))
+                .on_action(cx.listener(Self::new_search_in_directory_for_selection))
+                .on_action(cx.listener(Self::new_search_in_file))
                 .on_action(cx.listener(Self::unfold_directory))
                 .on_action(cx.listener(Self::fold_directory))
                 .when(!project.is_read_only(), |el| {
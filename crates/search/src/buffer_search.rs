@@ -2024,6 +2024,47 @@ mod tests {
         .await;
     }
 
+    #[gpui::test]
+    async fn test_replace_with_whole_match(cx: &mut TestAppContext) {
+        let (editor, search_bar, cx) = init_test(cx);
+
+        run_replacement_test(ReplacementTestParams {
+            editor: &editor,
+            search_bar: &search_bar,
+            cx,
+            search_text: "expression",
+            search_options: None,
+            replacement_text: "<$0>",
+            replace_all: true,
+            expected_text: r#"
+            A regular <expression> (shortened as regex or regexp;[1] also referred to as
+            rational <expression>[2][3]) is a sequence of characters that specifies a search
+            pattern in text. Usually such patterns are used by string-searching algorithms
+            for "find" or "find and replace" operations on strings, or for input validation.
+            "#
+            .unindent(),
+        })
+        .await;
+
+        run_replacement_test(ReplacementTestParams {
+            editor: &editor,
+            search_bar: &search_bar,
+            cx,
+            search_text: r"\bregexp?\b",
+            search_options: Some(SearchOptions::REGEX),
+            replacement_text: "`$0`",
+            replace_all: true,
+            expected_text: r#"
+            A regular <expression> (shortened as `regex` or `regexp`;[1] also referred to as
+            rational <expression>[2][3]) is a sequence of characters that specifies a search
+            pattern in text. Usually such patterns are used by string-searching algorithms
+            for "find" or "find and replace" operations on strings, or for input validation.
+            "#
+            .unindent(),
+        })
+        .await;
+    }
+
     #[gpui::test]
     async fn test_invalid_regexp_search_after_valid(cx: &mut TestAppContext) {
         let (editor, search_bar, cx) = init_test(cx);
@@ -0,0 +1,203 @@
+use anyhow::Result;
+use editor::{CompletionProvider, Editor};
+use gpui::{AppContext, Model, Task, ViewContext};
+use language::{Buffer, CodeLabel, LanguageServerId, ToOffset};
+use parking_lot::RwLock;
+use project::{Completion, Project};
+use std::{ops::Range, path::Path, sync::Arc};
+
+/// Completes worktree paths in the include and exclude filter editors of project search.
+pub(crate) struct PathFilterCompletionProvider {
+    project: Model<Project>,
+}
+
+impl PathFilterCompletionProvider {
+    /// Makes the given filter editor suggest project paths, also when a `/` is typed.
+    pub(crate) fn register(
+        editor: &mut Editor,
+        project: Model<Project>,
+        cx: &mut ViewContext<Editor>,
+    ) {
+        editor.set_completion_provider(Box::new(Self { project }));
+        if let Some(buffer) = editor.buffer().read(cx).as_singleton() {
+            buffer.update(cx, |buffer, cx| {
+                buffer.set_completion_triggers(vec!["/".to_string()], cx)
+            });
+        }
+    }
+}
+
+impl CompletionProvider for PathFilterCompletionProvider {
+    fn completions(
+        &self,
+        buffer: &Model<Buffer>,
+        buffer_position: language::Anchor,
+        cx: &mut ViewContext<Editor>,
+    ) -> Task<Result<Vec<Completion>>> {
+        let buffer = buffer.read(cx);
+        let text = buffer.text();
+        let cursor = buffer_position.to_offset(buffer);
+        // Filters are comma-separated, so only the glob under the cursor is completed.
+        let glob_start = text[..cursor].rfind(',').map_or(0, |ix| ix + 1);
+        let glob = text[glob_start..cursor].trim_start();
+        let old_range = buffer.anchor_before(cursor - glob.len())..buffer_position;
+
+        let completions = path_completions(self.project.read(cx), glob, cx)
+            .into_iter()
+            .map(|completion| Completion {
+                old_range: old_range.clone(),
+                label: CodeLabel {
+                    text: completion.new_text.clone(),
+                    runs: Vec::new(),
+                    filter_range: completion.name_range,
+                },
+                new_text: completion.new_text,
+                documentation: None,
+                server_id: LanguageServerId(0),
+                lsp_completion: Default::default(),
+            })
+            .collect();
+        Task::ready(Ok(completions))
+    }
+
+    fn resolve_completions(
+        &self,
+        _completion_indices: Vec<usize>,
+        _completions: Arc<RwLock<Box<[Completion]>>>,
+        _cx: &mut ViewContext<Editor>,
+    ) -> Task<Result<bool>> {
+        Task::ready(Ok(false))
+    }
+
+    fn apply_additional_edits_for_completion(
+        &self,
+        _buffer: Model<Buffer>,
+        _completion: Completion,
+        _push_to_history: bool,
+        _cx: &mut ViewContext<Editor>,
+    ) -> Task<Result<Option<language::Transaction>>> {
+        Task::ready(Ok(None))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct PathCompletion {
+    /// The glob replacing the one being typed.
+    pub new_text: String,
+    /// The range of the entry's own name within `new_text`, used to filter completions.
+    pub name_range: Range<usize>,
+}
+
+/// Suggests the entries of the directory that the partially typed `glob` points into.
+/// Directories complete to a glob matching everything inside of them.
+///
+/// With several worktrees, paths start with the worktree's root name, like search results do.
+pub(crate) fn path_completions(
+    project: &Project,
+    glob: &str,
+    cx: &AppContext,
+) -> Vec<PathCompletion> {
+    let (dir, _) = glob.rsplit_once('/').unwrap_or(("", glob));
+    let dir_prefix = if dir.is_empty() {
+        String::new()
+    } else {
+        format!("{dir}/")
+    };
+    let worktrees = project
+        .visible_worktrees(cx)
+        .map(|worktree| worktree.read(cx))
+        .collect::<Vec<_>>();
+    let include_root = worktrees.len() > 1;
+
+    let mut completions = Vec::new();
+    for worktree in worktrees {
+        let parent = if include_root {
+            if dir.is_empty() {
+                completions.push(directory_completion(&dir_prefix, worktree.root_name()));
+                continue;
+            }
+            match Path::new(dir).strip_prefix(worktree.root_name()) {
+                Ok(parent) => parent,
+                Err(_) => continue,
+            }
+        } else {
+            Path::new(dir)
+        };
+
+        for entry in worktree.child_entries(parent) {
+            let Some(name) = entry.path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if entry.is_dir() {
+                completions.push(directory_completion(&dir_prefix, name));
+            } else {
+                completions.push(PathCompletion {
+                    new_text: format!("{dir_prefix}{name}"),
+                    name_range: dir_prefix.len()..dir_prefix.len() + name.len(),
+                });
+            }
+        }
+    }
+    completions
+}
+
+fn directory_completion(dir_prefix: &str, name: &str) -> PathCompletion {
+    PathCompletion {
+        new_text: format!("{dir_prefix}{name}/**"),
+        name_range: dir_prefix.len()..dir_prefix.len() + name.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    async fn test_path_completions(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings = SettingsStore::test(cx);
+            cx.set_global(settings);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "crates": {
+                    "search": { "search.rs": "" },
+                    "Cargo.toml": "",
+                },
+                "README.md": "",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+        let new_texts = |glob: &str, cx: &mut TestAppContext| {
+            project.read_with(cx, |project, cx| {
+                path_completions(project, glob, cx)
+                    .into_iter()
+                    .map(|completion| completion.new_text)
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(new_texts("", cx), ["README.md", "crates/**"]);
+        assert_eq!(
+            new_texts("crates/se", cx),
+            ["crates/Cargo.toml", "crates/search/**"],
+            "Entries of the typed directory should be suggested, leaving filtering to the menu"
+        );
+        assert_eq!(new_texts("missing/", cx), Vec::<String>::new());
+        assert_eq!(
+            project.read_with(cx, |project, cx| path_completions(project, "crates/", cx))[1]
+                .name_range,
+            7..13
+        );
+    }
+}
@@ -1,60 +1,307 @@
 use crate::{
+    path_completion::PathFilterCompletionProvider,
+    search_settings::{PathDisplay, SearchSettings},
     FocusSearch, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll, ReplaceNext, SearchOptions,
-    SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive, ToggleIncludeIgnored, ToggleRegex,
-    ToggleReplace, ToggleWholeWord,
+    SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive, ToggleGitTrackedOnly,
+    ToggleIncludeIgnored, ToggleInvertFiles, ToggleInvertMatches, ToggleRegex, ToggleReplace,
+    ToggleWholeWord,
 };
 use anyhow::Context as _;
 use collections::{HashMap, HashSet};
+use db::kvp::KEY_VALUE_STORE;
 use editor::{
-    actions::SelectAll,
+    actions::{GoToDefinition, SelectAll},
+    display_map::{BlockDisposition, BlockId, BlockProperties, BlockStyle, RenderBlock},
     items::active_match_index,
     scroll::{Autoscroll, Axis},
-    Anchor, Editor, EditorElement, EditorEvent, EditorStyle, MultiBuffer, MAX_TAB_TITLE_LEN,
+    Anchor, Bias, Editor, EditorElement, EditorEvent, EditorStyle, ExcerptId, MultiBuffer,
+    MultiBufferSnapshot, ToPoint as _, MAX_TAB_TITLE_LEN,
 };
 use gpui::{
-    actions, div, Action, AnyElement, AnyView, AppContext, Context as _, Element, EntityId,
-    EventEmitter, FocusHandle, FocusableView, FontStyle, FontWeight, Global, Hsla,
-    InteractiveElement, IntoElement, Model, ModelContext, ParentElement, Point, Render,
-    SharedString, Styled, Subscription, Task, TextStyle, View, ViewContext, VisualContext,
-    WeakModel, WeakView, WhiteSpace, WindowContext,
+    actions, div, impl_actions, percentage, Action, Animation, AnimationExt, AnyElement, AnyView,
+    AppContext, ClipboardItem, Context as _, DismissEvent, Element, EntityId, EventEmitter,
+    FocusHandle, FocusableView, FontStyle, FontWeight, Global, HighlightStyle, Hsla,
+    InteractiveElement, IntoElement, Model, ModelContext, ParentElement, Point, PromptLevel,
+    Render, SharedString, Styled, Subscription, Task, TextStyle, Transformation, View, ViewContext,
+    VisualContext, WeakModel, WeakView, WhiteSpace, WindowContext,
 };
+use language::{Buffer, ToPoint as _};
 use menu::Confirm;
-use project::{search::SearchQuery, search_history::SearchHistoryCursor, Project, ProjectPath};
-use settings::Settings;
+use project::{
+    search::{Inversion, SearchQuery},
+    search_history::SearchHistoryCursor,
+    Project, ProjectPath, SearchSkipReason,
+};
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsStore};
 use smol::stream::StreamExt;
 use std::{
     any::{Any, TypeId},
     mem,
     ops::{Not, Range},
-    path::Path,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
 };
 use theme::ThemeSettings;
 use ui::{
-    h_flex, prelude::*, v_flex, Icon, IconButton, IconName, Label, LabelCommon, LabelSize,
-    Selectable, Tooltip,
+    h_flex, popover_menu, prelude::*, v_flex, ContextMenu, Icon, IconButton, IconName, Label,
+    LabelCommon, LabelSize, ListItem, ListItemSpacing, Selectable, Tooltip,
 };
-use util::paths::PathMatcher;
+use util::{paths::PathMatcher, ResultExt, TryFutureExt};
 use workspace::{
     item::{BreadcrumbText, Item, ItemEvent, ItemHandle, TabContentParams},
     searchable::{Direction, SearchableItem, SearchableItemHandle},
-    DeploySearch, ItemNavHistory, NewSearch, Pane, ToolbarItemEvent, ToolbarItemLocation,
-    ToolbarItemView, Workspace, WorkspaceId,
+    DeploySearch, ItemNavHistory, ModalView, NewSearch, Pane, SplitDirection, ToolbarItemEvent,
+    ToolbarItemLocation, ToolbarItemView, Workspace, WorkspaceId,
 };
 
 const MIN_INPUT_WIDTH_REMS: f32 = 15.;
 const MAX_INPUT_WIDTH_REMS: f32 = 30.;
+/// How often observers are notified about results while a search streams them in.
+const STREAMING_NOTIFY_INTERVAL: Duration = Duration::from_millis(33);
+/// How long edits to the results must pause before their matches are searched again.
+const EDITED_RESULTS_REFRESH_DEBOUNCE: Duration = Duration::from_millis(250);
 
 actions!(
     project_search,
-    [SearchInNew, ToggleFocus, NextField, ToggleFilters]
+    [
+        SearchInNew,
+        ToggleFocus,
+        NextField,
+        ToggleFilters,
+        TogglePinned,
+        CopyMatchReference,
+        ToggleEditResults,
+        GoToMatchDefinition,
+        SaveFiltersAsDefault,
+        ResetFilters,
+        ExcludeMatchFile,
+        ToggleSearchAllWindows,
+        Dismiss,
+        SearchSelections,
+        ToggleLiteral,
+        OpenMatchInSplit,
+        RerunSearch,
+        ToggleExcludeCurrentFile,
+        SelectNextFile,
+        SelectPrevFile,
+        ResetSearchOptions,
+        ReplaceMatchAtCursor,
+        ExpandTruncatedLines,
+        SwapIncludeExclude,
+        PeekSearch
+    ]
 );
 
+/// Saves the active search as a preset of the project. Without a name, the preset is named after
+/// the query.
+#[derive(PartialEq, Clone, Default, Deserialize)]
+pub struct SaveSearchPreset {
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// Opens a new search from the project's preset with the given name, and runs it.
+#[derive(PartialEq, Clone, Deserialize)]
+pub struct ApplySearchPreset {
+    pub name: String,
+}
+
+impl_actions!(project_search, [SaveSearchPreset, ApplySearchPreset]);
+
+const SEARCH_FILTERS_KEY: &str = "ProjectSearchFilters";
+const SEARCH_PRESETS_KEY: &str = "ProjectSearchPresets";
+/// The most matches a peek search shows.
+const PEEK_SEARCH_MATCH_LIMIT: usize = 5;
+
+/// Builds a query matching any of the given selected texts, and whether it is a regex.
+/// Several distinct texts are escaped and joined into a regex alternation.
+fn selections_query(selected_texts: &[String]) -> Option<(String, bool)> {
+    let mut texts = Vec::new();
+    for text in selected_texts {
+        if !texts.contains(&text) {
+            texts.push(text);
+        }
+    }
+    match texts.as_slice() {
+        [] => None,
+        [text] => Some((text.to_string(), false)),
+        texts => Some((
+            texts
+                .iter()
+                .map(|text| regex::escape(text))
+                .collect::<Vec<_>>()
+                .join("|"),
+            true,
+        )),
+    }
+}
+
+/// The path of a matched file as configured by the `search.path_display` setting. Relative
+/// paths name the worktree when the project has several, like those in the filters.
+fn match_path(project: &Project, file: &dyn language::File, cx: &AppContext) -> PathBuf {
+    match SearchSettings::get_global(cx).path_display {
+        PathDisplay::Absolute => file
+            .as_local()
+            .map(|file| file.abs_path(cx))
+            .unwrap_or_else(|| file.full_path(cx)),
+        PathDisplay::Relative => filter_path(project, file, cx),
+    }
+}
+
+fn query_placeholder(options: SearchOptions) -> &'static str {
+    if options.contains(SearchOptions::REGEX) {
+        "Regex search all files.."
+    } else {
+        "Search all files.."
+    }
+}
+
+/// Returns the index of the first match in the file after or before the one containing the
+/// match at `index`, wrapping around at either end. Matches are grouped by file, in order.
+fn file_match_index(match_ranges: &[Range<Anchor>], index: usize, direction: Direction) -> usize {
+    let buffer_id = |index: usize| match_ranges[index].start.buffer_id;
+    let file_start = |mut index: usize| {
+        while index > 0 && buffer_id(index - 1) == buffer_id(index) {
+            index -= 1;
+        }
+        index
+    };
+
+    match direction {
+        Direction::Next => (index + 1..match_ranges.len())
+            .find(|&next| buffer_id(next) != buffer_id(index))
+            .unwrap_or(0),
+        Direction::Prev => match file_start(index) {
+            0 => file_start(match_ranges.len() - 1),
+            start => file_start(start - 1),
+        },
+    }
+}
+
+/// A match found by [`search_project`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProjectMatch {
+    pub path: ProjectPath,
+    /// The zero-based row of the match's start.
+    pub line: u32,
+    /// The zero-based column of the match's start, in bytes.
+    pub column: u32,
+    pub text: String,
+}
+
+/// Searches the project without a view, resolving to every match in the order of the files'
+/// paths, or to the first `limit` of them. Unlike a `ProjectSearch`, no excerpts are built for
+/// the results, so this suits tools that only need the locations of the matches.
+pub fn search_project(
+    project: &Model<Project>,
+    query: SearchQuery,
+    limit: Option<usize>,
+    cx: &mut AppContext,
+) -> Task<Vec<ProjectMatch>> {
+    let mut results = project.update(cx, |project, cx| project.search(query, cx));
+    cx.spawn(|cx| async move {
+        let mut matches = Vec::new();
+        while let Some(result) = results.next().await {
+            let project::SearchResult::Buffer { buffer, ranges } = result else {
+                continue;
+            };
+            buffer
+                .read_with(&cx, |buffer, cx| {
+                    let Some(file) = buffer.file() else {
+                        return;
+                    };
+                    let path = ProjectPath {
+                        worktree_id: file.worktree_id(cx),
+                        path: file.path().clone(),
+                    };
+                    matches.extend(ranges.into_iter().map(|range| {
+                        let start = range.start.to_point(buffer);
+                        ProjectMatch {
+                            path: path.clone(),
+                            line: start.row,
+                            column: start.column,
+                            text: buffer.text_for_range(range).collect(),
+                        }
+                    }));
+                })
+                .log_err();
+            if let Some(limit) = limit.filter(|&limit| matches.len() >= limit) {
+                // Dropping the results stops the search.
+                matches.truncate(limit);
+                break;
+            }
+        }
+        matches
+    })
+}
+
+/// The given project, followed by the projects of all other open workspace windows.
+fn open_projects(project: &Model<Project>, cx: &AppContext) -> Vec<Model<Project>> {
+    let mut projects = vec![project.clone()];
+    for window in cx.windows() {
+        let Some(workspace) = window
+            .downcast::<Workspace>()
+            .and_then(|workspace| workspace.read(cx).ok())
+        else {
+            continue;
+        };
+        if !projects.contains(workspace.project()) {
+            projects.push(workspace.project().clone());
+        }
+    }
+    projects
+}
+
+/// The path of the file as it's written in include and exclude filters, which only name the
+/// worktree when the project has several.
+fn filter_path(project: &Project, file: &dyn language::File, cx: &AppContext) -> PathBuf {
+    if project.visible_worktrees(cx).count() > 1 {
+        file.full_path(cx)
+    } else {
+        file.path().to_path_buf()
+    }
+}
+
+/// Include and exclude filters saved as the defaults for new searches in a project.
+#[derive(Serialize, Deserialize)]
+struct SerializedSearchFilters {
+    included: String,
+    excluded: String,
+}
+
+/// A search saved under a name for a project, so that it can be run again later.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct SearchPreset {
+    name: String,
+    query: String,
+    options: u8,
+    /// Whether a regex query is searched for literally. Presets saved before this was
+    /// remembered search the query as written.
+    #[serde(default)]
+    literal: bool,
+    included: String,
+    excluded: String,
+    /// Whether the file a search was opened from is left out of its results.
+    #[serde(default)]
+    exclude_origin_file: bool,
+}
+
+/// Text highlight key for the context lines faded out by `SearchSettings::dim_context_lines`.
+enum DimmedContextLines {}
+
+/// Background highlight key for the active match, which the scrollbar marks in a stronger color
+/// than the other matches.
+enum ActiveMatchHighlight {}
+
 #[derive(Default)]
 struct ActiveSettings(HashMap<WeakModel<Project>, ProjectSearchSettings>);
 
 impl Global for ActiveSettings {}
 
 pub fn init(cx: &mut AppContext) {
+    SearchSettings::register(cx);
     cx.set_global(ActiveSettings::default());
     cx.observe_new_views(|workspace: &mut Workspace, _cx| {
         register_workspace_action(workspace, move |search_bar, _: &FocusSearch, cx| {
@@ -63,6 +310,51 @@ pub fn init(cx: &mut AppContext) {
         register_workspace_action(workspace, move |search_bar, _: &ToggleFilters, cx| {
             search_bar.toggle_filters(cx);
         });
+        register_workspace_action(workspace, move |search_bar, _: &TogglePinned, cx| {
+            search_bar.toggle_pinned(cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &ToggleEditResults, cx| {
+            search_bar.toggle_edit_results(cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &ToggleLiteral, cx| {
+            search_bar.toggle_literal(cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &RerunSearch, cx| {
+            search_bar.rerun_search(cx);
+        });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleExcludeCurrentFile, cx| {
+                search_bar.toggle_exclude_origin_file(cx);
+            },
+        );
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &SaveFiltersAsDefault, cx| {
+                search_bar.save_filters_as_default(cx);
+            },
+        );
+        register_workspace_action(
+            workspace,
+            move |search_bar, action: &SaveSearchPreset, cx| {
+                search_bar.save_search_preset(action, cx);
+            },
+        );
+        register_workspace_action(workspace, move |search_bar, _: &ResetFilters, cx| {
+            search_bar.reset_filters(cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &ResetSearchOptions, cx| {
+            search_bar.reset_search_options(cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &SwapIncludeExclude, cx| {
+            search_bar.swap_include_exclude(cx);
+        });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleSearchAllWindows, cx| {
+                search_bar.toggle_search_all_windows(cx);
+            },
+        );
         register_workspace_action(workspace, move |search_bar, _: &ToggleCaseSensitive, cx| {
             search_bar.toggle_search_option(SearchOptions::CASE_SENSITIVE, cx);
         });
@@ -72,6 +364,12 @@ pub fn init(cx: &mut AppContext) {
         register_workspace_action(workspace, move |search_bar, _: &ToggleRegex, cx| {
             search_bar.toggle_search_option(SearchOptions::REGEX, cx);
         });
+        register_workspace_action(workspace, move |search_bar, _: &ToggleInvertMatches, cx| {
+            search_bar.toggle_search_option(SearchOptions::INVERT_MATCHES, cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &ToggleInvertFiles, cx| {
+            search_bar.toggle_search_option(SearchOptions::INVERT_FILES, cx);
+        });
         register_workspace_action(workspace, move |search_bar, action: &ToggleReplace, cx| {
             search_bar.toggle_replace(action, cx)
         });
@@ -87,11 +385,23 @@ pub fn init(cx: &mut AppContext) {
                 search_bar.select_next_match(action, cx)
             },
         );
+        register_workspace_action(workspace, move |search_bar, _: &SelectNextFile, cx| {
+            search_bar.select_file(Direction::Next, cx)
+        });
+        register_workspace_action(workspace, move |search_bar, _: &SelectPrevFile, cx| {
+            search_bar.select_file(Direction::Prev, cx)
+        });
 
         // Only handle search_in_new if there is a search present
         register_workspace_action_for_present_search(workspace, |workspace, action, cx| {
             ProjectSearchView::search_in_new(workspace, action, cx)
         });
+        register_workspace_action_for_present_search(workspace, |workspace, action, cx| {
+            ProjectSearchView::go_to_match_definition(workspace, action, cx)
+        });
+        register_workspace_action_for_present_search(workspace, |workspace, action, cx| {
+            ProjectSearchView::open_match_in_split(workspace, action, cx)
+        });
 
         // Both on present and dismissed search, we need to unconditionally handle those actions to focus from the editor.
         workspace.register_action(move |workspace, action: &DeploySearch, cx| {
@@ -110,6 +420,28 @@ pub fn init(cx: &mut AppContext) {
             ProjectSearchView::new_search(workspace, action, cx);
             cx.notify();
         });
+        workspace.register_action(move |workspace, action: &ApplySearchPreset, cx| {
+            if workspace.has_active_modal(cx) {
+                cx.propagate();
+                return;
+            }
+            ProjectSearchView::apply_search_preset(workspace, action, cx);
+        });
+        workspace.register_action(move |workspace, action: &SearchSelections, cx| {
+            if workspace.has_active_modal(cx) {
+                cx.propagate();
+                return;
+            }
+            ProjectSearchView::search_selections(workspace, action, cx);
+            cx.notify();
+        });
+        workspace.register_action(move |workspace, action: &PeekSearch, cx| {
+            if workspace.has_active_modal(cx) {
+                cx.propagate();
+                return;
+            }
+            PeekSearchModal::toggle(workspace, action, cx);
+        });
     })
     .detach();
 }
@@ -118,13 +450,38 @@ struct ProjectSearch {
     project: Model<Project>,
     excerpts: Model<MultiBuffer>,
     pending_search: Option<Task<Option<()>>>,
+    /// A notification coalescing the results streamed in since the last one.
+    pending_notify: Option<Task<()>>,
     match_ranges: Vec<Range<Anchor>>,
     active_query: Option<SearchQuery>,
     last_search_query_text: Option<String>,
     search_id: usize,
     no_results: Option<bool>,
+    /// Whether the latest search stopped early at the project's cap on results.
     limit_reached: bool,
+    skipped_file_count: usize,
+    /// How many files the latest search gave up on because matching them took too long.
+    timed_out_file_count: usize,
+    /// The files the latest search couldn't read, e.g. because of their permissions.
+    unreadable_files: Vec<ProjectPath>,
+    /// How many files passed the latest search's filters, unless only remote projects were searched.
+    files_matched_filters: Option<usize>,
     search_history_cursor: SearchHistoryCursor,
+    /// Whether to search the projects of all open windows, rather than only `project`.
+    search_all_windows: bool,
+    /// Subscriptions to the searched projects, to search their buffers again when they're saved.
+    project_subscriptions: Vec<Subscription>,
+    /// The results of each searched project, in the order the projects were searched.
+    project_results: Vec<ProjectResults>,
+}
+
+/// The excerpts of one searched project's results, which are kept together after those of the
+/// projects searched before it.
+#[derive(Clone)]
+struct ProjectResults {
+    project: Model<Project>,
+    /// The project's excerpts, in the order they appear in the results.
+    excerpt_ids: Vec<ExcerptId>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -142,13 +499,44 @@ pub struct ProjectSearchView {
     results_editor: View<Editor>,
     search_options: SearchOptions,
     panels_with_errors: HashSet<InputPanel>,
+    query_error: Option<SharedString>,
+    /// The pattern a regex search for the current query would run.
+    regex_preview: Option<SharedString>,
     active_match_index: Option<usize>,
     search_id: usize,
     query_editor_was_focused: bool,
+    last_query_cursor: Option<usize>,
     included_files_editor: View<Editor>,
     excluded_files_editor: View<Editor>,
     filters_enabled: bool,
     replace_enabled: bool,
+    edit_results_enabled: bool,
+    /// Whether the buffer search can search the results, following the `search.searchable_results`
+    /// setting.
+    searchable_results: bool,
+    /// Whether a regex query is searched for literally, without escaping its metacharacters.
+    literal_enabled: bool,
+    /// The file that was active when this search was opened, as written in the exclude filter.
+    origin_file: Option<PathBuf>,
+    /// Whether `origin_file` is left out of the results.
+    exclude_origin_file: bool,
+    pinned: bool,
+    search_pending: bool,
+    /// The pane beside the results that matches were last opened in.
+    match_split_pane: Option<WeakView<Pane>>,
+    /// The search whose notice about unreadable files was dismissed.
+    dismissed_unreadable_files_search_id: Option<usize>,
+    /// The excerpts edited through the results editor since their matches were last refreshed.
+    edited_excerpts: HashSet<ExcerptId>,
+    pending_match_refresh: Option<Task<()>>,
+    /// Whether replacements are being made in the results, whose edits don't refresh matches.
+    replacing_results: bool,
+    /// Where excerpts cut a long line short, which are marked with an ellipsis.
+    truncated_line_edges: Vec<Anchor>,
+    /// The label of each project's results and the excerpt its header is above, when the results
+    /// come from several projects.
+    project_headers: Vec<(ExcerptId, SharedString)>,
+    project_header_blocks: HashSet<BlockId>,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -167,93 +555,183 @@ impl ProjectSearch {
     fn new(project: Model<Project>, cx: &mut ModelContext<Self>) -> Self {
         let replica_id = project.read(cx).replica_id();
         let capability = project.read(cx).capability();
+        let project_subscriptions = vec![cx.subscribe(&project, Self::handle_project_event)];
 
         Self {
             project,
             excerpts: cx.new_model(|_| MultiBuffer::new(replica_id, capability)),
             pending_search: Default::default(),
+            pending_notify: None,
             match_ranges: Default::default(),
             active_query: None,
             last_search_query_text: None,
             search_id: 0,
             no_results: None,
             limit_reached: false,
+            skipped_file_count: 0,
+            timed_out_file_count: 0,
+            unreadable_files: Vec::new(),
+            files_matched_filters: None,
             search_history_cursor: Default::default(),
+            search_all_windows: false,
+            project_subscriptions,
+            project_results: Vec::new(),
         }
     }
 
     fn clone(&self, cx: &mut ModelContext<Self>) -> Model<Self> {
-        cx.new_model(|cx| Self {
-            project: self.project.clone(),
-            excerpts: self
-                .excerpts
-                .update(cx, |excerpts, cx| cx.new_model(|cx| excerpts.clone(cx))),
-            pending_search: Default::default(),
-            match_ranges: self.match_ranges.clone(),
-            active_query: self.active_query.clone(),
-            last_search_query_text: self.last_search_query_text.clone(),
-            search_id: self.search_id,
-            no_results: self.no_results,
-            limit_reached: self.limit_reached,
-            search_history_cursor: self.search_history_cursor.clone(),
+        cx.new_model(|cx| {
+            let mut this = Self {
+                project: self.project.clone(),
+                excerpts: self
+                    .excerpts
+                    .update(cx, |excerpts, cx| cx.new_model(|cx| excerpts.clone(cx))),
+                pending_search: Default::default(),
+                pending_notify: None,
+                match_ranges: self.match_ranges.clone(),
+                active_query: self.active_query.clone(),
+                last_search_query_text: self.last_search_query_text.clone(),
+                search_id: self.search_id,
+                no_results: self.no_results,
+                limit_reached: self.limit_reached,
+                skipped_file_count: self.skipped_file_count,
+                timed_out_file_count: self.timed_out_file_count,
+                unreadable_files: self.unreadable_files.clone(),
+                files_matched_filters: self.files_matched_filters,
+                search_history_cursor: self.search_history_cursor.clone(),
+                search_all_windows: self.search_all_windows,
+                project_subscriptions: Vec::new(),
+                project_results: self.project_results.clone(),
+            };
+            let projects = this.searched_projects(cx);
+            this.subscribe_to_projects(&projects, cx);
+            this
         })
     }
 
-    fn search(&mut self, query: SearchQuery, cx: &mut ModelContext<Self>) {
-        let search = self.project.update(cx, |project, cx| {
-            project
-                .search_history_mut()
-                .add(&mut self.search_history_cursor, query.as_str().to_string());
-            project.search(query.clone(), cx)
-        });
-        self.last_search_query_text = Some(query.as_str().to_string());
+    /// Runs the latest query again, so that its results reflect the project's current state.
+    fn rerun_search(&mut self, cx: &mut ModelContext<Self>) {
+        if let Some(query) = self.active_query.clone() {
+            let query_text = self
+                .last_search_query_text
+                .clone()
+                .unwrap_or_else(|| query.as_str().to_string());
+            self.search(query, query_text, cx);
+        }
+    }
+
+    /// Searches for `query`, remembering `query_text` as written in the query editor, inline
+    /// filters included, for the search history and for restoring the query.
+    fn search(&mut self, query: SearchQuery, query_text: String, cx: &mut ModelContext<Self>) {
+        let projects = self.searched_projects(cx);
+        // Every searched project remembers the query, though only this one's history is browsed
+        // with the cursor.
+        for project in &projects {
+            let mut cursor = SearchHistoryCursor::default();
+            let cursor = if *project == self.project {
+                &mut self.search_history_cursor
+            } else {
+                &mut cursor
+            };
+            project.update(cx, |project, _| {
+                project.search_history_mut().add(cursor, query_text.clone());
+            });
+        }
+        self.subscribe_to_projects(&projects, cx);
+        let project_results = projects
+            .iter()
+            .map(|project| ProjectResults {
+                project: project.clone(),
+                excerpt_ids: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+        // Projects are searched one after another, so results are grouped by project, then by file.
+        let searches = projects
+            .into_iter()
+            .map(|project| project.update(cx, |project, cx| project.search(query.clone(), cx)))
+            .collect::<Vec<_>>();
+        self.last_search_query_text = Some(query_text);
         self.search_id += 1;
         self.active_query = Some(query);
         self.match_ranges.clear();
+        self.limit_reached = false;
         self.pending_search = Some(cx.spawn(|this, mut cx| async move {
-            let mut matches = search;
             let this = this.upgrade()?;
             this.update(&mut cx, |this, cx| {
                 this.match_ranges.clear();
                 this.excerpts.update(cx, |this, cx| this.clear(cx));
                 this.no_results = Some(true);
                 this.limit_reached = false;
+                this.skipped_file_count = 0;
+                this.timed_out_file_count = 0;
+                this.unreadable_files.clear();
+                this.files_matched_filters = None;
+                this.project_results = project_results;
             })
             .ok()?;
 
             let mut limit_reached = false;
-            while let Some(result) = matches.next().await {
-                match result {
-                    project::SearchResult::Buffer { buffer, ranges } => {
-                        let mut match_ranges = this
-                            .update(&mut cx, |this, cx| {
-                                this.no_results = Some(false);
-                                this.excerpts.update(cx, |excerpts, cx| {
-                                    excerpts.stream_excerpts_with_context_lines(
-                                        buffer,
-                                        ranges,
-                                        editor::DEFAULT_MULTIBUFFER_CONTEXT,
-                                        cx,
-                                    )
+            let mut skipped_file_count = 0;
+            let mut timed_out_file_count = 0;
+            let mut unreadable_files = Vec::new();
+            let mut files_matched_filters = None;
+            for (project_ix, mut matches) in searches.into_iter().enumerate() {
+                while let Some(result) = matches.next().await {
+                    match result {
+                        project::SearchResult::Buffer { buffer, ranges } => {
+                            let mut match_ranges = this
+                                .update(&mut cx, |this, cx| {
+                                    this.no_results = Some(false);
+                                    this.excerpts.update(cx, |excerpts, cx| {
+                                        excerpts.stream_excerpts_with_context_lines(
+                                            buffer,
+                                            ranges,
+                                            editor::DEFAULT_MULTIBUFFER_CONTEXT,
+                                            true,
+                                            cx,
+                                        )
+                                    })
                                 })
-                            })
-                            .ok()?;
+                                .ok()?;
 
-                        while let Some(range) = match_ranges.next().await {
-                            this.update(&mut cx, |this, _| this.match_ranges.push(range))
+                            while let Some(range) = match_ranges.next().await {
+                                this.update(&mut cx, |this, _| {
+                                    let excerpt_ids =
+                                        &mut this.project_results[project_ix].excerpt_ids;
+                                    if excerpt_ids.last() != Some(&range.start.excerpt_id) {
+                                        excerpt_ids.push(range.start.excerpt_id);
+                                    }
+                                    this.match_ranges.push(range);
+                                })
+                                .ok()?;
+                            }
+                            this.update(&mut cx, |this, cx| this.notify_throttled(cx))
                                 .ok()?;
                         }
-                        this.update(&mut cx, |_, cx| cx.notify()).ok()?;
-                    }
-                    project::SearchResult::LimitReached => {
-                        limit_reached = true;
+                        project::SearchResult::Skipped { path, reason } => match reason {
+                            SearchSkipReason::FileTooLarge { .. } => skipped_file_count += 1,
+                            SearchSkipReason::Unreadable => unreadable_files.push(path),
+                            SearchSkipReason::MatchTimedOut => timed_out_file_count += 1,
+                        },
+                        project::SearchResult::FilesMatchedFilters { count } => {
+                            *files_matched_filters.get_or_insert(0) += count;
+                        }
+                        project::SearchResult::LimitReached => {
+                            limit_reached = true;
+                        }
                     }
                 }
             }
 
             this.update(&mut cx, |this, cx| {
                 this.limit_reached = limit_reached;
+                this.skipped_file_count = skipped_file_count;
+                this.timed_out_file_count = timed_out_file_count;
+                unreadable_files.sort();
+                this.unreadable_files = unreadable_files;
+                this.files_matched_filters = files_matched_filters;
                 this.pending_search.take();
+                this.pending_notify.take();
                 cx.notify();
             })
             .ok()?;
@@ -262,6 +740,322 @@ impl ProjectSearch {
         }));
         cx.notify();
     }
+
+    /// The projects that a search covers, which are those of all open windows when
+    /// `search_all_windows` is on.
+    fn searched_projects(&self, cx: &AppContext) -> Vec<Model<Project>> {
+        if self.search_all_windows {
+            open_projects(&self.project, cx)
+        } else {
+            vec![self.project.clone()]
+        }
+    }
+
+    fn subscribe_to_projects(&mut self, projects: &[Model<Project>], cx: &mut ModelContext<Self>) {
+        self.project_subscriptions = projects
+            .iter()
+            .map(|project| cx.subscribe(project, Self::handle_project_event))
+            .collect();
+    }
+
+    /// Searches buffers again when they're saved, if the `search.refresh_on_save` setting is
+    /// on. This includes buffers that had no results, as saving may have added some.
+    fn handle_project_event(
+        &mut self,
+        project: Model<Project>,
+        event: &project::Event,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if let project::Event::BufferSaved(buffer) = event {
+            if SearchSettings::get_global(cx).refresh_on_save
+                && self.active_query.is_some()
+                && self.pending_search.is_none()
+            {
+                self.refresh_buffer_results(project, buffer.clone(), cx);
+            }
+        }
+    }
+
+    /// Searches a single buffer for the latest query again, replacing its excerpts and matches
+    /// in the results without searching the rest of the project.
+    fn refresh_buffer_results(
+        &mut self,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(query) = self.active_query.clone() else {
+            return;
+        };
+        let search_id = self.search_id;
+        let ranges = project.update(cx, |project, cx| project.search_buffer(&buffer, query, cx));
+        cx.spawn(|this, mut cx| async move {
+            let Some(ranges) = ranges.await else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                if this.search_id == search_id && this.pending_search.is_none() {
+                    this.replace_buffer_results(project, buffer, ranges, cx);
+                }
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Replaces the excerpts and matches of the given buffer in the results with ones for the
+    /// given ranges. A buffer that had no results yet is placed among the others of its project
+    /// by its path.
+    fn replace_buffer_results(
+        &mut self,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        ranges: Vec<Range<language::Anchor>>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let project_ix = match self
+            .project_results
+            .iter()
+            .position(|results| results.project == project)
+        {
+            Some(project_ix) => project_ix,
+            None => {
+                self.project_results.push(ProjectResults {
+                    project,
+                    excerpt_ids: Vec::new(),
+                });
+                self.project_results.len() - 1
+            }
+        };
+        let buffer_id = buffer.read(cx).remote_id();
+        let buffer_path = buffer.read(cx).file().map(|file| file.full_path(cx));
+        let old_excerpt_ids = self
+            .excerpts
+            .read(cx)
+            .excerpts_for_buffer(&buffer, cx)
+            .into_iter()
+            .map(|(excerpt_id, _)| excerpt_id)
+            .collect::<Vec<_>>();
+
+        // Excerpts removed from the results since they were recorded are passed over.
+        let snapshot = self.excerpts.read(cx).snapshot(cx);
+        let mut prev_excerpt_id = self.project_results[..project_ix]
+            .iter()
+            .rev()
+            .find_map(|results| {
+                results
+                    .excerpt_ids
+                    .iter()
+                    .rev()
+                    .find(|excerpt_id| snapshot.buffer_for_excerpt(**excerpt_id).is_some())
+                    .copied()
+            })
+            .unwrap_or(ExcerptId::min());
+        for excerpt_id in &self.project_results[project_ix].excerpt_ids {
+            let Some(excerpt_buffer) = snapshot.buffer_for_excerpt(*excerpt_id) else {
+                continue;
+            };
+            if excerpt_buffer.remote_id() == buffer_id
+                || old_excerpt_ids.is_empty()
+                    && excerpt_buffer.file().map(|file| file.full_path(cx)) > buffer_path
+            {
+                break;
+            }
+            prev_excerpt_id = *excerpt_id;
+        }
+
+        let new_match_ranges = self.excerpts.update(cx, |excerpts, cx| {
+            excerpts.remove_excerpts(old_excerpt_ids.clone(), cx);
+            excerpts.insert_excerpts_with_context_lines_after(
+                prev_excerpt_id,
+                buffer,
+                ranges,
+                editor::DEFAULT_MULTIBUFFER_CONTEXT,
+                true,
+                cx,
+            )
+        });
+
+        let mut new_excerpt_ids = new_match_ranges
+            .iter()
+            .map(|range| range.start.excerpt_id)
+            .collect::<Vec<_>>();
+        new_excerpt_ids.dedup();
+        let excerpt_ids = &mut self.project_results[project_ix].excerpt_ids;
+        excerpt_ids.retain(|excerpt_id| !old_excerpt_ids.contains(excerpt_id));
+        let ix = excerpt_ids
+            .iter()
+            .position(|excerpt_id| *excerpt_id == prev_excerpt_id)
+            .map_or(0, |ix| ix + 1);
+        excerpt_ids.splice(ix..ix, new_excerpt_ids);
+
+        let snapshot = self.excerpts.read(cx).snapshot(cx);
+        self.match_ranges
+            .retain(|range| range.start.buffer_id != Some(buffer_id));
+        self.match_ranges.extend(new_match_ranges);
+        self.match_ranges
+            .sort_by(|a, b| a.start.cmp(&b.start, &snapshot));
+        self.no_results = Some(self.match_ranges.is_empty());
+        cx.notify();
+    }
+
+    /// Searches the buffers of the given excerpts for the latest query again and updates the
+    /// matches within those excerpts, leaving the excerpts themselves and all other matches as
+    /// they are.
+    fn refresh_excerpt_match_ranges(
+        &mut self,
+        excerpt_ids: HashSet<ExcerptId>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(query) = self.active_query.clone() else {
+            return;
+        };
+        let mut excerpts_by_buffer = HashMap::default();
+        for (excerpt_id, buffer, range) in self.excerpts.read(cx).snapshot(cx).excerpts() {
+            if excerpt_ids.contains(&excerpt_id) {
+                excerpts_by_buffer
+                    .entry(buffer.remote_id())
+                    .or_insert_with(Vec::new)
+                    .push((excerpt_id, range.context));
+            }
+        }
+
+        let projects = self.searched_projects(cx);
+        let mut searches = Vec::new();
+        for (buffer_id, excerpts) in excerpts_by_buffer {
+            let Some(buffer) = self.excerpts.read(cx).buffer(buffer_id) else {
+                continue;
+            };
+            let Some(worktree_id) = buffer.read(cx).file().map(|file| file.worktree_id(cx)) else {
+                continue;
+            };
+            let Some(project) = projects
+                .iter()
+                .find(|project| project.read(cx).worktree_for_id(worktree_id, cx).is_some())
+            else {
+                continue;
+            };
+            // The whole buffer is searched so that matches near excerpt edges see their context.
+            let ranges = project.update(cx, |project, cx| {
+                project.search_buffer(&buffer, query.clone(), cx)
+            });
+            searches.push((buffer, excerpts, ranges));
+        }
+
+        let search_id = self.search_id;
+        cx.spawn(|this, mut cx| async move {
+            for (buffer, excerpts, ranges) in searches {
+                let ranges = ranges.await.unwrap_or_default();
+                this.update(&mut cx, |this, cx| {
+                    if this.search_id == search_id && this.pending_search.is_none() {
+                        this.replace_excerpt_match_ranges(buffer, excerpts, ranges, cx);
+                    }
+                })
+                .ok();
+            }
+        })
+        .detach();
+    }
+
+    /// Replaces the matches within the given excerpts of a buffer with the given ranges that
+    /// fall inside them.
+    fn replace_excerpt_match_ranges(
+        &mut self,
+        buffer: Model<Buffer>,
+        excerpts: Vec<(ExcerptId, Range<language::Anchor>)>,
+        ranges: Vec<Range<language::Anchor>>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let buffer_snapshot = buffer.read(cx).snapshot();
+        let snapshot = self.excerpts.read(cx).snapshot(cx);
+        let new_match_ranges = ranges
+            .into_iter()
+            .filter_map(|range| {
+                let (excerpt_id, _) = excerpts.iter().find(|(_, context)| {
+                    context.start.cmp(&range.start, &buffer_snapshot).is_le()
+                        && context.end.cmp(&range.end, &buffer_snapshot).is_ge()
+                })?;
+                let start = snapshot.anchor_in_excerpt(*excerpt_id, range.start)?;
+                let end = snapshot.anchor_in_excerpt(*excerpt_id, range.end)?;
+                Some(start..end)
+            })
+            .collect::<Vec<_>>();
+
+        self.match_ranges.retain(|range| {
+            !excerpts
+                .iter()
+                .any(|(excerpt_id, _)| range.start.excerpt_id == *excerpt_id)
+        });
+        self.match_ranges.extend(new_match_ranges);
+        self.match_ranges
+            .sort_by(|a, b| a.start.cmp(&b.start, &snapshot));
+        cx.notify();
+    }
+
+    /// How many files the latest search scanned, which excludes the skipped, timed out and
+    /// unreadable ones.
+    fn searched_file_count(&self) -> Option<usize> {
+        Some(self.files_matched_filters?.saturating_sub(
+            self.skipped_file_count + self.timed_out_file_count + self.unreadable_files.len(),
+        ))
+    }
+
+    /// Replaces the text of one match in the results with the query's replacement, leaving the
+    /// other matches as they are. Returns whether it was replaced, which it isn't when the range
+    /// is no longer one of the matches or the query has no replacement for it.
+    pub fn replace_range(
+        &mut self,
+        range: Range<Anchor>,
+        query: &SearchQuery,
+        cx: &mut ModelContext<Self>,
+    ) -> bool {
+        // The results of an inverted query are whole lines, not text to replace.
+        if query.inversion().is_some() || !self.match_ranges.contains(&range) {
+            return false;
+        }
+        let snapshot = self.excerpts.read(cx).snapshot(cx);
+        let text = snapshot.text_for_range(range.clone()).collect::<String>();
+        let Some(replacement) = query.replacement_for(&text) else {
+            return false;
+        };
+        self.excerpts.update(cx, |excerpts, cx| {
+            excerpts.start_transaction(cx);
+            excerpts.edit([(range.clone(), replacement.as_ref())], None, cx);
+            excerpts.end_transaction(cx);
+        });
+        self.refresh_excerpt_match_ranges(HashSet::from_iter([range.start.excerpt_id]), cx);
+        true
+    }
+
+    /// The match range containing the given position of the results, if any.
+    fn match_range_at(&self, position: &Anchor, cx: &AppContext) -> Option<Range<Anchor>> {
+        let snapshot = self.excerpts.read(cx).snapshot(cx);
+        self.match_ranges
+            .iter()
+            .find(|range| {
+                range.start.cmp(position, &snapshot).is_le()
+                    && range.end.cmp(position, &snapshot).is_ge()
+            })
+            .cloned()
+    }
+
+    /// Notifies observers once the streaming interval passes, so that results arriving in
+    /// quick succession don't each cause a re-render.
+    fn notify_throttled(&mut self, cx: &mut ModelContext<Self>) {
+        if self.pending_notify.is_some() {
+            return;
+        }
+        self.pending_notify = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor()
+                .timer(STREAMING_NOTIFY_INTERVAL)
+                .await;
+            this.update(&mut cx, |this, cx| {
+                this.pending_notify = None;
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -270,17 +1064,28 @@ pub enum ViewEvent {
     Activate,
     EditorEvent(editor::EditorEvent),
     Dismiss,
+    /// The active match changed, either through navigation or because the results changed.
+    ActiveMatchChanged {
+        index: Option<usize>,
+        total: usize,
+    },
 }
 
 impl EventEmitter<ViewEvent> for ProjectSearchView {}
 
 impl Render for ProjectSearchView {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
-        if self.has_matches() {
+        if self.has_results(cx) {
             div()
                 .flex_1()
                 .size_full()
                 .track_focus(&self.focus_handle)
+                .on_action(cx.listener(Self::copy_match_reference))
+                .on_action(cx.listener(Self::exclude_match_file))
+                .on_action(cx.listener(Self::replace_match_at_cursor))
+                .on_action(cx.listener(Self::expand_truncated_lines))
+                .on_action(cx.listener(Self::toggle_focus))
+                .on_action(cx.listener(Self::dismiss))
                 .child(self.results_editor.clone())
         } else {
             let model = self.model.read(cx);
@@ -298,7 +1103,9 @@ impl Render for ProjectSearchView {
 
             let minor_text: Option<SharedString> = if let Some(no_results) = model.no_results {
                 if model.pending_search.is_none() && no_results {
-                    Some("No results found in this project for the provided query".into())
+                    Some(self.no_results_summary(cx).unwrap_or_else(|| {
+                        "No results found in this project for the provided query".into()
+                    }))
                 } else {
                     None
                 }
@@ -367,34 +1174,49 @@ impl Item for ProjectSearchView {
             .update(cx, |editor, cx| editor.deactivated(cx));
     }
 
+    /// The buffer search finds text within the results when enabled. While it's deployed, it
+    /// handles the search actions both bars share, such as `SelectNextMatch`, before this view.
+    fn as_searchable(&self, _: &View<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        self.searchable_results
+            .then(|| Box::new(self.results_editor.clone()) as Box<dyn SearchableItemHandle>)
+    }
+
     fn tab_content(&self, params: TabContentParams, cx: &WindowContext<'_>) -> AnyElement {
-        let last_query: Option<SharedString> = self
-            .model
-            .read(cx)
-            .last_search_query_text
-            .as_ref()
-            .map(|query| {
-                let query = query.replace('\n', "");
-                let query_text = util::truncate_and_trailoff(&query, MAX_TAB_TITLE_LEN);
-                query_text.into()
-            });
-        let tab_name = last_query
-            .filter(|query| !query.is_empty())
-            .unwrap_or_else(|| "Project Search".into());
+        let tab_name = self.tab_title(cx);
+        let icon_color = if params.selected {
+            Color::Default
+        } else {
+            Color::Muted
+        };
+        let icon = if self.search_pending {
+            Icon::new(IconName::ArrowCircle)
+                .color(icon_color)
+                .with_animation(
+                    "project-search-pending",
+                    Animation::new(Duration::from_secs(2)).repeat(),
+                    |icon, delta| icon.transform(Transformation::rotate(percentage(delta))),
+                )
+                .into_any_element()
+        } else {
+            Icon::new(IconName::MagnifyingGlass)
+                .color(icon_color)
+                .into_any_element()
+        };
         h_flex()
             .gap_2()
-            .child(
-                Icon::new(IconName::MagnifyingGlass).color(if params.selected {
-                    Color::Default
-                } else {
-                    Color::Muted
-                }),
-            )
+            .child(icon)
             .child(Label::new(tab_name).color(if params.selected {
                 Color::Default
             } else {
                 Color::Muted
             }))
+            .when(self.pinned, |this| {
+                this.child(
+                    Icon::new(IconName::Pin)
+                        .size(IconSize::XSmall)
+                        .color(Color::Muted),
+                )
+            })
             .into_any()
     }
 
@@ -414,8 +1236,10 @@ impl Item for ProjectSearchView {
         false
     }
 
+    /// The results can only be saved while they can be changed, so that a read-only view never
+    /// writes its buffers.
     fn can_save(&self, _: &AppContext) -> bool {
-        true
+        self.replace_enabled || self.edit_results_enabled
     }
 
     fn is_dirty(&self, cx: &AppContext) -> bool {
@@ -497,7 +1321,7 @@ impl Item for ProjectSearchView {
     }
 
     fn breadcrumb_location(&self) -> ToolbarItemLocation {
-        if self.has_matches() {
+        if self.has_matches() || self.search_pending {
             ToolbarItemLocation::Secondary
         } else {
             ToolbarItemLocation::Hidden
@@ -505,7 +1329,16 @@ impl Item for ProjectSearchView {
     }
 
     fn breadcrumbs(&self, theme: &theme::Theme, cx: &AppContext) -> Option<Vec<BreadcrumbText>> {
-        self.results_editor.breadcrumbs(theme, cx)
+        if self.has_matches() {
+            return self.results_editor.breadcrumbs(theme, cx);
+        }
+
+        let query = self.model.read(cx).active_query.as_ref()?;
+        Some(vec![BreadcrumbText {
+            text: format!("Searching: {}", query.as_str().replace('\n', "")),
+            highlights: None,
+            font: None,
+        }])
     }
 
     fn serialized_item_kind() -> Option<&'static str> {
@@ -534,14 +1367,166 @@ impl ProjectSearchView {
         });
     }
 
-    fn current_settings(&self) -> ProjectSearchSettings {
-        ProjectSearchSettings {
-            search_options: self.search_options,
-            filters_enabled: self.filters_enabled,
-        }
-    }
-    fn toggle_search_option(&mut self, option: SearchOptions, cx: &mut ViewContext<Self>) {
-        self.search_options.toggle(option);
+    fn toggle_search_all_windows(&mut self, cx: &mut ViewContext<Self>) {
+        let search_all_windows = self.model.update(cx, |model, _| {
+            model.search_all_windows = !model.search_all_windows;
+            model.search_all_windows
+        });
+        // Results may come from several projects, so label each file with its worktree.
+        self.results_editor.update(cx, |editor, cx| {
+            editor.set_show_worktree_root_in_excerpt_headers(search_all_windows, cx)
+        });
+        cx.notify();
+    }
+
+    fn toggle_exclude_origin_file(&mut self, cx: &mut ViewContext<Self>) {
+        self.exclude_origin_file = !self.exclude_origin_file;
+        cx.notify();
+    }
+
+    fn toggle_pinned(&mut self, cx: &mut ViewContext<Self>) {
+        self.pinned = !self.pinned;
+        cx.emit(ViewEvent::UpdateTab);
+        cx.notify();
+    }
+
+    /// Remember the current filters, so that new searches in this project start out with them.
+    fn save_filters_as_default(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(key) = project_kvp_key(SEARCH_FILTERS_KEY, &self.model.read(cx).project, cx)
+        else {
+            return;
+        };
+        let filters = SerializedSearchFilters {
+            included: self.included_files_editor.read(cx).text(cx),
+            excluded: self.excluded_files_editor.read(cx).text(cx),
+        };
+        cx.background_executor()
+            .spawn(
+                async move {
+                    KEY_VALUE_STORE
+                        .write_kvp(key, serde_json::to_string(&filters)?)
+                        .await?;
+                    anyhow::Ok(())
+                }
+                .log_err(),
+            )
+            .detach();
+    }
+
+    /// Save the query, options and filters under a name, replacing the project's preset of that name.
+    fn save_search_preset(&mut self, name: Option<String>, cx: &mut ViewContext<Self>) {
+        let project = self.model.read(cx).project.clone();
+        let Some(key) = project_kvp_key(SEARCH_PRESETS_KEY, &project, cx) else {
+            return;
+        };
+        let query = self.query_editor.read(cx).text(cx);
+        let name = name.unwrap_or_else(|| query.clone());
+        if name.trim().is_empty() {
+            return;
+        }
+
+        let mut presets = load_search_presets(&project, cx);
+        presets.retain(|preset| preset.name != name);
+        presets.push(SearchPreset {
+            name,
+            query,
+            options: self.search_options.bits(),
+            literal: self.literal_enabled,
+            included: self.included_files_editor.read(cx).text(cx),
+            excluded: self.excluded_files_editor.read(cx).text(cx),
+            exclude_origin_file: self.exclude_origin_file,
+        });
+        cx.background_executor()
+            .spawn(
+                async move {
+                    KEY_VALUE_STORE
+                        .write_kvp(key, serde_json::to_string(&presets)?)
+                        .await?;
+                    anyhow::Ok(())
+                }
+                .log_err(),
+            )
+            .detach();
+    }
+
+    fn apply_preset(&mut self, preset: SearchPreset, cx: &mut ViewContext<Self>) {
+        self.search_options = SearchOptions::from_bits(preset.options).unwrap_or_else(|| {
+            log::warn!(
+                "search preset {:?} has unknown options {:#b}, ignoring them",
+                preset.name,
+                preset.options & !SearchOptions::all().bits()
+            );
+            SearchOptions::from_bits_truncate(preset.options)
+        });
+        self.literal_enabled = preset.literal;
+        self.exclude_origin_file = preset.exclude_origin_file;
+        self.update_regex_preview(cx);
+        self.update_query_placeholder(cx);
+        self.set_query(&preset.query, cx);
+        if !preset.included.is_empty() || !preset.excluded.is_empty() {
+            self.filters_enabled = true;
+        }
+        self.included_files_editor
+            .update(cx, |editor, cx| editor.set_text(preset.included, cx));
+        self.excluded_files_editor
+            .update(cx, |editor, cx| editor.set_text(preset.excluded, cx));
+        self.search(cx);
+    }
+
+    fn reset_filters(&mut self, cx: &mut ViewContext<Self>) {
+        self.included_files_editor
+            .update(cx, |editor, cx| editor.set_text("", cx));
+        self.excluded_files_editor
+            .update(cx, |editor, cx| editor.set_text("", cx));
+        if let Some(key) = project_kvp_key(SEARCH_FILTERS_KEY, &self.model.read(cx).project, cx) {
+            cx.background_executor()
+                .spawn(KEY_VALUE_STORE.delete_kvp(key).log_err())
+                .detach();
+        }
+        cx.notify();
+    }
+
+    /// Search the complement of the filters, by trading the include and exclude globs.
+    fn swap_include_exclude(&mut self, cx: &mut ViewContext<Self>) {
+        let included_files = self.included_files_editor.read(cx).text(cx);
+        let excluded_files = self.excluded_files_editor.read(cx).text(cx);
+        self.included_files_editor
+            .update(cx, |editor, cx| editor.set_text(excluded_files, cx));
+        self.excluded_files_editor
+            .update(cx, |editor, cx| editor.set_text(included_files, cx));
+        cx.notify();
+    }
+
+    /// Turn every search option off and clear the filters, without forgetting the saved defaults.
+    fn reset_search_options(&mut self, cx: &mut ViewContext<Self>) {
+        self.search_options = SearchOptions::NONE;
+        self.exclude_origin_file = false;
+        self.included_files_editor
+            .update(cx, |editor, cx| editor.set_text("", cx));
+        self.excluded_files_editor
+            .update(cx, |editor, cx| editor.set_text("", cx));
+        self.panels_with_errors.clear();
+        self.update_query_placeholder(cx);
+        self.update_regex_preview(cx);
+        cx.update_global(|state: &mut ActiveSettings, cx| {
+            state.0.insert(
+                self.model.read(cx).project.downgrade(),
+                self.current_settings(),
+            );
+        });
+        cx.notify();
+    }
+
+    fn current_settings(&self) -> ProjectSearchSettings {
+        ProjectSearchSettings {
+            search_options: self.search_options,
+            filters_enabled: self.filters_enabled,
+        }
+    }
+    fn toggle_search_option(&mut self, option: SearchOptions, cx: &mut ViewContext<Self>) {
+        self.search_options.toggle(option);
+        self.update_query_placeholder(cx);
+        self.update_regex_preview(cx);
         cx.update_global(|state: &mut ActiveSettings, cx| {
             state.0.insert(
                 self.model.read(cx).project.downgrade(),
@@ -550,6 +1535,13 @@ impl ProjectSearchView {
         });
     }
 
+    fn update_query_placeholder(&mut self, cx: &mut ViewContext<Self>) {
+        let placeholder = query_placeholder(self.search_options);
+        self.query_editor.update(cx, |editor, cx| {
+            editor.set_placeholder_text(placeholder, cx)
+        });
+    }
+
     fn replace_next(&mut self, _: &ReplaceNext, cx: &mut ViewContext<Self>) {
         if self.model.read(cx).match_ranges.is_empty() {
             return;
@@ -558,18 +1550,249 @@ impl ProjectSearchView {
             return;
         };
 
-        let query = self.model.read(cx).active_query.clone();
-        if let Some(query) = query {
-            let query = query.with_replacement(self.replacement(cx));
-
-            // TODO: Do we need the clone here?
-            let mat = self.model.read(cx).match_ranges[active_index].clone();
-            self.results_editor.update(cx, |editor, cx| {
-                editor.replace(&mat, &query, cx);
-            });
+        let range = self.model.read(cx).match_ranges[active_index].clone();
+        if self.replace_match(&range, cx) {
             self.select_match(Direction::Next, cx)
         }
     }
+
+    /// Show the whole lines of the excerpts at the cursors that only show a window of a long
+    /// line.
+    fn expand_truncated_lines(&mut self, _: &ExpandTruncatedLines, cx: &mut ViewContext<Self>) {
+        let excerpt_ids = self
+            .results_editor
+            .read(cx)
+            .selections
+            .disjoint_anchors()
+            .iter()
+            .flat_map(|selection| [selection.start.excerpt_id, selection.end.excerpt_id])
+            .collect::<HashSet<_>>();
+        self.expand_excerpts_to_whole_lines(excerpt_ids, cx);
+    }
+
+    fn expand_excerpts_to_whole_lines(
+        &mut self,
+        excerpt_ids: impl IntoIterator<Item = ExcerptId>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let excerpts = self.model.read(cx).excerpts.clone();
+        excerpts.update(cx, |excerpts, cx| {
+            excerpts.expand_excerpts_to_whole_lines(excerpt_ids, cx)
+        });
+    }
+
+    /// Puts a header naming the project above the results of each project, when more than one
+    /// was searched.
+    fn update_project_headers(&mut self, cx: &mut ViewContext<Self>) {
+        let model = self.model.read(cx);
+        let snapshot = model.excerpts.read(cx).snapshot(cx);
+        let mut headers = Vec::new();
+        if model.project_results.len() > 1 {
+            for results in &model.project_results {
+                let first_excerpt_id = results
+                    .excerpt_ids
+                    .iter()
+                    .find(|excerpt_id| snapshot.buffer_for_excerpt(**excerpt_id).is_some());
+                if let Some(excerpt_id) = first_excerpt_id {
+                    headers.push((*excerpt_id, project_label(&results.project, cx)));
+                }
+            }
+        }
+        if headers == self.project_headers {
+            return;
+        }
+
+        let blocks = headers
+            .iter()
+            .filter_map(|(excerpt_id, label)| {
+                Some(BlockProperties {
+                    position: snapshot.anchor_in_excerpt(*excerpt_id, language::Anchor::MIN)?,
+                    height: 2,
+                    style: BlockStyle::Sticky,
+                    render: project_header_renderer(label.clone()),
+                    disposition: BlockDisposition::Above,
+                })
+            })
+            .collect::<Vec<_>>();
+        let old_blocks = mem::take(&mut self.project_header_blocks);
+        self.project_header_blocks = self.results_editor.update(cx, |editor, cx| {
+            editor.remove_blocks(old_blocks, None, cx);
+            editor.insert_blocks(blocks, None, cx).into_iter().collect()
+        });
+        self.project_headers = headers;
+    }
+
+    fn update_truncated_line_markers(&mut self, cx: &mut ViewContext<Self>) {
+        let snapshot = self.model.read(cx).excerpts.read(cx).snapshot(cx);
+        let edges = truncated_line_edges(&snapshot);
+        if edges != self.truncated_line_edges {
+            self.truncated_line_edges = edges.clone();
+            self.results_editor
+                .update(cx, |editor, cx| editor.set_inline_markers(edges, "…", cx));
+        }
+    }
+
+    /// The match at the given position of the results, when replacing is enabled.
+    fn replaceable_match_at(&self, position: &Anchor, cx: &AppContext) -> Option<Range<Anchor>> {
+        if !self.replace_enabled {
+            return None;
+        }
+        self.model.read(cx).match_range_at(position, cx)
+    }
+
+    /// Replace only the match under the cursor, without moving on to the next one.
+    fn replace_match_at_cursor(&mut self, _: &ReplaceMatchAtCursor, cx: &mut ViewContext<Self>) {
+        let head = self
+            .results_editor
+            .read(cx)
+            .selections
+            .newest_anchor()
+            .head();
+        if let Some(range) = self.model.read(cx).match_range_at(&head, cx) {
+            self.replace_match(&range, cx);
+        }
+    }
+
+    /// Replaces the given match range of the results, returning whether it was replaced.
+    fn replace_match(&mut self, range: &Range<Anchor>, cx: &mut ViewContext<Self>) -> bool {
+        let Some(query) = self.model.read(cx).active_query.clone() else {
+            return false;
+        };
+        let query = query.with_replacement(self.replacement(cx));
+        self.model.update(cx, |model, cx| {
+            model.replace_range(range.clone(), &query, cx)
+        })
+    }
+
+    /// Copy a `path:line:column` reference to the active match, for sharing it elsewhere.
+    fn copy_match_reference(&mut self, _: &CopyMatchReference, cx: &mut ViewContext<Self>) {
+        let Some(active_index) = self.active_match_index else {
+            return;
+        };
+        let model = self.model.read(cx);
+        let Some(range) = model.match_ranges.get(active_index) else {
+            return;
+        };
+        let snapshot = model.excerpts.read(cx).snapshot(cx);
+        let Some(buffer) = snapshot.buffer_for_excerpt(range.start.excerpt_id) else {
+            return;
+        };
+        let Some(file) = buffer.file() else {
+            return;
+        };
+
+        let point = range.start.text_anchor.to_point(buffer);
+        let reference = format!(
+            "{}:{}:{}",
+            match_path(model.project.read(cx), file.as_ref(), cx).display(),
+            point.row + 1,
+            point.column + 1
+        );
+        cx.write_to_clipboard(ClipboardItem::new(reference));
+    }
+
+    /// Exclude the file under the cursor in the results and search again without it.
+    fn exclude_match_file(&mut self, _: &ExcludeMatchFile, cx: &mut ViewContext<Self>) {
+        let head = self
+            .results_editor
+            .read(cx)
+            .selections
+            .newest_anchor()
+            .head();
+        let model = self.model.read(cx);
+        let Some(buffer) = head
+            .buffer_id
+            .and_then(|buffer_id| model.excerpts.read(cx).buffer(buffer_id))
+        else {
+            return;
+        };
+        let Some(file) = buffer.read(cx).file() else {
+            return;
+        };
+        let path = filter_path(model.project.read(cx), file.as_ref(), cx);
+        let Some(glob) = Self::file_include_glob(&path) else {
+            return;
+        };
+
+        let excluded_files = self.excluded_files_editor.read(cx).text(cx);
+        let excluded_files = if excluded_files.trim().is_empty() {
+            glob
+        } else {
+            format!("{}, {}", excluded_files.trim_end(), glob)
+        };
+        self.excluded_files_editor
+            .update(cx, |editor, cx| editor.set_text(excluded_files, cx));
+        self.filters_enabled = true;
+        self.search(cx);
+        cx.notify();
+    }
+
+    /// The results editor is read-only unless editing results was explicitly enabled, so that
+    /// typing into it can't accidentally modify source files. Replacements lift that temporarily.
+    fn update_results_editable(
+        &mut self,
+        cx: &mut ViewContext<Self>,
+        update: impl FnOnce(&mut Editor, &mut ViewContext<Editor>),
+    ) {
+        let edit_results_enabled = self.edit_results_enabled;
+        self.replacing_results = true;
+        self.results_editor.update(cx, |editor, cx| {
+            editor.set_read_only(false);
+            update(editor, cx);
+            editor.set_read_only(!edit_results_enabled);
+        });
+        // The editor handles its events for these edits after this update, before deferred calls.
+        cx.defer(|this, _| this.replacing_results = false);
+    }
+
+    fn toggle_edit_results(&mut self, cx: &mut ViewContext<Self>) {
+        self.edit_results_enabled = !self.edit_results_enabled;
+        let read_only = !self.edit_results_enabled;
+        self.results_editor
+            .update(cx, |editor, _| editor.set_read_only(read_only));
+        cx.notify();
+    }
+
+    fn toggle_literal(&mut self, cx: &mut ViewContext<Self>) {
+        self.literal_enabled = !self.literal_enabled;
+        self.update_regex_preview(cx);
+        cx.notify();
+    }
+
+    fn update_regex_preview(&mut self, cx: &mut ViewContext<Self>) {
+        self.regex_preview = self
+            .search_options
+            .contains(SearchOptions::REGEX)
+            .then(|| {
+                let query_text = self.query_editor.read(cx).text(cx);
+                let (text, _, _, _) = Self::parse_inline_filters(&query_text);
+                let text = self.regex_text(text);
+                SearchQuery::regex(
+                    text,
+                    self.search_options.contains(SearchOptions::WHOLE_WORD),
+                    self.search_options.contains(SearchOptions::CASE_SENSITIVE),
+                    false,
+                    Vec::new(),
+                    Vec::new(),
+                )
+                .ok()?
+                .regex_source()
+            })
+            .flatten()
+            .map(SharedString::from);
+        cx.notify();
+    }
+
+    /// The regex pattern searched for the given query text, which is escaped in literal mode.
+    fn regex_text(&self, text: &str) -> String {
+        // Escaping keeps the query a regex, so replacements still expand capture groups.
+        if self.literal_enabled {
+            regex::escape(text)
+        } else {
+            text.to_string()
+        }
+    }
+
     pub fn replacement(&self, cx: &AppContext) -> String {
         self.replacement_editor.read(cx).text(cx)
     }
@@ -578,11 +1801,60 @@ impl ProjectSearchView {
             return;
         }
 
-        let Some(query) = self.model.read(cx).active_query.as_ref() else {
+        let Some(query) = self
+            .model
+            .read(cx)
+            .active_query
+            .as_ref()
+            .filter(|query| query.inversion().is_none())
+        else {
             return;
         };
         let query = query.clone().with_replacement(self.replacement(cx));
 
+        let match_ranges = &self.model.read(cx).match_ranges;
+        if match_ranges.is_empty() {
+            return;
+        }
+        let match_count = match_ranges.len();
+        let file_count = match_ranges
+            .iter()
+            .map(|range| range.start.buffer_id)
+            .collect::<HashSet<_>>()
+            .len();
+        if file_count <= SearchSettings::get_global(cx).replace_all_confirmation_threshold {
+            self.replace_all_matches(&query, cx);
+            return;
+        }
+
+        let message = format!(
+            "Replace {} occurrences across {} files?",
+            match_count, file_count
+        );
+        let search_id = self.model.read(cx).search_id;
+        let answer = cx.prompt(
+            PromptLevel::Warning,
+            &message,
+            None,
+            &["Replace All", "Cancel"],
+        );
+        cx.spawn(|this, mut cx| async move {
+            if matches!(answer.await, Ok(0)) {
+                this.update(&mut cx, |this, cx| {
+                    // The matches may have changed while the prompt was open, and the answer
+                    // only covers the ones it described.
+                    let model = this.model.read(cx);
+                    if model.search_id == search_id && model.match_ranges.len() == match_count {
+                        this.replace_all_matches(&query, cx);
+                    }
+                })
+                .ok();
+            }
+        })
+        .detach();
+    }
+
+    fn replace_all_matches(&mut self, query: &SearchQuery, cx: &mut ViewContext<Self>) {
         let match_ranges = self
             .model
             .update(cx, |model, _| mem::take(&mut model.match_ranges));
@@ -590,14 +1862,20 @@ impl ProjectSearchView {
             return;
         }
 
-        self.results_editor.update(cx, |editor, cx| {
+        self.update_results_editable(cx, |editor, cx| {
             for item in &match_ranges {
-                editor.replace(item, &query, cx);
+                editor.replace(item, query, cx);
             }
         });
 
-        self.model.update(cx, |model, _cx| {
+        // The replacements may match the query themselves, so search their excerpts once more.
+        let excerpt_ids = match_ranges
+            .iter()
+            .map(|range| range.start.excerpt_id)
+            .collect();
+        self.model.update(cx, |model, cx| {
             model.match_ranges = match_ranges;
+            model.refresh_excerpt_match_ranges(excerpt_ids, cx);
         });
     }
 
@@ -608,38 +1886,54 @@ impl ProjectSearchView {
     ) -> Self {
         let project;
         let excerpts;
+        let search_all_windows;
         let mut replacement_text = None;
         let mut query_text = String::new();
         let mut subscriptions = Vec::new();
 
         // Read in settings if available
-        let (mut options, filters_enabled) = if let Some(settings) = settings {
+        let (mut options, mut filters_enabled) = if let Some(settings) = settings {
             (settings.search_options, settings.filters_enabled)
         } else {
-            (SearchOptions::NONE, false)
+            (
+                SearchOptions::NONE,
+                SearchSettings::get_global(cx).show_filters_by_default,
+            )
         };
 
         {
             let model = model.read(cx);
             project = model.project.clone();
             excerpts = model.excerpts.clone();
+            search_all_windows = model.search_all_windows;
             if let Some(active_query) = model.active_query.as_ref() {
-                query_text = active_query.as_str().to_string();
+                query_text = model
+                    .last_search_query_text
+                    .clone()
+                    .unwrap_or_else(|| active_query.as_str().to_string());
                 replacement_text = active_query.replacement().map(ToOwned::to_owned);
                 options = SearchOptions::from_query(active_query);
             }
         }
         subscriptions.push(cx.observe(&model, |this, _, cx| this.model_changed(cx)));
 
+        let default_filters = load_default_search_filters(&project, cx);
+        if default_filters.is_some() {
+            filters_enabled = true;
+        }
+
         let query_editor = cx.new_view(|cx| {
             let mut editor = Editor::single_line(cx);
-            editor.set_placeholder_text("Search all files..", cx);
+            editor.set_placeholder_text(query_placeholder(options), cx);
             editor.set_text(query_text, cx);
             editor
         });
         // Subscribe to query_editor in order to reraise editor events for workspace item activation purposes
         subscriptions.push(
-            cx.subscribe(&query_editor, |_, _, event: &EditorEvent, cx| {
+            cx.subscribe(&query_editor, |this, _, event: &EditorEvent, cx| {
+                if matches!(event, EditorEvent::BufferEdited) {
+                    this.update_regex_preview(cx);
+                }
                 cx.emit(ViewEvent::EditorEvent(event.clone()))
             }),
         );
@@ -651,17 +1945,81 @@ impl ProjectSearchView {
             }
             editor
         });
+        let searchable_results = SearchSettings::get_global(cx).searchable_results;
         let results_editor = cx.new_view(|cx| {
             let mut editor = Editor::for_multibuffer(excerpts, Some(project.clone()), cx);
-            editor.set_searchable(false);
+            editor.set_searchable(searchable_results);
+            editor.set_read_only(true);
+            editor.show_search_highlights_in_scrollbar::<Self>();
+            editor.show_active_search_highlight_in_scrollbar::<ActiveMatchHighlight>();
+            editor.set_show_worktree_root_in_excerpt_headers(search_all_windows, cx);
+            editor.set_show_absolute_paths_in_excerpt_headers(
+                SearchSettings::get_global(cx).path_display == PathDisplay::Absolute,
+                cx,
+            );
+            let search_view = cx.view().downgrade();
+            editor.set_custom_context_menu(move |editor, point, cx| {
+                let search_view = search_view.upgrade()?;
+                let snapshot = editor.snapshot(cx);
+                let position = snapshot
+                    .buffer_snapshot
+                    .anchor_before(point.to_point(&snapshot.display_snapshot));
+                let range = search_view.read(cx).replaceable_match_at(&position, cx);
+                let is_truncated = search_view
+                    .read(cx)
+                    .truncated_line_edges
+                    .iter()
+                    .any(|edge| edge.excerpt_id == position.excerpt_id);
+                if range.is_none() && !is_truncated {
+                    return None;
+                }
+                let search_view = search_view.downgrade();
+                Some(ContextMenu::build(cx, move |menu, _| {
+                    let menu = if let Some(range) = range {
+                        let search_view = search_view.clone();
+                        menu.entry("Replace This Match", None, move |cx| {
+                            search_view
+                                .update(cx, |search_view, cx| search_view.replace_match(&range, cx))
+                                .ok();
+                        })
+                    } else {
+                        menu
+                    };
+                    if is_truncated {
+                        menu.entry("Show Whole Line", None, move |cx| {
+                            search_view
+                                .update(cx, |search_view, cx| {
+                                    search_view
+                                        .expand_excerpts_to_whole_lines([position.excerpt_id], cx)
+                                })
+                                .ok();
+                        })
+                    } else {
+                        menu
+                    }
+                }))
+            });
             editor
         });
+        subscriptions.push(cx.observe_global::<SettingsStore>(|this, cx| {
+            let settings = SearchSettings::get_global(cx);
+            let show_absolute_paths = settings.path_display == PathDisplay::Absolute;
+            this.searchable_results = settings.searchable_results;
+            let searchable_results = this.searchable_results;
+            this.results_editor.update(cx, |editor, cx| {
+                editor.set_show_absolute_paths_in_excerpt_headers(show_absolute_paths, cx);
+                editor.set_searchable(searchable_results);
+            });
+        }));
         subscriptions.push(cx.observe(&results_editor, |_, _, cx| cx.emit(ViewEvent::UpdateTab)));
 
         subscriptions.push(
             cx.subscribe(&results_editor, |this, _, event: &EditorEvent, cx| {
-                if matches!(event, editor::EditorEvent::SelectionsChanged { .. }) {
-                    this.update_match_index(cx);
+                match event {
+                    EditorEvent::SelectionsChanged { .. } => this.update_match_index(cx),
+                    EditorEvent::Edited => this.schedule_match_refresh(cx),
+                    EditorEvent::BufferEdited => this.update_truncated_line_markers(cx),
+                    _ => {}
                 }
                 // Reraise editor events for workspace item activation purposes
                 cx.emit(ViewEvent::EditorEvent(event.clone()));
@@ -671,7 +2029,10 @@ impl ProjectSearchView {
         let included_files_editor = cx.new_view(|cx| {
             let mut editor = Editor::single_line(cx);
             editor.set_placeholder_text("Include: crates/**/*.toml", cx);
-
+            PathFilterCompletionProvider::register(&mut editor, project.clone(), cx);
+            if let Some(filters) = &default_filters {
+                editor.set_text(filters.included.as_str(), cx);
+            }
             editor
         });
         // Subscribe to include_files_editor in order to reraise editor events for workspace item activation purposes
@@ -684,7 +2045,10 @@ impl ProjectSearchView {
         let excluded_files_editor = cx.new_view(|cx| {
             let mut editor = Editor::single_line(cx);
             editor.set_placeholder_text("Exclude: vendor/*, *.lock", cx);
-
+            PathFilterCompletionProvider::register(&mut editor, project.clone(), cx);
+            if let Some(filters) = &default_filters {
+                editor.set_text(filters.excluded.as_str(), cx);
+            }
             editor
         });
         // Subscribe to excluded_files_editor in order to reraise editor events for workspace item activation purposes
@@ -697,7 +2061,7 @@ impl ProjectSearchView {
         let focus_handle = cx.focus_handle();
         subscriptions.push(cx.on_focus_in(&focus_handle, |this, cx| {
             if this.focus_handle.is_focused(cx) {
-                if this.has_matches() {
+                if this.has_results(cx) {
                     this.results_editor.focus_handle(cx).focus(cx);
                 } else {
                     this.query_editor.focus_handle(cx).focus(cx);
@@ -715,15 +2079,34 @@ impl ProjectSearchView {
             results_editor,
             search_options: options,
             panels_with_errors: HashSet::default(),
+            query_error: None,
+            regex_preview: None,
             active_match_index: None,
             query_editor_was_focused: false,
+            last_query_cursor: None,
             included_files_editor,
             excluded_files_editor,
             filters_enabled,
             replace_enabled: false,
+            edit_results_enabled: false,
+            searchable_results,
+            literal_enabled: false,
+            origin_file: None,
+            exclude_origin_file: false,
+            pinned: false,
+            search_pending: false,
+            match_split_pane: None,
+            dismissed_unreadable_files_search_id: None,
+            edited_excerpts: HashSet::default(),
+            pending_match_refresh: None,
+            replacing_results: false,
+            truncated_line_edges: Vec::new(),
+            project_headers: Vec::new(),
+            project_header_blocks: HashSet::default(),
             _subscriptions: subscriptions,
         };
         this.model_changed(cx);
+        this.update_regex_preview(cx);
         this
     }
 
@@ -731,11 +2114,60 @@ impl ProjectSearchView {
         workspace: &mut Workspace,
         dir_path: &Path,
         cx: &mut ViewContext<Workspace>,
+    ) {
+        Self::new_search_in_directory_with_query(workspace, dir_path, None, cx)
+    }
+
+    /// Like `new_search_in_directory`, but a given query is filled in and searched for right away.
+    pub fn new_search_in_directory_with_query(
+        workspace: &mut Workspace,
+        dir_path: &Path,
+        query: Option<&str>,
+        cx: &mut ViewContext<Workspace>,
     ) {
         let Some(filter_str) = dir_path.to_str() else {
             return;
         };
+        Self::new_search_with_included_files(workspace, filter_str, query, cx);
+    }
+
+    /// Like `new_search_in_directory`, but only the given file is searched.
+    pub fn new_search_in_file(
+        workspace: &mut Workspace,
+        file_path: &Path,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(filter_str) = Self::file_include_glob(file_path) else {
+            return;
+        };
+        Self::new_search_with_included_files(workspace, &filter_str, None, cx);
+    }
+
+    /// Returns a glob that matches exactly the given file, escaping any glob syntax in its
+    /// path, for the include or exclude filters. Paths that can't be expressed as a single
+    /// glob, such as those containing the `,` that separates globs, yield `None`.
+    fn file_include_glob(file_path: &Path) -> Option<String> {
+        let path = file_path.to_str()?;
+        if path.contains(',') {
+            return None;
+        }
+        let mut glob = String::with_capacity(path.len());
+        for c in path.chars() {
+            if matches!(c, '*' | '?' | '[' | ']' | '{' | '}') {
+                glob.extend(['[', c, ']']);
+            } else {
+                glob.push(c);
+            }
+        }
+        Some(glob)
+    }
 
+    fn new_search_with_included_files(
+        workspace: &mut Workspace,
+        filter_str: &str,
+        query: Option<&str>,
+        cx: &mut ViewContext<Workspace>,
+    ) {
         let model = cx.new_model(|cx| ProjectSearch::new(workspace.project().clone(), cx));
         let search = cx.new_view(|cx| ProjectSearchView::new(model, cx, None));
         workspace.add_item_to_active_pane(Box::new(search.clone()), cx);
@@ -744,24 +2176,70 @@ impl ProjectSearchView {
                 .included_files_editor
                 .update(cx, |editor, cx| editor.set_text(filter_str, cx));
             search.filters_enabled = true;
+            if let Some(query) = query {
+                search.set_query(query, cx);
+                search.search(cx);
+            }
             search.focus_query_editor(cx)
         });
     }
 
-    // Re-activate the most recently activated search in this pane or the most recent if it has been closed.
-    // If no search exists in the workspace, create a new one.
+    /// Re-activate the most recently activated search in this pane or the most recent if it has been closed.
+    /// Pinned searches are never reused. If no other search exists in the workspace, create a new one.
     fn deploy_search(
         workspace: &mut Workspace,
         action: &workspace::DeploySearch,
         cx: &mut ViewContext<Workspace>,
     ) {
-        let existing = workspace
+        let existing = Self::reusable_search(workspace, cx);
+        Self::existing_or_new_search(workspace, existing, action, cx);
+    }
+
+    fn reusable_search(workspace: &Workspace, cx: &AppContext) -> Option<View<ProjectSearchView>> {
+        workspace
             .active_pane()
             .read(cx)
             .items()
-            .find_map(|item| item.downcast::<ProjectSearchView>());
+            .filter_map(|item| item.downcast::<ProjectSearchView>())
+            .find(|search| !search.read(cx).pinned)
+    }
 
-        Self::existing_or_new_search(workspace, existing, action, cx);
+    /// Search the project for the text of any non-empty selection in the active editor at once.
+    fn search_selections(
+        workspace: &mut Workspace,
+        _: &SearchSelections,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(editor) = workspace
+            .active_item(cx)
+            .and_then(|item| item.act_as::<Editor>(cx))
+        else {
+            return;
+        };
+        let selected_texts = editor.update(cx, |editor, cx| {
+            let buffer = editor.buffer().read(cx).snapshot(cx);
+            editor
+                .selections
+                .all::<usize>(cx)
+                .into_iter()
+                .filter(|selection| !selection.is_empty())
+                .map(|selection| buffer.text_for_range(selection.range()).collect::<String>())
+                .collect::<Vec<_>>()
+        });
+        let Some((query, is_regex)) = selections_query(&selected_texts) else {
+            return;
+        };
+
+        let existing = Self::reusable_search(workspace, cx);
+        let search = Self::existing_or_new_search(workspace, existing, &DeploySearch::find(), cx);
+        search.update(cx, |search, cx| {
+            search.set_query(&query, cx);
+            if is_regex {
+                search.search_options.insert(SearchOptions::REGEX);
+                search.update_query_placeholder(cx);
+            }
+            search.search(cx);
+        });
     }
 
     fn search_in_new(workspace: &mut Workspace, _: &SearchInNew, cx: &mut ViewContext<Workspace>) {
@@ -770,21 +2248,33 @@ impl ProjectSearchView {
             .and_then(|item| item.downcast::<ProjectSearchView>())
         {
             let new_query = search_view.update(cx, |search_view, cx| {
-                let new_query = search_view.build_search_query(cx);
+                let new_query = search_view.build_search_query(cx).map(|query| {
+                    let query_text = search_view.searched_query_text(cx);
+                    (query, query_text)
+                });
                 if new_query.is_some() {
-                    if let Some(old_query) = search_view.model.read(cx).active_query.clone() {
+                    let model = search_view.model.read(cx);
+                    let old_query = model.active_query.clone().map(|old_query| {
+                        let old_query_text = model
+                            .last_search_query_text
+                            .clone()
+                            .unwrap_or_else(|| old_query.as_str().to_string());
+                        (old_query, old_query_text)
+                    });
+                    if let Some((old_query, old_query_text)) = old_query {
                         search_view.query_editor.update(cx, |editor, cx| {
-                            editor.set_text(old_query.as_str(), cx);
+                            editor.set_text(old_query_text, cx);
                         });
                         search_view.search_options = SearchOptions::from_query(&old_query);
+                        search_view.update_query_placeholder(cx);
                     }
                 }
                 new_query
             });
-            if let Some(new_query) = new_query {
+            if let Some((new_query, new_query_text)) = new_query {
                 let model = cx.new_model(|cx| {
                     let mut model = ProjectSearch::new(workspace.project().clone(), cx);
-                    model.search(new_query, cx);
+                    model.search(new_query, new_query_text, cx);
                     model
                 });
                 workspace.add_item_to_active_pane(
@@ -795,30 +2285,130 @@ impl ProjectSearchView {
         }
     }
 
-    // Add another search tab to the workspace.
-    fn new_search(
+    /// Open the active match in a regular editor and jump to the definition of the symbol there.
+    fn go_to_match_definition(
         workspace: &mut Workspace,
-        _: &workspace::NewSearch,
+        _: &GoToMatchDefinition,
         cx: &mut ViewContext<Workspace>,
     ) {
-        Self::existing_or_new_search(workspace, None, &DeploySearch::find(), cx)
+        let Some((buffer, point)) = workspace
+            .active_item(cx)
+            .and_then(|item| item.downcast::<ProjectSearchView>())
+            .and_then(|search_view| search_view.read(cx).active_match_location(cx))
+        else {
+            return;
+        };
+
+        let pane = workspace.active_pane().clone();
+        let editor = workspace.open_project_item::<Editor>(pane, buffer, cx);
+        editor.update(cx, |editor, cx| {
+            editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                s.select_ranges([point..point])
+            });
+            // Without a language server there are no definitions, and the cursor stays on the match.
+            editor
+                .go_to_definition(&GoToDefinition, cx)
+                .detach_and_log_err(cx);
+        });
     }
 
-    fn existing_or_new_search(
+    /// Open the active match's file in a split beside the results, keeping the results focused
+    /// so that the next matches can be reviewed in the same split.
+    fn open_match_in_split(
         workspace: &mut Workspace,
-        existing: Option<View<ProjectSearchView>>,
-        action: &workspace::DeploySearch,
+        _: &OpenMatchInSplit,
         cx: &mut ViewContext<Workspace>,
     ) {
-        let query = workspace.active_item(cx).and_then(|item| {
-            let editor = item.act_as::<Editor>(cx)?;
-            let query = editor.query_suggestion(cx);
+        let Some(search_view) = workspace
+            .active_item(cx)
+            .and_then(|item| item.downcast::<ProjectSearchView>())
+        else {
+            return;
+        };
+        let Some((buffer, point)) = search_view.read(cx).active_match_location(cx) else {
+            return;
+        };
+
+        let results_pane = workspace.active_pane().clone();
+        let split_pane = search_view
+            .read(cx)
+            .match_split_pane
+            .as_ref()
+            .and_then(|pane| pane.upgrade())
+            .filter(|pane| pane != &results_pane && workspace.panes().contains(pane))
+            .unwrap_or_else(|| {
+                workspace.split_pane(results_pane.clone(), SplitDirection::Right, cx)
+            });
+        search_view.update(cx, |search_view, _| {
+            search_view.match_split_pane = Some(split_pane.downgrade());
+        });
+
+        let editor = workspace.open_project_item::<Editor>(split_pane, buffer, cx);
+        editor.update(cx, |editor, cx| {
+            editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                s.select_ranges([point..point])
+            });
+        });
+        workspace.activate_item(&search_view, cx);
+    }
+
+    fn active_match_location(&self, cx: &AppContext) -> Option<(Model<Buffer>, language::Point)> {
+        let model = self.model.read(cx);
+        let range = model.match_ranges.get(self.active_match_index?)?;
+        let buffer = model.excerpts.read(cx).buffer(range.start.buffer_id?)?;
+        let point = range.start.text_anchor.to_point(buffer.read(cx));
+        Some((buffer, point))
+    }
+
+    /// Add a search tab for the project's preset with the given name, and run it.
+    fn apply_search_preset(
+        workspace: &mut Workspace,
+        action: &ApplySearchPreset,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(preset) = load_search_presets(workspace.project(), cx)
+            .into_iter()
+            .find(|preset| preset.name == action.name)
+        else {
+            return;
+        };
+
+        let model = cx.new_model(|cx| ProjectSearch::new(workspace.project().clone(), cx));
+        let search = cx.new_view(|cx| ProjectSearchView::new(model, cx, None));
+        workspace.add_item_to_active_pane(Box::new(search.clone()), cx);
+        search.update(cx, |search, cx| search.apply_preset(preset, cx));
+    }
+
+    // Add another search tab to the workspace.
+    fn new_search(
+        workspace: &mut Workspace,
+        _: &workspace::NewSearch,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        Self::existing_or_new_search(workspace, None, &DeploySearch::find(), cx);
+    }
+
+    fn existing_or_new_search(
+        workspace: &mut Workspace,
+        existing: Option<View<ProjectSearchView>>,
+        action: &workspace::DeploySearch,
+        cx: &mut ViewContext<Workspace>,
+    ) -> View<ProjectSearchView> {
+        let query = workspace.active_item(cx).and_then(|item| {
+            let editor = item.act_as::<Editor>(cx)?;
+            let query = editor.query_suggestion(cx);
             if query.is_empty() {
                 None
             } else {
                 Some(query)
             }
         });
+        let origin_file = workspace.active_item(cx).and_then(|item| {
+            let editor = item.act_as::<Editor>(cx)?;
+            let buffer = editor.read(cx).buffer().read(cx).as_singleton()?;
+            let file = buffer.read(cx).file()?;
+            Some(filter_path(workspace.project().read(cx), file.as_ref(), cx))
+        });
 
         let search = if let Some(existing) = existing {
             workspace.activate_item(&existing, cx);
@@ -836,7 +2426,11 @@ impl ProjectSearchView {
             };
 
             let model = cx.new_model(|cx| ProjectSearch::new(workspace.project().clone(), cx));
-            let view = cx.new_view(|cx| ProjectSearchView::new(model, cx, settings));
+            let view = cx.new_view(|cx| {
+                let mut view = ProjectSearchView::new(model, cx, settings);
+                view.origin_file = origin_file;
+                view
+            });
 
             workspace.add_item_to_active_pane(Box::new(view.clone()), cx);
             view
@@ -849,56 +2443,78 @@ impl ProjectSearchView {
             }
             search.focus_query_editor(cx)
         });
+        search
     }
 
     fn search(&mut self, cx: &mut ViewContext<Self>) {
         if let Some(query) = self.build_search_query(cx) {
-            self.model.update(cx, |model, cx| model.search(query, cx));
+            let query_text = self.searched_query_text(cx);
+            self.model
+                .update(cx, |model, cx| model.search(query, query_text, cx));
         }
     }
 
+    /// The query as written in the query editor, inline filters included, so that restoring
+    /// it keeps its filters. This is the text before literal mode escapes it, so that
+    /// searching for it again doesn't escape it twice.
+    fn searched_query_text(&self, cx: &AppContext) -> String {
+        self.query_editor.read(cx).text(cx)
+    }
+
     fn build_search_query(&mut self, cx: &mut ViewContext<Self>) -> Option<SearchQuery> {
         // Do not bail early in this function, as we want to fill out `self.panels_with_errors`.
-        let text = self.query_editor.read(cx).text(cx);
-        let included_files =
-            match Self::parse_path_matches(&self.included_files_editor.read(cx).text(cx)) {
-                Ok(included_files) => {
-                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Include);
-                    if should_unmark_error {
-                        cx.notify();
-                    }
-                    included_files
+        let query_text = self.query_editor.read(cx).text(cx);
+        let (text, inline_included_files, inline_excluded_files, inline_languages) =
+            Self::parse_inline_filters(&query_text);
+        let included_files_text = self.included_files_editor.read(cx).text(cx);
+        let included_globs = included_files_text.split(',').chain(inline_included_files);
+        let included_files = match Self::parse_path_matches(included_globs) {
+            Ok(included_files) => {
+                let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Include);
+                if should_unmark_error {
+                    cx.notify();
                 }
-                Err(_e) => {
-                    let should_mark_error = self.panels_with_errors.insert(InputPanel::Include);
-                    if should_mark_error {
-                        cx.notify();
-                    }
-                    vec![]
+                included_files
+            }
+            Err(_e) => {
+                let should_mark_error = self.panels_with_errors.insert(InputPanel::Include);
+                if should_mark_error {
+                    cx.notify();
                 }
-            };
-        let excluded_files =
-            match Self::parse_path_matches(&self.excluded_files_editor.read(cx).text(cx)) {
-                Ok(excluded_files) => {
-                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Exclude);
-                    if should_unmark_error {
-                        cx.notify();
-                    }
-
-                    excluded_files
+                vec![]
+            }
+        };
+        let excluded_files_text = self.excluded_files_editor.read(cx).text(cx);
+        let excluded_origin_file = self
+            .origin_file
+            .as_ref()
+            .filter(|_| self.exclude_origin_file)
+            .and_then(|path| Self::file_include_glob(path));
+        let excluded_globs = excluded_files_text
+            .split(',')
+            .chain(inline_excluded_files)
+            .chain(excluded_origin_file.as_deref());
+        let excluded_files = match Self::parse_path_matches(excluded_globs) {
+            Ok(excluded_files) => {
+                let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Exclude);
+                if should_unmark_error {
+                    cx.notify();
                 }
-                Err(_e) => {
-                    let should_mark_error = self.panels_with_errors.insert(InputPanel::Exclude);
-                    if should_mark_error {
-                        cx.notify();
-                    }
-                    vec![]
+
+                excluded_files
+            }
+            Err(_e) => {
+                let should_mark_error = self.panels_with_errors.insert(InputPanel::Exclude);
+                if should_mark_error {
+                    cx.notify();
                 }
-            };
+                vec![]
+            }
+        };
 
         let query = if self.search_options.contains(SearchOptions::REGEX) {
             match SearchQuery::regex(
-                text,
+                self.regex_text(text),
                 self.search_options.contains(SearchOptions::WHOLE_WORD),
                 self.search_options.contains(SearchOptions::CASE_SENSITIVE),
                 self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
@@ -906,6 +2522,7 @@ impl ProjectSearchView {
                 excluded_files,
             ) {
                 Ok(query) => {
+                    self.query_error = None;
                     let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Query);
                     if should_unmark_error {
                         cx.notify();
@@ -913,7 +2530,9 @@ impl ProjectSearchView {
 
                     Some(query)
                 }
-                Err(_e) => {
+                Err(e) => {
+                    // Surface why the regex failed to compile, e.g. an unsupported inline flag.
+                    self.query_error = Some(e.to_string().into());
                     let should_mark_error = self.panels_with_errors.insert(InputPanel::Query);
                     if should_mark_error {
                         cx.notify();
@@ -932,6 +2551,7 @@ impl ProjectSearchView {
                 excluded_files,
             ) {
                 Ok(query) => {
+                    self.query_error = None;
                     let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Query);
                     if should_unmark_error {
                         cx.notify();
@@ -949,17 +2569,104 @@ impl ProjectSearchView {
                 }
             }
         };
+        let languages = match self.resolve_languages(inline_languages, cx) {
+            Ok(languages) => languages,
+            Err(unknown_language) => {
+                self.query_error = Some(format!("Unknown language: {unknown_language}").into());
+                let should_mark_error = self.panels_with_errors.insert(InputPanel::Query);
+                if should_mark_error {
+                    cx.notify();
+                }
+                Vec::new()
+            }
+        };
         if !self.panels_with_errors.is_empty() {
             return None;
         }
         if query.as_ref().is_some_and(|query| query.is_empty()) {
             return None;
         }
-        query
+        let inversion = if !self.search_options.contains(SearchOptions::INVERT_MATCHES) {
+            None
+        } else if self.search_options.contains(SearchOptions::INVERT_FILES) {
+            Some(Inversion::Files)
+        } else {
+            Some(Inversion::Lines)
+        };
+        let mut query = query.map(|query| query.with_languages(languages));
+        if self
+            .search_options
+            .contains(SearchOptions::GIT_TRACKED_ONLY)
+        {
+            query = query.map(|query| query.with_git_tracked_only());
+        }
+        query.map(|query| match inversion {
+            Some(inversion) => query.with_inversion(inversion),
+            None => query,
+        })
+    }
+
+    /// Splits leading `path:<glob>`, `-path:<glob>` and `lang:<language>` tokens off the query,
+    /// so that `path:src/**/*.rs error handling` searches for `error handling` within
+    /// `src/**/*.rs`, and `lang:rust unwrap` searches for `unwrap` in Rust files only.
+    /// Tokens without a value, like a bare `path:`, are searched for as written.
+    /// Returns the remaining query text along with the included and excluded globs and languages.
+    fn parse_inline_filters(text: &str) -> (&str, Vec<&str>, Vec<&str>, Vec<&str>) {
+        let mut included_globs = Vec::new();
+        let mut excluded_globs = Vec::new();
+        let mut languages = Vec::new();
+        let mut query = text;
+        loop {
+            let (token, rest) = query
+                .trim_start()
+                .split_once(char::is_whitespace)
+                .unwrap_or((query.trim_start(), ""));
+            let value = |prefix: &str| token.strip_prefix(prefix).filter(|value| !value.is_empty());
+            if let Some(glob) = value("-path:") {
+                excluded_globs.push(glob);
+            } else if let Some(glob) = value("path:") {
+                included_globs.push(glob);
+            } else if let Some(language) = value("lang:") {
+                languages.push(language);
+            } else {
+                break;
+            }
+            query = rest.trim_start();
+        }
+        (query, included_globs, excluded_globs, languages)
+    }
+
+    /// Resolves language names against the project's language registry, ignoring case, and
+    /// returns the names of the languages as registered or the first unknown name.
+    fn resolve_languages<'a>(
+        &self,
+        names: impl IntoIterator<Item = &'a str>,
+        cx: &AppContext,
+    ) -> Result<Vec<Arc<str>>, &'a str> {
+        let language_names = self
+            .model
+            .read(cx)
+            .project
+            .read(cx)
+            .languages()
+            .language_names();
+        names
+            .into_iter()
+            .map(|name| {
+                language_names
+                    .iter()
+                    .find(|language_name| language_name.eq_ignore_ascii_case(name))
+                    .map(|language_name| Arc::from(language_name.as_str()))
+                    .ok_or(name)
+            })
+            .collect()
     }
 
-    fn parse_path_matches(text: &str) -> anyhow::Result<Vec<PathMatcher>> {
-        text.split(',')
+    fn parse_path_matches<'a>(
+        globs: impl IntoIterator<Item = &'a str>,
+    ) -> anyhow::Result<Vec<PathMatcher>> {
+        globs
+            .into_iter()
             .map(str::trim)
             .filter(|maybe_glob_str| !maybe_glob_str.is_empty())
             .map(|maybe_glob_str| {
@@ -970,23 +2677,66 @@ impl ProjectSearchView {
     }
 
     fn select_match(&mut self, direction: Direction, cx: &mut ViewContext<Self>) {
+        // Matches keep being appended while a search streams in, so navigate from the match
+        // at the cursor within the latest ones rather than an index derived from fewer.
+        self.update_match_index(cx);
         if let Some(index) = self.active_match_index {
             let match_ranges = self.model.read(cx).match_ranges.clone();
-            let new_index = self.results_editor.update(cx, |editor, cx| {
+            let mut new_index = self.results_editor.update(cx, |editor, cx| {
                 editor.match_index_for_direction(&match_ranges, index, direction, 1, cx)
             });
 
-            let range_to_select = match_ranges[new_index].clone();
-            self.results_editor.update(cx, |editor, cx| {
-                let range_to_select = editor.range_for_match(&range_to_select);
-                editor.unfold_ranges([range_to_select.clone()], false, true, cx);
-                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
-                    s.select_ranges([range_to_select])
-                });
-            });
+            // Files deleted since the search can't be opened, so their matches are passed over.
+            for _ in 0..match_ranges.len() {
+                if !self.is_match_in_deleted_file(&match_ranges[new_index], cx) {
+                    self.select_match_range(match_ranges[new_index].clone(), cx);
+                    return;
+                }
+                new_index = match direction {
+                    Direction::Next => (new_index + 1) % match_ranges.len(),
+                    Direction::Prev => (new_index + match_ranges.len() - 1) % match_ranges.len(),
+                };
+            }
+        }
+    }
+
+    /// Selects the first match in the next or previous file of the results, skipping the
+    /// remaining matches in the current one.
+    fn select_file(&mut self, direction: Direction, cx: &mut ViewContext<Self>) {
+        self.update_match_index(cx);
+        if let Some(index) = self.active_match_index {
+            let match_ranges = self.model.read(cx).match_ranges.clone();
+            let mut new_index = index;
+            for _ in 0..match_ranges.len() {
+                new_index = file_match_index(&match_ranges, new_index, direction);
+                if !self.is_match_in_deleted_file(&match_ranges[new_index], cx) {
+                    self.select_match_range(match_ranges[new_index].clone(), cx);
+                    return;
+                }
+            }
         }
     }
 
+    fn is_match_in_deleted_file(&self, range: &Range<Anchor>, cx: &AppContext) -> bool {
+        range
+            .start
+            .buffer_id
+            .and_then(|buffer_id| self.model.read(cx).excerpts.read(cx).buffer(buffer_id))
+            .and_then(|buffer| buffer.read(cx).file().map(|file| file.is_deleted()))
+            .unwrap_or(false)
+    }
+
+    fn select_match_range(&mut self, range: Range<Anchor>, cx: &mut ViewContext<Self>) {
+        let autoscroll = SearchSettings::get_global(cx)
+            .autoscroll_on_navigate
+            .then(Autoscroll::fit);
+        self.results_editor.update(cx, |editor, cx| {
+            let range_to_select = editor.range_for_match(&range);
+            editor.unfold_ranges([range_to_select.clone()], false, true, cx);
+            editor.change_selections(autoscroll, cx, |s| s.select_ranges([range_to_select]));
+        });
+    }
+
     fn focus_query_editor(&mut self, cx: &mut ViewContext<Self>) {
         self.query_editor.update(cx, |query_editor, cx| {
             query_editor.select_all(&SelectAll, cx);
@@ -1001,23 +2751,50 @@ impl ProjectSearchView {
             .update(cx, |query_editor, cx| query_editor.set_text(query, cx));
     }
 
-    fn focus_results_editor(&mut self, cx: &mut ViewContext<Self>) {
+    /// Focuses the query editor with the cursor where it was when focus last moved to the results,
+    /// falling back to selecting the whole query.
+    fn restore_query_focus(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(last_cursor) = self.last_query_cursor else {
+            return self.focus_query_editor(cx);
+        };
         self.query_editor.update(cx, |query_editor, cx| {
+            let cursor = query_editor
+                .buffer()
+                .read(cx)
+                .snapshot(cx)
+                .clip_offset(last_cursor, Bias::Left);
+            query_editor.change_selections(None, cx, |s| s.select_ranges([cursor..cursor]));
+        });
+        self.query_editor_was_focused = true;
+        let editor_handle = self.query_editor.focus_handle(cx);
+        cx.focus(&editor_handle);
+    }
+
+    fn focus_results_editor(&mut self, cx: &mut ViewContext<Self>) {
+        let query_cursor = self.query_editor.update(cx, |query_editor, cx| {
             let cursor = query_editor.selections.newest_anchor().head();
             query_editor.change_selections(None, cx, |s| s.select_ranges([cursor..cursor]));
+            query_editor.selections.newest::<usize>(cx).head()
         });
+        self.last_query_cursor = Some(query_cursor);
         self.query_editor_was_focused = false;
         let results_handle = self.results_editor.focus_handle(cx);
         cx.focus(&results_handle);
     }
 
     fn model_changed(&mut self, cx: &mut ViewContext<Self>) {
+        self.search_pending = self.model.read(cx).pending_search.is_some();
+        self.update_project_headers(cx);
         let match_ranges = self.model.read(cx).match_ranges.clone();
         if match_ranges.is_empty() {
-            self.active_match_index = None;
+            self.results_editor.update(cx, |editor, cx| {
+                editor.clear_background_highlights::<Self>(cx);
+            });
+            self.set_active_match_index(None, cx);
         } else {
-            self.active_match_index = Some(0);
             self.update_match_index(cx);
+            // The match ranges may have changed without the active index changing.
+            self.update_active_match_highlight(cx);
             let prev_search_id = mem::replace(&mut self.search_id, self.model.read(cx).search_id);
             let is_new_search = self.search_id != prev_search_id;
             self.results_editor.update(cx, |editor, cx| {
@@ -1035,6 +2812,19 @@ impl ProjectSearchView {
                     |theme| theme.search_match_background,
                     cx,
                 );
+                if SearchSettings::get_global(cx).dim_context_lines {
+                    let snapshot = editor.buffer().read(cx).snapshot(cx);
+                    editor.highlight_text::<DimmedContextLines>(
+                        context_line_ranges(&match_ranges, &snapshot),
+                        HighlightStyle {
+                            fade_out: Some(0.5),
+                            ..Default::default()
+                        },
+                        cx,
+                    );
+                } else {
+                    editor.clear_highlights::<DimmedContextLines>(cx);
+                }
             });
             if is_new_search && self.query_editor.focus_handle(cx).is_focused(cx) {
                 self.focus_results_editor(cx);
@@ -1045,6 +2835,33 @@ impl ProjectSearchView {
         cx.notify();
     }
 
+    /// Searches the excerpts edited through the results editor again once the edits pause, so
+    /// that the match ranges and their highlights keep covering the text that matches the query.
+    fn schedule_match_refresh(&mut self, cx: &mut ViewContext<Self>) {
+        if self.replacing_results {
+            return;
+        }
+        // Edits are made at the selections, so those are in the excerpts that were edited.
+        let selections = self.results_editor.read(cx).selections.disjoint_anchors();
+        self.edited_excerpts.extend(
+            selections
+                .iter()
+                .flat_map(|selection| [selection.start.excerpt_id, selection.end.excerpt_id]),
+        );
+        self.pending_match_refresh = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor()
+                .timer(EDITED_RESULTS_REFRESH_DEBOUNCE)
+                .await;
+            this.update(&mut cx, |this, cx| {
+                let excerpt_ids = mem::take(&mut this.edited_excerpts);
+                this.model.update(cx, |model, cx| {
+                    model.refresh_excerpt_match_ranges(excerpt_ids, cx)
+                });
+            })
+            .ok();
+        }));
+    }
+
     fn update_match_index(&mut self, cx: &mut ViewContext<Self>) {
         let results_editor = self.results_editor.read(cx);
         let new_index = active_match_index(
@@ -1052,16 +2869,256 @@ impl ProjectSearchView {
             &results_editor.selections.newest_anchor().head(),
             &results_editor.buffer().read(cx).snapshot(cx),
         );
-        if self.active_match_index != new_index {
-            self.active_match_index = new_index;
+        self.set_active_match_index(new_index, cx);
+    }
+
+    fn set_active_match_index(&mut self, index: Option<usize>, cx: &mut ViewContext<Self>) {
+        if self.active_match_index != index {
+            self.active_match_index = index;
+            self.update_active_match_highlight(cx);
+            let total = self.model.read(cx).match_ranges.len();
+            cx.emit(ViewEvent::ActiveMatchChanged { index, total });
             cx.notify();
         }
     }
 
+    fn update_active_match_highlight(&mut self, cx: &mut ViewContext<Self>) {
+        let active_range = self
+            .active_match_index
+            .and_then(|index| self.model.read(cx).match_ranges.get(index).cloned());
+        self.results_editor
+            .update(cx, |editor, cx| match active_range {
+                Some(range) => editor.highlight_background::<ActiveMatchHighlight>(
+                    &[range],
+                    |theme| theme.search_match_background,
+                    cx,
+                ),
+                None => {
+                    editor.clear_background_highlights::<ActiveMatchHighlight>(cx);
+                }
+            });
+    }
+
     pub fn has_matches(&self) -> bool {
         self.active_match_index.is_some()
     }
 
+    /// Whether there are results to show, which stays true after edits or replacements
+    /// leave the excerpts without any matches.
+    fn has_results(&self, cx: &AppContext) -> bool {
+        self.has_matches()
+            || !self
+                .model
+                .read(cx)
+                .excerpts
+                .read(cx)
+                .excerpt_ids()
+                .is_empty()
+    }
+
+    /// The number of matches in the results, which is how many the latest search found along
+    /// with any since refreshed from edits.
+    pub fn match_count(&self, cx: &AppContext) -> usize {
+        self.model.read(cx).match_ranges.len()
+    }
+
+    /// The index of the selected match among [`Self::matches`], which is `None` only when there
+    /// are no matches.
+    pub fn active_match_index(&self) -> Option<usize> {
+        self.active_match_index
+    }
+
+    /// The matches in the results in order, each as the file it is in and its range within that
+    /// file. Matches in buffers without a file have no path.
+    pub fn matches<'a>(
+        &'a self,
+        cx: &'a AppContext,
+    ) -> impl Iterator<Item = (Option<ProjectPath>, Range<language::Point>)> + 'a {
+        let model = self.model.read(cx);
+        let excerpts = model.excerpts.read(cx);
+        model.match_ranges.iter().filter_map(move |range| {
+            let buffer = excerpts.buffer(range.start.buffer_id?)?;
+            let buffer = buffer.read(cx);
+            let path = buffer.file().map(|file| ProjectPath {
+                worktree_id: file.worktree_id(cx),
+                path: file.path().clone(),
+            });
+            let range =
+                range.start.text_anchor.to_point(buffer)..range.end.text_anchor.to_point(buffer);
+            Some((path, range))
+        })
+    }
+
+    /// Explains a finished search without matches, telling filters that matched no files apart
+    /// from files that were searched without finding the query.
+    fn no_results_summary(&self, cx: &AppContext) -> Option<SharedString> {
+        let model = self.model.read(cx);
+        if model.pending_search.is_some() || model.no_results != Some(true) {
+            return None;
+        }
+        if model.files_matched_filters? == 0 {
+            return Some("0 files matched filters".into());
+        }
+        let searched_file_count = model.searched_file_count()?;
+        Some(
+            format!(
+                "{searched_file_count} {} searched, 0 matches",
+                if searched_file_count == 1 {
+                    "file"
+                } else {
+                    "files"
+                }
+            )
+            .into(),
+        )
+    }
+
+    /// The latest query, followed by how many matches it found once it has found any, e.g.
+    /// "two (42)". The query is truncated so that the whole title fits `MAX_TAB_TITLE_LEN`.
+    fn tab_title(&self, cx: &AppContext) -> SharedString {
+        let model = self.model.read(cx);
+        let Some(query) = model
+            .last_search_query_text
+            .as_ref()
+            .map(|query| query.replace('\n', ""))
+            .filter(|query| !query.is_empty())
+        else {
+            return "Project Search".into();
+        };
+
+        let match_count = model.match_ranges.len();
+        if match_count == 0 {
+            return util::truncate_and_trailoff(&query, MAX_TAB_TITLE_LEN).into();
+        }
+        let count = format!(
+            " ({match_count}{})",
+            if model.limit_reached { "+" } else { "" }
+        );
+        // Leave room for the count and the ellipsis of a truncated query.
+        let max_query_len = MAX_TAB_TITLE_LEN.saturating_sub(count.len() + 1).max(5);
+        format!(
+            "{}{count}",
+            util::truncate_and_trailoff(&query, max_query_len)
+        )
+        .into()
+    }
+
+    /// Tells how many files the latest search couldn't read, until the notice is dismissed.
+    fn unreadable_files_notice(&self, cx: &AppContext) -> Option<SharedString> {
+        let model = self.model.read(cx);
+        let count = model.unreadable_files.len();
+        if count == 0 || self.dismissed_unreadable_files_search_id == Some(model.search_id) {
+            return None;
+        }
+        Some(
+            format!(
+                "{count} {} could not be read",
+                if count == 1 { "file" } else { "files" }
+            )
+            .into(),
+        )
+    }
+
+    /// Warns that the latest search gave up on some files because matching them took too long,
+    /// which usually means the regex is pathologically slow.
+    fn timed_out_files_notice(&self, cx: &AppContext) -> Option<SharedString> {
+        let count = self.model.read(cx).timed_out_file_count;
+        (count > 0).then(|| {
+            format!(
+                "{count} {} took too long to match",
+                if count == 1 { "file" } else { "files" }
+            )
+            .into()
+        })
+    }
+
+    /// Tells that files with results were deleted since the search, as navigating skips them.
+    fn deleted_files_notice(&self, cx: &AppContext) -> Option<SharedString> {
+        let count = self
+            .model
+            .read(cx)
+            .excerpts
+            .read(cx)
+            .all_buffers()
+            .into_iter()
+            .filter(|buffer| buffer.read(cx).file().is_some_and(|file| file.is_deleted()))
+            .count();
+        (count > 0).then(|| {
+            format!(
+                "{count} deleted {} skipped",
+                if count == 1 { "file" } else { "files" }
+            )
+            .into()
+        })
+    }
+
+    /// Lists the files the latest search couldn't read, for the notice's tooltip.
+    fn unreadable_files_list(&self, cx: &AppContext) -> SharedString {
+        const MAX_LISTED_FILES: usize = 10;
+
+        let unreadable_files = &self.model.read(cx).unreadable_files;
+        let mut list = unreadable_files
+            .iter()
+            .take(MAX_LISTED_FILES)
+            .map(|path| path.path.to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if unreadable_files.len() > MAX_LISTED_FILES {
+            list.push_str(&format!(
+                "\nand {} more",
+                unreadable_files.len() - MAX_LISTED_FILES
+            ));
+        }
+        list.into()
+    }
+
+    fn dismiss_unreadable_files_notice(&mut self, cx: &mut ViewContext<Self>) {
+        self.dismissed_unreadable_files_search_id = Some(self.model.read(cx).search_id);
+        cx.notify();
+    }
+
+    /// Whether the latest search hit the limit on results, so more matches may exist
+    /// than are shown.
+    pub fn results_truncated(&self, cx: &AppContext) -> bool {
+        self.model.read(cx).limit_reached
+    }
+
+    /// Counts the latest search's matches under each directory, including worktree roots,
+    /// so that the project panel can show where the matches live.
+    pub fn match_counts_by_directory(&self, cx: &AppContext) -> HashMap<ProjectPath, usize> {
+        let model = self.model.read(cx);
+        let excerpts = model.excerpts.read(cx);
+        // Matches are counted per buffer first, so that each file's directories are only
+        // visited once however many matches it has.
+        let mut counts_by_buffer = HashMap::<_, usize>::default();
+        for buffer_id in model
+            .match_ranges
+            .iter()
+            .filter_map(|range| range.start.buffer_id)
+        {
+            *counts_by_buffer.entry(buffer_id).or_insert(0) += 1;
+        }
+
+        let mut counts = HashMap::default();
+        for (buffer_id, match_count) in counts_by_buffer {
+            let Some(buffer) = excerpts.buffer(buffer_id) else {
+                continue;
+            };
+            let Some(file) = buffer.read(cx).file() else {
+                continue;
+            };
+            let worktree_id = file.worktree_id(cx);
+            for directory in file.path().ancestors().skip(1) {
+                let directory = ProjectPath {
+                    worktree_id,
+                    path: Arc::from(directory),
+                };
+                *counts.entry(directory).or_default() += match_count;
+            }
+        }
+        counts
+    }
+
     fn landing_text_minor(&self) -> SharedString {
         "Include/exclude specific paths with the filter option. Matching exact word and/or casing is available too.".into()
     }
@@ -1082,6 +3139,37 @@ impl ProjectSearchView {
             return self.focus_results_editor(cx);
         }
     }
+
+    /// Cancels a running search. Otherwise moves focus from the results back to the query,
+    /// or closes the search when the results aren't focused.
+    fn dismiss(&mut self, _: &Dismiss, cx: &mut ViewContext<Self>) {
+        if self.model.read(cx).pending_search.is_some() {
+            self.cancel_search(cx);
+        } else if self.results_editor.focus_handle(cx).contains_focused(cx) {
+            self.restore_query_focus(cx);
+        } else {
+            cx.emit(ViewEvent::Dismiss);
+        }
+    }
+
+    fn cancel_search(&mut self, cx: &mut ViewContext<Self>) {
+        self.model.update(cx, |model, cx| {
+            // Dropping the task stops the search, keeping the results found so far.
+            if model.pending_search.take().is_some() {
+                model.pending_notify.take();
+                cx.notify();
+            }
+        });
+    }
+
+    fn toggle_focus(&mut self, _: &ToggleFocus, cx: &mut ViewContext<Self>) {
+        if self.results_editor.focus_handle(cx).contains_focused(cx) {
+            cx.stop_propagation();
+            self.restore_query_focus(cx);
+        } else {
+            cx.propagate();
+        }
+    }
 }
 
 impl ProjectSearchBar {
@@ -1212,28 +3300,167 @@ impl ProjectSearchBar {
         }
     }
 
-    fn move_focus_to_results(&self, cx: &mut ViewContext<Self>) {
+    fn toggle_literal(&mut self, cx: &mut ViewContext<Self>) {
         if let Some(search_view) = self.active_project_search.as_ref() {
             search_view.update(cx, |search_view, cx| {
-                search_view.move_focus_to_results(cx);
+                search_view.toggle_literal(cx);
+                search_view.search(cx);
             });
             cx.notify();
         }
     }
 
-    fn is_option_enabled(&self, option: SearchOptions, cx: &AppContext) -> bool {
-        if let Some(search) = self.active_project_search.as_ref() {
-            search.read(cx).search_options.contains(option)
-        } else {
-            false
+    fn rerun_search(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view
+                    .model
+                    .update(cx, |model, cx| model.rerun_search(cx))
+            });
+            cx.notify();
         }
     }
 
-    fn next_history_query(&mut self, _: &NextHistoryQuery, cx: &mut ViewContext<Self>) {
+    fn toggle_exclude_origin_file(&mut self, cx: &mut ViewContext<Self>) {
         if let Some(search_view) = self.active_project_search.as_ref() {
             search_view.update(cx, |search_view, cx| {
-                let new_query = search_view.model.update(cx, |model, cx| {
-                    if let Some(new_query) = model.project.update(cx, |project, _| {
+                search_view.toggle_exclude_origin_file(cx);
+                search_view.search(cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn toggle_search_all_windows(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.toggle_search_all_windows(cx);
+                search_view.search(cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn toggle_pinned(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.toggle_pinned(cx));
+            cx.notify();
+        }
+    }
+
+    fn toggle_edit_results(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.toggle_edit_results(cx));
+            cx.notify();
+        }
+    }
+
+    fn save_filters_as_default(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.save_filters_as_default(cx)
+            });
+        }
+    }
+
+    fn save_search_preset(&mut self, action: &SaveSearchPreset, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.save_search_preset(action.name.clone(), cx)
+            });
+        }
+    }
+
+    fn render_presets_menu(&self, cx: &AppContext) -> Option<impl IntoElement> {
+        let project = self
+            .active_project_search
+            .as_ref()?
+            .read(cx)
+            .model
+            .read(cx)
+            .project
+            .clone();
+        Some(
+            popover_menu("project-search-presets")
+                .trigger(
+                    IconButton::new("project-search-presets-button", IconName::Menu)
+                        .tooltip(|cx| Tooltip::text("Search presets", cx)),
+                )
+                .menu(move |cx| {
+                    let presets = load_search_presets(&project, cx);
+                    Some(ContextMenu::build(cx, |menu, _| {
+                        let menu = menu
+                            .action("Save Current Search", Box::new(SaveSearchPreset::default()));
+                        if presets.is_empty() {
+                            return menu;
+                        }
+                        presets.into_iter().fold(menu.separator(), |menu, preset| {
+                            menu.action(
+                                preset.name.clone(),
+                                Box::new(ApplySearchPreset { name: preset.name }),
+                            )
+                        })
+                    }))
+                }),
+        )
+    }
+
+    fn reset_filters(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.reset_filters(cx));
+            cx.notify();
+        }
+    }
+
+    fn swap_include_exclude(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.swap_include_exclude(cx);
+                search_view.search(cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn reset_search_options(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.reset_search_options(cx);
+                search_view.search(cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn dismiss(&mut self, action: &Dismiss, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.dismiss(action, cx));
+            cx.notify();
+        }
+    }
+
+    fn move_focus_to_results(&self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.move_focus_to_results(cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn is_option_enabled(&self, option: SearchOptions, cx: &AppContext) -> bool {
+        if let Some(search) = self.active_project_search.as_ref() {
+            search.read(cx).search_options.contains(option)
+        } else {
+            false
+        }
+    }
+
+    fn next_history_query(&mut self, _: &NextHistoryQuery, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                let new_query = search_view.model.update(cx, |model, cx| {
+                    if let Some(new_query) = model.project.update(cx, |project, _| {
                         project
                             .search_history_mut()
                             .next(&mut model.search_history_cursor)
@@ -1298,6 +3525,14 @@ impl ProjectSearchBar {
         }
     }
 
+    fn select_file(&mut self, direction: Direction, cx: &mut ViewContext<Self>) {
+        if let Some(search) = self.active_project_search.as_ref() {
+            search.update(cx, |this, cx| {
+                this.select_file(direction, cx);
+            })
+        }
+    }
+
     fn render_text_input(&self, editor: &View<Editor>, cx: &ViewContext<Self>) -> impl IntoElement {
         let settings = ThemeSettings::get_global(cx);
         let text_style = TextStyle {
@@ -1336,6 +3571,7 @@ impl Render for ProjectSearchBar {
             return div();
         };
         let search = search.read(cx);
+        let option_buttons = SearchSettings::get_global(cx).option_buttons;
 
         let query_column = h_flex()
             .flex_1()
@@ -1352,26 +3588,85 @@ impl Render for ProjectSearchBar {
             .on_action(cx.listener(|this, action, cx| this.previous_history_query(action, cx)))
             .on_action(cx.listener(|this, action, cx| this.next_history_query(action, cx)))
             .child(self.render_text_input(&search.query_editor, cx))
+            .children(search.query_error.clone().map(|error| {
+                div()
+                    .id("project-search-query-error")
+                    .child(Icon::new(IconName::XCircle).color(Color::Error))
+                    .tooltip(move |cx| Tooltip::text(error.clone(), cx))
+            }))
+            .children(
+                search
+                    .regex_preview
+                    .clone()
+                    .filter(|_| search.query_error.is_none())
+                    .map(|pattern| {
+                        div()
+                            .id("project-search-regex-preview")
+                            .child(Icon::new(IconName::Regex).color(Color::Muted))
+                            .tooltip(move |cx| {
+                                Tooltip::with_meta("Searching for", None, pattern.clone(), cx)
+                            })
+                    }),
+            )
             .child(
                 h_flex()
-                    .child(SearchOptions::CASE_SENSITIVE.as_button(
-                        self.is_option_enabled(SearchOptions::CASE_SENSITIVE, cx),
-                        cx.listener(|this, _, cx| {
-                            this.toggle_search_option(SearchOptions::CASE_SENSITIVE, cx);
-                        }),
-                    ))
-                    .child(SearchOptions::WHOLE_WORD.as_button(
-                        self.is_option_enabled(SearchOptions::WHOLE_WORD, cx),
+                    .when(option_buttons.case_sensitive, |this| {
+                        this.child(SearchOptions::CASE_SENSITIVE.as_button(
+                            self.is_option_enabled(SearchOptions::CASE_SENSITIVE, cx),
+                            cx.listener(|this, _, cx| {
+                                this.toggle_search_option(SearchOptions::CASE_SENSITIVE, cx);
+                            }),
+                        ))
+                    })
+                    .when(option_buttons.whole_word, |this| {
+                        this.child(SearchOptions::WHOLE_WORD.as_button(
+                            self.is_option_enabled(SearchOptions::WHOLE_WORD, cx),
+                            cx.listener(|this, _, cx| {
+                                this.toggle_search_option(SearchOptions::WHOLE_WORD, cx);
+                            }),
+                        ))
+                    })
+                    .when(option_buttons.regex, |this| {
+                        this.child(SearchOptions::REGEX.as_button(
+                            self.is_option_enabled(SearchOptions::REGEX, cx),
+                            cx.listener(|this, _, cx| {
+                                this.toggle_search_option(SearchOptions::REGEX, cx);
+                            }),
+                        ))
+                    })
+                    .when(self.is_option_enabled(SearchOptions::REGEX, cx), |this| {
+                        this.child(
+                            IconButton::new("project-search-toggle-literal", IconName::Quote)
+                                .on_click(cx.listener(|this, _, cx| {
+                                    this.toggle_literal(cx);
+                                }))
+                                .selected(search.literal_enabled)
+                                .tooltip(|cx| {
+                                    Tooltip::for_action(
+                                        "Toggle matching the query literally",
+                                        &ToggleLiteral,
+                                        cx,
+                                    )
+                                }),
+                        )
+                    })
+                    .child(SearchOptions::INVERT_MATCHES.as_button(
+                        self.is_option_enabled(SearchOptions::INVERT_MATCHES, cx),
                         cx.listener(|this, _, cx| {
-                            this.toggle_search_option(SearchOptions::WHOLE_WORD, cx);
+                            this.toggle_search_option(SearchOptions::INVERT_MATCHES, cx);
                         }),
                     ))
-                    .child(SearchOptions::REGEX.as_button(
-                        self.is_option_enabled(SearchOptions::REGEX, cx),
-                        cx.listener(|this, _, cx| {
-                            this.toggle_search_option(SearchOptions::REGEX, cx);
-                        }),
-                    )),
+                    .when(
+                        self.is_option_enabled(SearchOptions::INVERT_MATCHES, cx),
+                        |this| {
+                            this.child(SearchOptions::INVERT_FILES.as_button(
+                                self.is_option_enabled(SearchOptions::INVERT_FILES, cx),
+                                cx.listener(|this, _, cx| {
+                                    this.toggle_search_option(SearchOptions::INVERT_FILES, cx);
+                                }),
+                            ))
+                        },
+                    ),
             );
 
         let mode_column = v_flex().items_start().justify_start().child(
@@ -1401,7 +3696,51 @@ impl Render for ProjectSearchBar {
                                 .unwrap_or_default(),
                         )
                         .tooltip(|cx| Tooltip::for_action("Toggle replace", &ToggleReplace, cx)),
-                ),
+                )
+                .child(
+                    IconButton::new("project-search-toggle-edit-results", IconName::Pencil)
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.toggle_edit_results(cx);
+                        }))
+                        .selected(
+                            self.active_project_search
+                                .as_ref()
+                                .map(|search| search.read(cx).edit_results_enabled)
+                                .unwrap_or_default(),
+                        )
+                        .tooltip(|cx| {
+                            Tooltip::for_action("Toggle editing results", &ToggleEditResults, cx)
+                        }),
+                )
+                .child(
+                    IconButton::new("project-search-toggle-all-windows", IconName::Screen)
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.toggle_search_all_windows(cx);
+                        }))
+                        .selected(
+                            self.active_project_search
+                                .as_ref()
+                                .map(|search| search.read(cx).model.read(cx).search_all_windows)
+                                .unwrap_or_default(),
+                        )
+                        .tooltip(|cx| {
+                            Tooltip::for_action("Search all windows", &ToggleSearchAllWindows, cx)
+                        }),
+                )
+                .child(
+                    IconButton::new("project-search-toggle-pinned", IconName::Pin)
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.toggle_pinned(cx);
+                        }))
+                        .selected(
+                            self.active_project_search
+                                .as_ref()
+                                .map(|search| search.read(cx).pinned)
+                                .unwrap_or_default(),
+                        )
+                        .tooltip(|cx| Tooltip::for_action("Toggle pinned", &TogglePinned, cx)),
+                )
+                .children(self.render_presets_menu(cx)),
         );
 
         let match_text = search
@@ -1418,7 +3757,13 @@ impl Render for ProjectSearchBar {
             })
             .unwrap_or_else(|| "0/0".to_string());
 
-        let limit_reached = search.model.read(cx).limit_reached;
+        let limit_reached = search.results_truncated(cx);
+        let skipped_file_count = search.model.read(cx).skipped_file_count;
+        let no_results_summary = search.no_results_summary(cx);
+        let unreadable_files_notice = search.unreadable_files_notice(cx);
+        let timed_out_files_notice = search.timed_out_files_notice(cx);
+        let deleted_files_notice = search.deleted_files_notice(cx);
+        let unreadable_files_list = search.unreadable_files_list(cx);
 
         let matches_column = h_flex()
             .child(
@@ -1464,7 +3809,77 @@ impl Render for ProjectSearchBar {
                         .child(Label::new("Search limit reached").color(Color::Warning))
                         .ml_2(),
                 )
-            });
+            })
+            .children(no_results_summary.map(|summary| {
+                div()
+                    .child(Label::new(summary).color(Color::Muted))
+                    .ml_2()
+            }))
+            .when(skipped_file_count > 0, |this| {
+                this.child(
+                    div()
+                        .id("project-search-skipped-files")
+                        .child(
+                            Label::new(format!(
+                                "{skipped_file_count} large {} skipped",
+                                if skipped_file_count == 1 {
+                                    "file"
+                                } else {
+                                    "files"
+                                }
+                            ))
+                            .color(Color::Muted),
+                        )
+                        .tooltip(|cx| {
+                            Tooltip::text(
+                                "Files larger than the search.max_file_size setting are not searched",
+                                cx,
+                            )
+                        })
+                        .ml_2(),
+                )
+            })
+            .children(timed_out_files_notice.map(|notice| {
+                div()
+                    .id("project-search-timed-out-files")
+                    .child(Label::new(notice).color(Color::Warning))
+                    .tooltip(|cx| {
+                        Tooltip::text(
+                            "Files are skipped when matching them takes longer than the search.file_match_timeout_ms setting. The regex may be pathologically slow, e.g. from nested quantifiers like (a+)+",
+                            cx,
+                        )
+                    })
+                    .ml_2()
+            }))
+            .children(deleted_files_notice.map(|notice| {
+                div()
+                    .id("project-search-deleted-files")
+                    .child(Label::new(notice).color(Color::Muted))
+                    .tooltip(|cx| {
+                        Tooltip::text("Matches in files deleted since the search are skipped", cx)
+                    })
+                    .ml_2()
+            }))
+            .children(unreadable_files_notice.map(|notice| {
+                h_flex()
+                    .id("project-search-unreadable-files")
+                    .ml_2()
+                    .gap_1()
+                    .child(Label::new(notice).color(Color::Warning))
+                    .tooltip(move |cx| Tooltip::text(unreadable_files_list.clone(), cx))
+                    .child(
+                        IconButton::new("project-search-dismiss-unreadable-files", IconName::Close)
+                            .icon_size(IconSize::XSmall)
+                            .on_click(cx.listener(|this, _, cx| {
+                                if let Some(search) = this.active_project_search.as_ref() {
+                                    search.update(cx, |search, cx| {
+                                        search.dismiss_unreadable_files_notice(cx);
+                                    })
+                                }
+                            }))
+                            .tooltip(|cx| Tooltip::text("Dismiss", cx)),
+                    )
+            }));
 
         let search_line = h_flex()
             .flex_1()
@@ -1496,6 +3911,23 @@ impl Render for ProjectSearchBar {
                         }))
                         .tooltip(|cx| Tooltip::for_action("Replace next match", &ReplaceNext, cx)),
                 )
+                .child(
+                    IconButton::new("project-search-replace-at-cursor", IconName::Replace)
+                        .on_click(cx.listener(|this, _, cx| {
+                            if let Some(search) = this.active_project_search.as_ref() {
+                                search.update(cx, |this, cx| {
+                                    this.replace_match_at_cursor(&ReplaceMatchAtCursor, cx);
+                                })
+                            }
+                        }))
+                        .tooltip(|cx| {
+                            Tooltip::for_action(
+                                "Replace match at cursor",
+                                &ReplaceMatchAtCursor,
+                                cx,
+                            )
+                        }),
+                )
                 .child(
                     IconButton::new("project-search-replace-all", IconName::ReplaceAll)
                         .on_click(cx.listener(|this, _, cx| {
@@ -1539,6 +3971,16 @@ impl Render for ProjectSearchBar {
                                     this.toggle_search_option(SearchOptions::INCLUDE_IGNORED, cx);
                                 }),
                             ),
+                        )
+                        .child(
+                            SearchOptions::GIT_TRACKED_ONLY.as_button(
+                                search
+                                    .search_options
+                                    .contains(SearchOptions::GIT_TRACKED_ONLY),
+                                cx.listener(|this, _, cx| {
+                                    this.toggle_search_option(SearchOptions::GIT_TRACKED_ONLY, cx);
+                                }),
+                            ),
                         ),
                 )
                 .child(
@@ -1552,13 +3994,33 @@ impl Render for ProjectSearchBar {
                         .border_1()
                         .border_color(search.border_color_for(InputPanel::Exclude, cx))
                         .rounded_lg()
-                        .child(self.render_text_input(&search.excluded_files_editor, cx)),
+                        .child(self.render_text_input(&search.excluded_files_editor, cx))
+                        .when(search.origin_file.is_some(), |this| {
+                            this.child(
+                                IconButton::new(
+                                    "project-search-exclude-current-file",
+                                    IconName::File,
+                                )
+                                .on_click(cx.listener(|this, _, cx| {
+                                    this.toggle_exclude_origin_file(cx);
+                                }))
+                                .selected(search.exclude_origin_file)
+                                .tooltip(|cx| {
+                                    Tooltip::for_action(
+                                        "Exclude the file this search was opened from",
+                                        &ToggleExcludeCurrentFile,
+                                        cx,
+                                    )
+                                }),
+                            )
+                        }),
                 )
         });
 
         v_flex()
             .key_context("ProjectSearchBar")
             .on_action(cx.listener(|this, _: &ToggleFocus, cx| this.move_focus_to_results(cx)))
+            .on_action(cx.listener(Self::dismiss))
             .on_action(cx.listener(|this, _: &ToggleFilters, cx| {
                 this.toggle_filters(cx);
             }))
@@ -1598,6 +4060,9 @@ impl Render for ProjectSearchBar {
                 this.on_action(cx.listener(|this, _: &ToggleIncludeIgnored, cx| {
                     this.toggle_search_option(SearchOptions::INCLUDE_IGNORED, cx);
                 }))
+                .on_action(cx.listener(|this, _: &ToggleGitTrackedOnly, cx| {
+                    this.toggle_search_option(SearchOptions::GIT_TRACKED_ONLY, cx);
+                }))
             })
             .on_action(cx.listener(Self::select_next_match))
             .on_action(cx.listener(Self::select_prev_match))
@@ -1639,6 +4104,35 @@ impl ToolbarItemView for ProjectSearchBar {
     }
 }
 
+fn project_kvp_key(prefix: &str, project: &Model<Project>, cx: &AppContext) -> Option<String> {
+    let roots = project
+        .read(cx)
+        .visible_worktrees(cx)
+        .map(|worktree| worktree.read(cx).abs_path().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    if roots.is_empty() {
+        None
+    } else {
+        Some(format!("{prefix}-{}", roots.join(",")))
+    }
+}
+
+fn load_default_search_filters(
+    project: &Model<Project>,
+    cx: &AppContext,
+) -> Option<SerializedSearchFilters> {
+    let key = project_kvp_key(SEARCH_FILTERS_KEY, project, cx)?;
+    let filters = KEY_VALUE_STORE.read_kvp(&key).log_err().flatten()?;
+    serde_json::from_str(&filters).log_err()
+}
+
+fn load_search_presets(project: &Model<Project>, cx: &AppContext) -> Vec<SearchPreset> {
+    project_kvp_key(SEARCH_PRESETS_KEY, project, cx)
+        .and_then(|key| KEY_VALUE_STORE.read_kvp(&key).log_err().flatten())
+        .and_then(|presets| serde_json::from_str(&presets).log_err())
+        .unwrap_or_default()
+}
+
 fn register_workspace_action<A: Action>(
     workspace: &mut Workspace,
     callback: fn(&mut ProjectSearchBar, &A, &mut ViewContext<ProjectSearchBar>),
@@ -1693,59 +4187,373 @@ fn register_workspace_action_for_present_search<A: Action>(
     });
 }
 
-#[cfg(test)]
-pub mod tests {
-    use super::*;
-    use editor::DisplayPoint;
-    use gpui::{Action, TestAppContext, WindowHandle};
-    use project::FakeFs;
-    use serde_json::json;
-    use settings::SettingsStore;
-    use std::sync::Arc;
-    use workspace::DeploySearch;
+/// A glance at the first few project matches for the active editor's selection, shown in a modal
+/// rather than a search tab.
+struct PeekSearchModal {
+    workspace: WeakView<Workspace>,
+    query: String,
+    matches: Vec<PeekSearchMatch>,
+    search_pending: bool,
+    focus_handle: FocusHandle,
+    _search: Task<()>,
+}
 
-    #[gpui::test]
-    async fn test_project_search(cx: &mut TestAppContext) {
-        init_test(cx);
+struct PeekSearchMatch {
+    buffer: Model<Buffer>,
+    range: Range<language::Anchor>,
+    /// The match's file and line number, as in `src/main.rs:12`.
+    location: SharedString,
+    /// The line the match starts on, without surrounding whitespace.
+    line: SharedString,
+}
 
-        let fs = FakeFs::new(cx.background_executor.clone());
-        fs.insert_tree(
-            "/dir",
-            json!({
-                "one.rs": "const ONE: usize = 1;",
-                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
-                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
-                "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
-            }),
-        )
-        .await;
-        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
-        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
-        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+impl ModalView for PeekSearchModal {}
 
-        perform_search(search_view, "TWO", cx);
-        search_view.update(cx, |search_view, cx| {
-            assert_eq!(
-                search_view
-                    .results_editor
-                    .update(cx, |editor, cx| editor.display_text(cx)),
-                "\n\nconst THREE: usize = one::ONE + two::TWO;\n\n\nconst TWO: usize = one::ONE + one::ONE;"
-            );
-            let match_background_color = cx.theme().colors().search_match_background;
-            assert_eq!(
-                search_view
-                    .results_editor
-                    .update(cx, |editor, cx| editor.all_text_background_highlights(cx)),
-                &[
-                    (
-                        DisplayPoint::new(2, 32)..DisplayPoint::new(2, 35),
-                        match_background_color
-                    ),
-                    (
-                        DisplayPoint::new(2, 37)..DisplayPoint::new(2, 40),
-                        match_background_color
-                    ),
-                    (
+impl EventEmitter<DismissEvent> for PeekSearchModal {}
+
+impl FocusableView for PeekSearchModal {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl PeekSearchModal {
+    fn toggle(workspace: &mut Workspace, _: &PeekSearch, cx: &mut ViewContext<Workspace>) {
+        let Some(editor) = workspace
+            .active_item(cx)
+            .and_then(|item| item.act_as::<Editor>(cx))
+        else {
+            return;
+        };
+        let query = editor.query_suggestion(cx);
+        if query.is_empty() {
+            return;
+        }
+        let project = workspace.project().clone();
+        let workspace_handle = cx.view().downgrade();
+        workspace.toggle_modal(cx, move |cx| {
+            Self::new(workspace_handle, project, query, cx)
+        });
+    }
+
+    fn new(
+        workspace: WeakView<Workspace>,
+        project: Model<Project>,
+        query: String,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let search = match SearchQuery::text(&query, false, false, false, Vec::new(), Vec::new())
+            .log_err()
+        {
+            Some(search_query) => {
+                search_project(&project, search_query, Some(PEEK_SEARCH_MATCH_LIMIT), cx)
+            }
+            None => Task::ready(Vec::new()),
+        };
+        let search = cx.spawn(|this, mut cx| async move {
+            let mut matches = Vec::new();
+            for project_match in search.await {
+                // The buffer shows the match's line and is what opening the match jumps to.
+                let Ok(buffer) = project.update(&mut cx, |project, cx| {
+                    project.open_buffer(project_match.path.clone(), cx)
+                }) else {
+                    return;
+                };
+                let Some(buffer) = buffer.await.log_err() else {
+                    continue;
+                };
+                let Ok(peek_match) =
+                    cx.update(|cx| PeekSearchMatch::new(buffer, &project_match, cx))
+                else {
+                    return;
+                };
+                matches.push(peek_match);
+            }
+            this.update(&mut cx, |this, cx| {
+                this.matches = matches;
+                this.search_pending = false;
+                cx.notify();
+            })
+            .ok();
+        });
+        Self {
+            workspace,
+            query,
+            matches: Vec::new(),
+            search_pending: true,
+            focus_handle: cx.focus_handle(),
+            _search: search,
+        }
+    }
+
+    fn open_match(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(peek_match) = self.matches.get(ix) else {
+            return;
+        };
+        let buffer = peek_match.buffer.clone();
+        let range = {
+            let buffer = buffer.read(cx);
+            peek_match.range.start.to_point(buffer)..peek_match.range.end.to_point(buffer)
+        };
+        self.workspace
+            .update(cx, |workspace, cx| {
+                let pane = workspace.active_pane().clone();
+                let editor = workspace.open_project_item::<Editor>(pane, buffer, cx);
+                editor.update(cx, |editor, cx| {
+                    editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                        s.select_ranges([range])
+                    });
+                });
+            })
+            .ok();
+        cx.emit(DismissEvent);
+    }
+
+    fn open_full_search(&mut self, cx: &mut ViewContext<Self>) {
+        let query = self.query.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                let existing = ProjectSearchView::reusable_search(workspace, cx);
+                let search = ProjectSearchView::existing_or_new_search(
+                    workspace,
+                    existing,
+                    &DeploySearch::find(),
+                    cx,
+                );
+                search.update(cx, |search, cx| {
+                    search.set_query(&query, cx);
+                    search.search(cx);
+                });
+            })
+            .ok();
+        cx.emit(DismissEvent);
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+}
+
+impl PeekSearchMatch {
+    fn new(buffer: Model<Buffer>, project_match: &ProjectMatch, cx: &AppContext) -> Self {
+        let snapshot = buffer.read(cx).snapshot();
+        let row = project_match.line;
+        let start = snapshot.point_to_offset(language::Point::new(row, project_match.column));
+        let range =
+            snapshot.anchor_before(start)..snapshot.anchor_after(start + project_match.text.len());
+        let path = snapshot.file().map_or_else(
+            || "untitled".to_string(),
+            |file| file.path().to_string_lossy().into_owned(),
+        );
+        let line = snapshot
+            .text_for_range(
+                language::Point::new(row, 0)..language::Point::new(row, snapshot.line_len(row)),
+            )
+            .collect::<String>();
+        Self {
+            buffer,
+            range,
+            location: format!("{path}:{}", row + 1).into(),
+            line: line.trim().to_string().into(),
+        }
+    }
+}
+
+impl Render for PeekSearchModal {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let status = if self.search_pending {
+            Some("Searching...")
+        } else if self.matches.is_empty() {
+            Some("No results")
+        } else {
+            None
+        };
+
+        v_flex()
+            .key_context("PeekSearch")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::cancel))
+            .elevation_2(cx)
+            .w_96()
+            .p_1()
+            .child(
+                h_flex().px_2().py_1().child(
+                    Label::new(format!("Matches for \"{}\"", self.query))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                ),
+            )
+            .children(status.map(|status| h_flex().px_2().py_1().child(Label::new(status))))
+            .children(self.matches.iter().enumerate().map(|(ix, peek_match)| {
+                ListItem::new(ix)
+                    .inset(true)
+                    .spacing(ListItemSpacing::Sparse)
+                    .on_click(cx.listener(move |this, _, cx| this.open_match(ix, cx)))
+                    .child(
+                        v_flex()
+                            .child(
+                                Label::new(peek_match.location.clone())
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                            .child(Label::new(peek_match.line.clone())),
+                    )
+            }))
+            .child(
+                h_flex().justify_end().px_1().pt_1().child(
+                    Button::new("peek-search-open-full-search", "Open Full Search")
+                        .on_click(cx.listener(|this, _, cx| this.open_full_search(cx))),
+                ),
+            )
+    }
+}
+
+/// Names a project by its visible worktrees, for the headers of results from several projects.
+fn project_label(project: &Model<Project>, cx: &AppContext) -> SharedString {
+    project
+        .read(cx)
+        .visible_worktrees(cx)
+        .map(|worktree| worktree.read(cx).root_name().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+        .into()
+}
+
+fn project_header_renderer(label: SharedString) -> RenderBlock {
+    Box::new(move |cx| {
+        h_flex()
+            .id("project header")
+            .py_2()
+            .pl_10()
+            .pr_5()
+            .w_full()
+            .gap_2()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(Icon::new(IconName::Folder).color(Color::Muted))
+            .child(Label::new(label.clone()))
+            .into_any_element()
+    })
+}
+
+/// Returns where excerpts start or end partway through a line, which they do when they only
+/// show a window of a long line.
+fn truncated_line_edges(snapshot: &MultiBufferSnapshot) -> Vec<Anchor> {
+    let mut edges = Vec::new();
+    for (excerpt_id, buffer, range) in snapshot.excerpts() {
+        let start = range.context.start.to_point(buffer);
+        let end = range.context.end.to_point(buffer);
+        if start.column > 0 {
+            edges.extend(snapshot.anchor_in_excerpt(excerpt_id, range.context.start));
+        }
+        if end.column > 0 && end.column < buffer.line_len(end.row) {
+            edges.extend(snapshot.anchor_in_excerpt(excerpt_id, range.context.end));
+        }
+    }
+    edges
+}
+
+/// Returns the ranges covering every line of the results buffer that doesn't contain a match.
+fn context_line_ranges(
+    match_ranges: &[Range<Anchor>],
+    snapshot: &MultiBufferSnapshot,
+) -> Vec<Range<Anchor>> {
+    let match_rows = match_ranges
+        .iter()
+        .flat_map(|range| range.start.to_point(snapshot).row..=range.end.to_point(snapshot).row)
+        .collect::<HashSet<_>>();
+
+    let mut ranges = Vec::new();
+    let mut context_start = None;
+    for row in 0..=snapshot.max_point().row {
+        if match_rows.contains(&row) {
+            if let Some(start) = context_start.take() {
+                ranges.push(context_line_range(start, row - 1, snapshot));
+            }
+        } else if context_start.is_none() {
+            context_start = Some(row);
+        }
+    }
+    if let Some(start) = context_start {
+        ranges.push(context_line_range(
+            start,
+            snapshot.max_point().row,
+            snapshot,
+        ));
+    }
+    ranges
+}
+
+fn context_line_range(
+    start_row: u32,
+    end_row: u32,
+    snapshot: &MultiBufferSnapshot,
+) -> Range<Anchor> {
+    snapshot.anchor_before(language::Point::new(start_row, 0))
+        ..snapshot.anchor_after(language::Point::new(end_row, snapshot.line_len(end_row)))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use editor::DisplayPoint;
+    use gpui::{Action, TestAppContext, WindowHandle};
+    use language::{Language, LanguageConfig, LanguageMatcher};
+    use project::{project_settings::SearchFilesSettings, FakeFs};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::{cell::RefCell, rc::Rc, sync::Arc};
+    use workspace::DeploySearch;
+
+    #[gpui::test]
+    async fn test_project_search(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+                "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "TWO", cx);
+        search_view.update(cx, |search_view, cx| {
+            assert_eq!(
+                search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx)),
+                "\n\nconst THREE: usize = one::ONE + two::TWO;\n\n\nconst TWO: usize = one::ONE + one::ONE;"
+            );
+            let match_background_color = cx.theme().colors().search_match_background;
+            let mut highlights = search_view
+                .results_editor
+                .update(cx, |editor, cx| editor.all_text_background_highlights(cx));
+            highlights.sort_by_key(|(range, _)| range.start);
+            // The active match is highlighted a second time, on top of the other matches.
+            assert_eq!(
+                highlights,
+                &[
+                    (
+                        DisplayPoint::new(2, 32)..DisplayPoint::new(2, 35),
+                        match_background_color
+                    ),
+                    (
+                        DisplayPoint::new(2, 32)..DisplayPoint::new(2, 35),
+                        match_background_color
+                    ),
+                    (
+                        DisplayPoint::new(2, 37)..DisplayPoint::new(2, 40),
+                        match_background_color
+                    ),
+                    (
                         DisplayPoint::new(5, 6)..DisplayPoint::new(5, 9),
                         match_background_color
                     )
@@ -1828,7 +4636,7 @@ pub mod tests {
     }
 
     #[gpui::test]
-    async fn test_deploy_project_search_focus(cx: &mut TestAppContext) {
+    async fn test_active_match_changed_events(cx: &mut TestAppContext) {
         init_test(cx);
 
         let fs = FakeFs::new(cx.background_executor.clone());
@@ -1838,272 +4646,2981 @@ pub mod tests {
                 "one.rs": "const ONE: usize = 1;",
                 "two.rs": "const TWO: usize = one::ONE + one::ONE;",
                 "three.rs": "const THREE: usize = one::ONE + two::TWO;",
-                "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
             }),
         )
         .await;
         let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
-        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
-        let workspace = window;
-        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
-
-        let active_item = cx.read(|cx| {
-            workspace
-                .read(cx)
-                .unwrap()
-                .active_pane()
-                .read(cx)
-                .active_item()
-                .and_then(|item| item.downcast::<ProjectSearchView>())
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let search_view = cx.add_window({
+            let events = events.clone();
+            |cx| {
+                cx.subscribe(&cx.view().clone(), move |_, _, event: &ViewEvent, _| {
+                    if let ViewEvent::ActiveMatchChanged { index, total } = event {
+                        events.borrow_mut().push((*index, *total));
+                    }
+                })
+                .detach();
+                ProjectSearchView::new(search.clone(), cx, None)
+            }
         });
-        assert!(
-            active_item.is_none(),
-            "Expected no search panel to be active"
+
+        perform_search(search_view, "TWO", cx);
+        assert_eq!(
+            events.borrow().last(),
+            Some(&(Some(0), 3)),
+            "Landing on the first match of a new search should be reported"
         );
+        events.borrow_mut().clear();
 
-        window
-            .update(cx, move |workspace, cx| {
-                assert_eq!(workspace.panes().len(), 1);
-                workspace.panes()[0].update(cx, move |pane, cx| {
-                    pane.toolbar()
-                        .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
-                });
+        for direction in [Direction::Next, Direction::Next, Direction::Prev] {
+            search_view
+                .update(cx, |search_view, cx| {
+                    search_view.select_match(direction, cx)
+                })
+                .unwrap();
+        }
+        assert_eq!(
+            events.borrow().as_slice(),
+            &[(Some(1), 3), (Some(2), 3), (Some(1), 3)]
+        );
+        events.borrow_mut().clear();
 
-                ProjectSearchView::deploy_search(workspace, &workspace::DeploySearch::find(), cx)
-            })
+        search_view
+            .update(cx, |search_view, cx| search_view.update_match_index(cx))
             .unwrap();
+        assert!(
+            events.borrow().is_empty(),
+            "No event should be emitted when the active match stays the same"
+        );
+    }
 
-        let Some(search_view) = cx.read(|cx| {
-            workspace
-                .read(cx)
-                .unwrap()
-                .active_pane()
-                .read(cx)
-                .active_item()
-                .and_then(|item| item.downcast::<ProjectSearchView>())
-        }) else {
-            panic!("Search view expected to appear after new search event trigger")
-        };
+    #[gpui::test]
+    async fn test_search_all_windows(cx: &mut TestAppContext) {
+        init_test(cx);
 
-        cx.spawn(|mut cx| async move {
-            window
-                .update(&mut cx, |_, cx| {
-                    cx.dispatch_action(ToggleFocus.boxed_clone())
-                })
-                .unwrap();
-        })
-        .detach();
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/a",
+            json!({
+                "one.rs": "const ONE: usize = TWO;",
+            }),
+        )
+        .await;
+        fs.insert_tree(
+            "/b",
+            json!({
+                "two.rs": "const TWO: usize = 2;",
+            }),
+        )
+        .await;
+        let project_a = Project::test(fs.clone(), ["/a".as_ref()], cx).await;
+        let project_b = Project::test(fs.clone(), ["/b".as_ref()], cx).await;
+        cx.add_window(|cx| Workspace::test_new(project_b, cx));
+        let search = cx.new_model(|cx| ProjectSearch::new(project_a, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "TWO", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst ONE: usize = TWO;",
+                    "Only the view's own project should be searched by default"
+                );
+                search_view.toggle_search_all_windows(cx);
+                search_view.search(cx);
+            })
+            .unwrap();
         cx.background_executor.run_until_parked();
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                assert!(
-                    search_view.query_editor.focus_handle(cx).is_focused(cx),
-                    "Empty search view should be focused after the toggle focus event: no results panel to focus on",
+
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\n\n\nconst ONE: usize = TWO;\n\n\n\n\nconst TWO: usize = 2;",
+                    "Results should include other windows' projects, grouped under project headers"
                 );
-           });
-        }).unwrap();
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 2);
+            })
+            .unwrap();
+    }
 
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    let query_editor = &search_view.query_editor;
-                    assert!(
-                        query_editor.focus_handle(cx).is_focused(cx),
-                        "Search view should be focused after the new search view is activated",
-                    );
-                    let query_text = query_editor.read(cx).text(cx);
-                    assert!(
-                        query_text.is_empty(),
-                        "New search query should be empty but got '{query_text}'",
-                    );
-                    let results_text = search_view
-                        .results_editor
-                        .update(cx, |editor, cx| editor.display_text(cx));
-                    assert!(
-                        results_text.is_empty(),
-                        "Empty search view should have no results but got '{results_text}'"
-                    );
+    #[gpui::test]
+    async fn test_search_all_windows_groups_results_by_project(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/z",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "three.rs": "const THREE: usize = 3;",
+            }),
+        )
+        .await;
+        fs.insert_tree(
+            "/b",
+            json!({
+                "a.rs": "let a = 0;",
+                "two.rs": "const TWO: usize = 2;",
+            }),
+        )
+        .await;
+        let project_z = Project::test(fs.clone(), ["/z".as_ref()], cx).await;
+        let project_b = Project::test(fs.clone(), ["/b".as_ref()], cx).await;
+        cx.add_window(|cx| Workspace::test_new(project_b.clone(), cx));
+        let search = cx.new_model(|cx| ProjectSearch::new(project_z.clone(), cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<SearchSettings>(cx, |settings| {
+                    settings.refresh_on_save = Some(true);
                 });
+            });
+        });
+        let groups = |cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, cx| {
+                    let model = search_view.model.read(cx);
+                    let snapshot = model.excerpts.read(cx).snapshot(cx);
+                    let live_excerpt_ids = model
+                        .project_results
+                        .iter()
+                        .flat_map(|results| results.excerpt_ids.iter().copied())
+                        .filter(|excerpt_id| snapshot.buffer_for_excerpt(*excerpt_id).is_some())
+                        .collect::<Vec<_>>();
+                    assert_eq!(
+                        live_excerpt_ids,
+                        snapshot
+                            .excerpts()
+                            .map(|(excerpt_id, _, _)| excerpt_id)
+                            .collect::<Vec<_>>(),
+                        "Each project's excerpts should be together, in the order of the projects"
+                    );
+                    let headers = search_view
+                        .project_headers
+                        .iter()
+                        .map(|(_, label)| label.to_string())
+                        .collect::<Vec<_>>();
+                    let groups = model
+                        .project_results
+                        .iter()
+                        .map(|results| {
+                            let paths = results
+                                .excerpt_ids
+                                .iter()
+                                .filter_map(|excerpt_id| snapshot.buffer_for_excerpt(*excerpt_id))
+                                .map(|buffer| buffer.file().unwrap().full_path(cx))
+                                .collect::<Vec<_>>();
+                            (project_label(&results.project, cx).to_string(), paths)
+                        })
+                        .collect::<Vec<_>>();
+                    (headers, groups)
+                })
+                .unwrap()
+        };
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.toggle_search_all_windows(cx)
             })
             .unwrap();
+        perform_search(search_view, "const", cx);
+        assert_eq!(
+            groups(cx),
+            (
+                vec!["z".to_string(), "b".to_string()],
+                vec![
+                    (
+                        "z".to_string(),
+                        vec![PathBuf::from("z/one.rs"), PathBuf::from("z/three.rs")]
+                    ),
+                    ("b".to_string(), vec![PathBuf::from("b/two.rs")]),
+                ]
+            )
+        );
 
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    search_view.query_editor.update(cx, |query_editor, cx| {
-                        query_editor.set_text("sOMETHINGtHATsURELYdOESnOTeXIST", cx)
-                    });
-                    search_view.search(cx);
-                });
-            })
+        // A saved file that had no results goes among those of its own project, even though its
+        // path comes before all others.
+        let buffer = project_b
+            .update(cx, |project, cx| project.open_local_buffer("/b/a.rs", cx))
+            .await
+            .unwrap();
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(0..buffer.len(), "const A: usize = 0;")], None, cx)
+        });
+        project_b
+            .update(cx, |project, cx| project.save_buffer(buffer, cx))
+            .await
             .unwrap();
         cx.background_executor.run_until_parked();
-        window
-            .update(cx, |_, cx| {
-            search_view.update(cx, |search_view, cx| {
-                let results_text = search_view
-                    .results_editor
-                    .update(cx, |editor, cx| editor.display_text(cx));
-                assert!(
-                    results_text.is_empty(),
-                    "Search view for mismatching query should have no results but got '{results_text}'"
-                );
-                assert!(
-                    search_view.query_editor.focus_handle(cx).is_focused(cx),
-                    "Search view should be focused after mismatching query had been used in search",
-                );
-            });
-        }).unwrap();
+        assert_eq!(
+            groups(cx),
+            (
+                vec!["z".to_string(), "b".to_string()],
+                vec![
+                    (
+                        "z".to_string(),
+                        vec![PathBuf::from("z/one.rs"), PathBuf::from("z/three.rs")]
+                    ),
+                    (
+                        "b".to_string(),
+                        vec![PathBuf::from("b/a.rs"), PathBuf::from("b/two.rs")]
+                    ),
+                ]
+            )
+        );
 
-        cx.spawn(|mut cx| async move {
-            window.update(&mut cx, |_, cx| {
-                cx.dispatch_action(ToggleFocus.boxed_clone())
-            })
-        })
-        .detach();
-        cx.background_executor.run_until_parked();
-        window.update(cx, |_, cx| {
-            search_view.update(cx, |search_view, cx| {
-                assert!(
-                    search_view.query_editor.focus_handle(cx).is_focused(cx),
-                    "Search view with mismatching query should be focused after the toggle focus event: still no results panel to focus on",
-                );
+        // Every searched project remembers the query.
+        for project in [&project_z, &project_b] {
+            let last_query = project.update(cx, |project, _| {
+                project
+                    .search_history_mut()
+                    .previous(&mut SearchHistoryCursor::default())
+                    .map(str::to_string)
             });
-        }).unwrap();
+            assert_eq!(last_query.as_deref(), Some("const"));
+        }
 
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    search_view
-                        .query_editor
-                        .update(cx, |query_editor, cx| query_editor.set_text("TWO", cx));
-                    search_view.search(cx);
-                });
+        // Results from a single project have no headers.
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.toggle_search_all_windows(cx)
             })
             .unwrap();
-        cx.background_executor.run_until_parked();
-        window.update(cx, |_, cx| {
-            search_view.update(cx, |search_view, cx| {
-                assert_eq!(
-                    search_view
-                        .results_editor
-                        .update(cx, |editor, cx| editor.display_text(cx)),
-                    "\n\nconst THREE: usize = one::ONE + two::TWO;\n\n\nconst TWO: usize = one::ONE + one::ONE;",
-                    "Search view results should match the query"
-                );
-                assert!(
-                    search_view.results_editor.focus_handle(cx).is_focused(cx),
-                    "Search view with mismatching query should be focused after search results are available",
-                );
-            });
-        }).unwrap();
-        cx.spawn(|mut cx| async move {
-            window
-                .update(&mut cx, |_, cx| {
-                    cx.dispatch_action(ToggleFocus.boxed_clone())
+        perform_search(search_view, "const", cx);
+        assert_eq!(
+            groups(cx),
+            (
+                Vec::new(),
+                vec![(
+                    "z".to_string(),
+                    vec![PathBuf::from("z/one.rs"), PathBuf::from("z/three.rs")]
+                )]
+            )
+        );
+    }
+
+    #[gpui::test]
+    async fn test_dismiss(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let dismissed = Rc::new(RefCell::new(false));
+        let search_view = cx.add_window({
+            let dismissed = dismissed.clone();
+            |cx| {
+                cx.subscribe(&cx.view().clone(), move |_, _, event: &ViewEvent, _| {
+                    if event == &ViewEvent::Dismiss {
+                        *dismissed.borrow_mut() = true;
+                    }
                 })
-                .unwrap();
-        })
-        .detach();
-        cx.background_executor.run_until_parked();
-        window.update(cx, |_, cx| {
-            search_view.update(cx, |search_view, cx| {
+                .detach();
+                ProjectSearchView::new(search.clone(), cx, None)
+            }
+        });
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view
+                    .query_editor
+                    .update(cx, |query_editor, cx| query_editor.set_text("ONE", cx));
+                search_view.search(cx);
+                assert!(search_view.model.read(cx).pending_search.is_some());
+                search_view.dismiss(&Dismiss, cx);
                 assert!(
-                    search_view.results_editor.focus_handle(cx).is_focused(cx),
-                    "Search view with matching query should still have its results editor focused after the toggle focus event",
+                    search_view.model.read(cx).pending_search.is_none(),
+                    "Dismissing a running search should cancel it"
                 );
-            });
-        }).unwrap();
-
-        workspace
-            .update(cx, |workspace, cx| {
-                ProjectSearchView::deploy_search(workspace, &workspace::DeploySearch::find(), cx)
             })
             .unwrap();
-        window.update(cx, |_, cx| {
-            search_view.update(cx, |search_view, cx| {
-                assert_eq!(search_view.query_editor.read(cx).text(cx), "two", "Query should be updated to first search result after search view 2nd open in a row");
-                assert_eq!(
-                    search_view
-                        .results_editor
-                        .update(cx, |editor, cx| editor.display_text(cx)),
-                    "\n\nconst THREE: usize = one::ONE + two::TWO;\n\n\nconst TWO: usize = one::ONE + one::ONE;",
-                    "Results should be unchanged after search view 2nd open in a row"
-                );
+        assert!(!*dismissed.borrow());
+
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.focus_results_editor(cx);
+                search_view.dismiss(&Dismiss, cx);
                 assert!(
                     search_view.query_editor.focus_handle(cx).is_focused(cx),
-                    "Focus should be moved into query editor again after search view 2nd open in a row"
+                    "Dismissing from the results should focus the query"
                 );
-            });
-        }).unwrap();
+            })
+            .unwrap();
+        assert!(!*dismissed.borrow());
 
-        cx.spawn(|mut cx| async move {
-            window
-                .update(&mut cx, |_, cx| {
-                    cx.dispatch_action(ToggleFocus.boxed_clone())
-                })
-                .unwrap();
-        })
-        .detach();
-        cx.background_executor.run_until_parked();
-        window.update(cx, |_, cx| {
-            search_view.update(cx, |search_view, cx| {
-                assert!(
-                    search_view.results_editor.focus_handle(cx).is_focused(cx),
-                    "Search view with matching query should switch focus to the results editor after the toggle focus event",
-                );
-            });
-        }).unwrap();
+        search_view
+            .update(cx, |search_view, cx| search_view.dismiss(&Dismiss, cx))
+            .unwrap();
+        assert!(
+            *dismissed.borrow(),
+            "Dismissing from the query should close the search"
+        );
+    }
+
+    #[test]
+    fn test_selections_query() {
+        assert_eq!(selections_query(&[]), None);
+        assert_eq!(
+            selections_query(&["a.b".to_string()]),
+            Some(("a.b".to_string(), false))
+        );
+        assert_eq!(
+            selections_query(&["a.b".to_string(), "c".to_string(), "a.b".to_string()]),
+            Some(("a\\.b|c".to_string(), true))
+        );
     }
 
     #[gpui::test]
-    async fn test_new_project_search_focus(cx: &mut TestAppContext) {
+    async fn test_search_selections(cx: &mut TestAppContext) {
         init_test(cx);
 
         let fs = FakeFs::new(cx.background_executor.clone());
         fs.insert_tree(
             "/dir",
             json!({
-                "one.rs": "const ONE: usize = 1;",
-                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
-                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
-                "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
+                "one.rs": "const ONE: usize = 1;\nconst TWO: usize = 2;",
+                "three.rs": "const THREE: usize = ONE + TWO;",
             }),
         )
         .await;
         let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
-        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
-        let workspace = window;
-        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
-
-        let active_item = cx.read(|cx| {
-            workspace
-                .read(cx)
-                .unwrap()
-                .active_pane()
-                .read(cx)
-                .active_item()
-                .and_then(|item| item.downcast::<ProjectSearchView>())
+        let worktree_id = project.update(cx, |this, cx| {
+            this.worktrees().next().unwrap().read(cx).id()
         });
-        assert!(
-            active_item.is_none(),
-            "Expected no search panel to be active"
-        );
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
 
+        let editor = window
+            .update(cx, |workspace, cx| {
+                workspace.open_path((worktree_id, "one.rs"), None, true, cx)
+            })
+            .unwrap()
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
         window
-            .update(cx, move |workspace, cx| {
-                assert_eq!(workspace.panes().len(), 1);
-                workspace.panes()[0].update(cx, move |pane, cx| {
-                    pane.toolbar()
-                        .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
+            .update(cx, |workspace, cx| {
+                editor.update(cx, |editor, cx| {
+                    editor.change_selections(None, cx, |s| s.select_ranges([6..9, 28..31]))
                 });
-
-                ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
+                ProjectSearchView::search_selections(workspace, &SearchSelections, cx);
             })
             .unwrap();
+        cx.background_executor.run_until_parked();
 
-        let Some(search_view) = cx.read(|cx| {
+        let search_view = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_item(cx)
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+                .expect("Searching selections should open a project search")
+        });
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "ONE|TWO");
+                    assert!(search_view.search_options.contains(SearchOptions::REGEX));
+                    assert_eq!(search_view.model.read(cx).match_ranges.len(), 4);
+                })
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_peek_search(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let worktree_id = project.update(cx, |this, cx| {
+            this.worktrees().next().unwrap().read(cx).id()
+        });
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+
+        let editor = window
+            .update(cx, |workspace, cx| {
+                workspace.open_path((worktree_id, "one.rs"), None, true, cx)
+            })
+            .unwrap()
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        window
+            .update(cx, |workspace, cx| {
+                editor.update(cx, |editor, cx| {
+                    editor.change_selections(None, cx, |s| s.select_ranges([6..9]))
+                });
+                PeekSearchModal::toggle(workspace, &PeekSearch, cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        let modal = window
+            .update(cx, |workspace, cx| {
+                workspace
+                    .active_modal::<PeekSearchModal>(cx)
+                    .expect("Peeking should open a modal")
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                let modal = modal.read(cx);
+                assert_eq!(modal.query, "ONE");
+                assert!(!modal.search_pending);
+                assert_eq!(
+                    modal
+                        .matches
+                        .iter()
+                        .map(|peek_match| (&*peek_match.location, &*peek_match.line))
+                        .collect::<Vec<_>>(),
+                    [
+                        ("one.rs:1", "const ONE: usize = 1;"),
+                        ("three.rs:1", "const THREE: usize = one::ONE + two::TWO;"),
+                        ("three.rs:1", "const THREE: usize = one::ONE + two::TWO;"),
+                        ("two.rs:1", "const TWO: usize = one::ONE + one::ONE;"),
+                        ("two.rs:1", "const TWO: usize = one::ONE + one::ONE;"),
+                    ],
+                    "Only the first {PEEK_SEARCH_MATCH_LIMIT} matches are shown"
+                );
+            })
+            .unwrap();
+
+        window
+            .update(cx, |_, cx| {
+                modal.update(cx, |modal, cx| modal.open_full_search(cx))
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        let search_view = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_item(cx)
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+                .expect("Opening the full search should open a project search")
+        });
+        window
+            .update(cx, |workspace, cx| {
+                assert!(workspace.active_modal::<PeekSearchModal>(cx).is_none());
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "ONE");
+                    assert_eq!(search_view.match_count(cx), 7);
+                })
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_copy_match_reference(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        // Without an active match, nothing is copied.
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.copy_match_reference(&CopyMatchReference, cx)
+            })
+            .unwrap();
+        assert_eq!(cx.read_from_clipboard(), None);
+
+        perform_search(search_view, "TWO", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.copy_match_reference(&CopyMatchReference, cx)
+            })
+            .unwrap();
+        assert_eq!(
+            cx.read_from_clipboard().map(|item| item.text().clone()),
+            Some("three.rs:1:33".to_string())
+        );
+    }
+
+    #[gpui::test]
+    async fn test_match_path_display(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "src": {
+                    "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "TWO", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.copy_match_reference(&CopyMatchReference, cx)
+            })
+            .unwrap();
+        assert_eq!(
+            cx.read_from_clipboard().map(|item| item.text().clone()),
+            Some("src/two.rs:1:7".to_string())
+        );
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<SearchSettings>(cx, |search_settings| {
+                    search_settings.path_display = Some(PathDisplay::Absolute)
+                });
+            })
+        });
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.copy_match_reference(&CopyMatchReference, cx)
+            })
+            .unwrap();
+        assert_eq!(
+            cx.read_from_clipboard().map(|item| item.text().clone()),
+            Some("/dir/src/two.rs:1:7".to_string())
+        );
+    }
+
+    #[gpui::test]
+    async fn test_match_path_display_with_multiple_worktrees(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a": { "one.rs": "const ONE: usize = 1;" },
+                "b": { "two.rs": "const TWO: usize = 2;" },
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir/a".as_ref(), "/dir/b".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "TWO", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.copy_match_reference(&CopyMatchReference, cx)
+            })
+            .unwrap();
+        assert_eq!(
+            cx.read_from_clipboard().map(|item| item.text().clone()),
+            Some("b/two.rs:1:7".to_string()),
+            "Relative paths should name the worktree when there are several"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_searchable_results(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree("/dir", json!({ "one.rs": "const ONE: usize = 1;" }))
+            .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        let view = search_view.root_view(cx).unwrap();
+
+        search_view
+            .update(cx, |search_view, _| {
+                assert!(search_view.as_searchable(&view).is_none());
+            })
+            .unwrap();
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<SearchSettings>(cx, |search_settings| {
+                    search_settings.searchable_results = Some(true)
+                });
+            })
+        });
+        search_view
+            .update(cx, |search_view, cx| {
+                let searchable = search_view.as_searchable(&view).unwrap();
+                assert_eq!(
+                    searchable.item_id(),
+                    search_view.results_editor.entity_id(),
+                    "The buffer search should search the results editor"
+                );
+                assert!(search_view.results_editor.read(cx).searchable());
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_exclude_match_file(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view
+                    .excluded_files_editor
+                    .update(cx, |editor, cx| editor.set_text("*.md", cx));
+                search_view.exclude_match_file(&ExcludeMatchFile, cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        search_view
+            .update(cx, |search_view, cx| {
+                assert!(search_view.filters_enabled);
+                assert_eq!(
+                    search_view.excluded_files_editor.read(cx).text(cx),
+                    "*.md, one.rs"
+                );
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst TWO: usize = one::ONE + one::ONE;"
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_exclude_current_file(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let worktree_id = project.update(cx, |this, cx| {
+            this.worktrees().next().unwrap().read(cx).id()
+        });
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+
+        window
+            .update(cx, |workspace, cx| {
+                workspace.open_path((worktree_id, "one.rs"), None, true, cx)
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        let search_view = window
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::deploy_search(workspace, &DeploySearch::find(), cx);
+                workspace.active_item_as::<ProjectSearchView>(cx).unwrap()
+            })
+            .unwrap();
+        let results_text = |cx: &mut TestAppContext| {
+            window
+                .update(cx, |_, cx| {
+                    search_view.update(cx, |search_view, cx| {
+                        search_view
+                            .results_editor
+                            .update(cx, |editor, cx| editor.display_text(cx))
+                    })
+                })
+                .unwrap()
+        };
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(
+                        search_view.origin_file.as_deref(),
+                        Some(Path::new("one.rs"))
+                    );
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("ONE", cx));
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        assert_eq!(
+            results_text(cx),
+            "\n\nconst ONE: usize = 1;\n\n\nconst TWO: usize = one::ONE + one::ONE;"
+        );
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.toggle_exclude_origin_file(cx);
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        assert_eq!(
+            results_text(cx),
+            "\n\nconst TWO: usize = one::ONE + one::ONE;",
+            "The file the search was opened from should be excluded"
+        );
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(
+                        search_view.excluded_files_editor.read(cx).text(cx),
+                        "",
+                        "The exclude filter itself should be left alone"
+                    );
+                });
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_streaming_notifications_are_throttled(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            serde_json::Value::Object(
+                (0..100)
+                    .map(|ix| (format!("file_{ix}.rs"), json!("const ONE: usize = 1;")))
+                    .collect(),
+            ),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        let notification_count = Rc::new(RefCell::new(0));
+        let _subscription = cx.update({
+            let notification_count = notification_count.clone();
+            |cx| cx.observe(&search, move |_, _| *notification_count.borrow_mut() += 1)
+        });
+
+        perform_search(search_view, "ONE", cx);
+        assert_eq!(
+            search.read_with(cx, |search, _| search.match_ranges.len()),
+            100
+        );
+        assert!(
+            *notification_count.borrow() <= 3,
+            "Expected results of quickly searched files to be coalesced, got {} notifications",
+            notification_count.borrow()
+        );
+
+        // Results that arrive without a search completing get flushed after the interval.
+        search.update(cx, |search, cx| search.notify_throttled(cx));
+        let notifications_before_flush = *notification_count.borrow();
+        cx.background_executor
+            .advance_clock(STREAMING_NOTIFY_INTERVAL);
+        cx.background_executor.run_until_parked();
+        assert_eq!(*notification_count.borrow(), notifications_before_flush + 1);
+    }
+
+    #[gpui::test]
+    async fn test_search_project(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE +\n    one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+
+        let query = SearchQuery::text("ONE", false, true, false, Vec::new(), Vec::new()).unwrap();
+        let matches = cx
+            .update(|cx| search_project(&project, query, None, cx))
+            .await;
+        let project_match = |path: &str, line, column| ProjectMatch {
+            path: ProjectPath {
+                worktree_id,
+                path: Arc::from(Path::new(path)),
+            },
+            line,
+            column,
+            text: "ONE".to_string(),
+        };
+        assert_eq!(
+            matches,
+            [
+                project_match("one.rs", 0, 6),
+                project_match("two.rs", 0, 24),
+                project_match("two.rs", 1, 9),
+            ]
+        );
+
+        let query = SearchQuery::text("ONE", false, true, false, Vec::new(), Vec::new()).unwrap();
+        let matches = cx
+            .update(|cx| search_project(&project, query, Some(2), cx))
+            .await;
+        assert_eq!(
+            matches,
+            [
+                project_match("one.rs", 0, 6),
+                project_match("two.rs", 0, 24),
+            ],
+            "Only the first matches up to the limit should be returned"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_navigate_streaming_matches(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a.rs": "X X",
+                "b.rs": "X",
+                "c.rs": "X",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "X", cx);
+        let all_match_ranges = search.read_with(cx, |search, _| search.match_ranges.clone());
+        assert_eq!(all_match_ranges.len(), 4);
+
+        // Only some matches have been reported so far, while the cursor is already placed
+        // within an excerpt whose matches are still streaming in.
+        search.update(cx, |search, cx| {
+            search.match_ranges.truncate(2);
+            cx.notify();
+        });
+        search_view
+            .update(cx, |search_view, cx| {
+                let cursor = all_match_ranges[2].start;
+                search_view.results_editor.update(cx, |editor, cx| {
+                    editor.change_selections(None, cx, |s| s.select_anchor_ranges([cursor..cursor]))
+                });
+            })
+            .unwrap();
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.active_match_index, Some(1));
+                // More matches stream in, before the view is notified about them.
+                search_view.model.update(cx, |search, _| {
+                    search.match_ranges = all_match_ranges;
+                });
+                search_view.select_match(Direction::Next, cx);
+            })
+            .unwrap();
+        search_view
+            .update(cx, |search_view, _| {
+                assert_eq!(
+                    search_view.active_match_index,
+                    Some(3),
+                    "Navigation should move past the match at the cursor among the streamed matches"
+                );
+            })
+            .unwrap();
+
+        // The search completes.
+        search.update(cx, |_, cx| cx.notify());
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.active_match_index, Some(3));
+                search_view.select_match(Direction::Next, cx);
+            })
+            .unwrap();
+        search_view
+            .update(cx, |search_view, _| {
+                assert_eq!(search_view.active_match_index, Some(0));
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_select_file(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a.rs": "X X X",
+                "b.rs": "X",
+                "c.rs": "X X",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "X", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 6);
+                assert_eq!(search_view.active_match_index, Some(0));
+            })
+            .unwrap();
+
+        for (direction, expected_index) in [
+            (Direction::Next, 3),
+            (Direction::Next, 4),
+            (Direction::Next, 0),
+            (Direction::Prev, 4),
+            (Direction::Prev, 3),
+            (Direction::Prev, 0),
+        ] {
+            search_view
+                .update(cx, |search_view, cx| search_view.select_file(direction, cx))
+                .unwrap();
+            search_view
+                .update(cx, |search_view, _| {
+                    assert_eq!(
+                        search_view.active_match_index,
+                        Some(expected_index),
+                        "selecting the {direction:?} file"
+                    );
+                })
+                .unwrap();
+        }
+
+        // From the middle of a file, the previous file is the one before it.
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.select_match(Direction::Next, cx);
+                search_view.select_file(Direction::Prev, cx);
+            })
+            .unwrap();
+        search_view
+            .update(cx, |search_view, _| {
+                assert_eq!(search_view.active_match_index, Some(4));
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_search_long_line(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let padding = "x".repeat(100_000);
+        let line = format!("{padding}needle{padding}");
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "bundle.min.js": line.clone(),
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "needle", cx);
+        let results_text = |cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, cx| {
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx))
+                })
+                .unwrap()
+        };
+        let text = results_text(cx);
+        assert!(
+            text.len() < 1000 && text.contains("xneedlex"),
+            "Only a window around the match should be excerpted from the long line"
+        );
+        assert!(
+            text.starts_with("\n\n…x") && text.ends_with("x…"),
+            "The cut off edges of the line should be marked, got {text:?}"
+        );
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.expand_truncated_lines(&ExpandTruncatedLines, cx)
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        assert_eq!(
+            results_text(cx),
+            format!("\n\n{line}"),
+            "Expanding the line should show all of it, without markers"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_regex_preview(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree("/dir", json!({ "one.rs": "const ONE: usize = 1;" }))
+            .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view
+                    .query_editor
+                    .update(cx, |editor, cx| editor.set_text("path:src/** a|b", cx));
+            })
+            .unwrap();
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.regex_preview, None);
+                search_view.toggle_search_option(SearchOptions::REGEX, cx);
+                search_view.toggle_search_option(SearchOptions::WHOLE_WORD, cx);
+                assert_eq!(
+                    search_view.regex_preview.as_deref(),
+                    Some("(?i)\\ba|b\\b"),
+                    "The preview should show the pattern as searched, without inline filters"
+                );
+                search_view.toggle_literal(cx);
+                assert_eq!(
+                    search_view.regex_preview.as_deref(),
+                    Some("(?i)\\ba\\|b\\b")
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_regex_query_placeholder(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree("/dir", json!({ "one.rs": "const ONE: usize = 1;" }))
+            .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| {
+                let placeholder = |search_view: &ProjectSearchView, cx: &mut WindowContext| {
+                    search_view.query_editor.update(cx, |editor, cx| {
+                        editor.placeholder_text(cx).map(ToOwned::to_owned)
+                    })
+                };
+                assert_eq!(
+                    placeholder(search_view, cx).as_deref(),
+                    Some("Search all files..")
+                );
+                search_view.toggle_search_option(SearchOptions::REGEX, cx);
+                assert_eq!(
+                    placeholder(search_view, cx).as_deref(),
+                    Some("Regex search all files..")
+                );
+                search_view.toggle_search_option(SearchOptions::REGEX, cx);
+                assert_eq!(
+                    placeholder(search_view, cx).as_deref(),
+                    Some("Search all files..")
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_inverted_search(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = 2;\nconst ONE: usize = 1;",
+                "three.rs": "const THREE: usize = 3;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.toggle_search_option(SearchOptions::INVERT_MATCHES, cx)
+            })
+            .unwrap();
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst THREE: usize = 3;\n\n\nconst TWO: usize = 2;\nconst ONE: usize = 1;"
+                );
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 2);
+                search_view.toggle_search_option(SearchOptions::INVERT_FILES, cx);
+            })
+            .unwrap();
+
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst THREE: usize = 3;"
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_literal_regex_query(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "let a = a.b + axb;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.toggle_search_option(SearchOptions::REGEX, cx);
+            })
+            .unwrap();
+        perform_search(search_view, "a.b", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 2);
+                search_view.toggle_literal(cx);
+            })
+            .unwrap();
+
+        perform_search(search_view, "a.b", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 1);
+                assert!(
+                    search_view
+                        .model
+                        .read(cx)
+                        .active_query
+                        .as_ref()
+                        .unwrap()
+                        .is_regex(),
+                    "A literal query should stay a regex, for capture-aware replacements"
+                );
+                assert_eq!(
+                    search_view.query_editor.read(cx).text(cx),
+                    "a.b",
+                    "The query itself should not be escaped"
+                );
+                assert_eq!(
+                    search_view.model.read(cx).last_search_query_text.as_deref(),
+                    Some("a.b"),
+                    "The query should be remembered as written, not escaped"
+                );
+            })
+            .unwrap();
+
+        // Searching again for the remembered query still finds only the literal text.
+        search_view
+            .update(cx, |search_view, cx| {
+                let query_text = search_view
+                    .model
+                    .read(cx)
+                    .last_search_query_text
+                    .clone()
+                    .unwrap();
+                search_view.set_query(&query_text, cx);
+                search_view.search(cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 1);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_navigation_skips_deleted_files(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "three.rs": "const THREE: usize = one::ONE;",
+                "two.rs": "const TWO: usize = one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "const", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.active_match_index, Some(0));
+                assert_eq!(search_view.deleted_files_notice(cx), None);
+            })
+            .unwrap();
+
+        fs.remove_file("/dir/three.rs".as_ref(), Default::default())
+            .await
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view.deleted_files_notice(cx).as_deref(),
+                    Some("1 deleted file skipped")
+                );
+            })
+            .unwrap();
+
+        for (direction, select_file, expected_index) in [
+            (Direction::Next, false, 2),
+            (Direction::Prev, false, 0),
+            (Direction::Next, true, 2),
+        ] {
+            search_view
+                .update(cx, |search_view, cx| {
+                    if select_file {
+                        search_view.select_file(direction, cx)
+                    } else {
+                        search_view.select_match(direction, cx)
+                    }
+                })
+                .unwrap();
+            search_view
+                .update(cx, |search_view, _| {
+                    assert_eq!(search_view.active_match_index, Some(expected_index));
+                })
+                .unwrap();
+        }
+    }
+
+    #[gpui::test]
+    async fn test_navigation_from_cursor_between_matches(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a.rs": "const A: usize = 1;\nlet x = 2;\nconst B: usize = 3;",
+                "b.rs": "const C: usize = 4;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "const", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst A: usize = 1;\nlet x = 2;\nconst B: usize = 3;\n\n\nconst C: usize = 4;"
+                );
+            })
+            .unwrap();
+
+        // Clicking on a line without a match, or after the last match in a file, still lets
+        // Next and Prev move to the nearest match in that direction.
+        for (cursor, direction, expected_index) in [
+            (DisplayPoint::new(3, 4), Direction::Next, 1),
+            (DisplayPoint::new(3, 4), Direction::Prev, 0),
+            (DisplayPoint::new(4, 19), Direction::Next, 2),
+            (DisplayPoint::new(4, 19), Direction::Prev, 1),
+        ] {
+            search_view
+                .update(cx, |search_view, cx| {
+                    search_view.results_editor.update(cx, |editor, cx| {
+                        editor.change_selections(None, cx, |s| {
+                            s.select_display_ranges([cursor..cursor])
+                        })
+                    });
+                })
+                .unwrap();
+            search_view
+                .update(cx, |search_view, cx| {
+                    assert!(search_view.active_match_index.is_some());
+                    search_view.select_match(direction, cx);
+                })
+                .unwrap();
+            search_view
+                .update(cx, |search_view, _| {
+                    assert_eq!(
+                        search_view.active_match_index,
+                        Some(expected_index),
+                        "{direction:?} from {cursor:?}"
+                    );
+                })
+                .unwrap();
+        }
+    }
+
+    #[gpui::test]
+    async fn test_timed_out_files_notice(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "adversarial.txt": format!("{}!\n", "a".repeat(64)).repeat(100),
+                "plain.txt": "aaa",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        search_view
+            .update(cx, |search_view, _| {
+                search_view.search_options = SearchOptions::REGEX;
+            })
+            .unwrap();
+
+        perform_search(search_view, "(a+)+$", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.timed_out_files_notice(cx), None);
+            })
+            .unwrap();
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<SearchFilesSettings>(cx, |settings| {
+                    settings.file_match_timeout_ms = Some(0);
+                });
+            });
+        });
+        perform_search(search_view, "(a+)+$", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view.timed_out_files_notice(cx).as_deref(),
+                    Some("1 file took too long to match")
+                );
+                assert_eq!(search_view.match_count(cx), 1);
+                assert_eq!(search_view.model.read(cx).searched_file_count(), Some(1));
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_unreadable_files_notice(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.unreadable_files_notice(cx), None);
+            })
+            .unwrap();
+
+        // Remove files without the worktree noticing, so that they can't be read.
+        fs.pause_events();
+        for path in ["/dir/two.rs", "/dir/three.rs"] {
+            fs.remove_file(path.as_ref(), Default::default())
+                .await
+                .unwrap();
+        }
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view.unreadable_files_notice(cx).as_deref(),
+                    Some("2 files could not be read")
+                );
+                assert_eq!(
+                    search_view.unreadable_files_list(cx).as_ref(),
+                    "three.rs\ntwo.rs"
+                );
+                assert_eq!(search_view.model.read(cx).searched_file_count(), Some(1));
+
+                search_view.dismiss_unreadable_files_notice(cx);
+                assert_eq!(search_view.unreadable_files_notice(cx), None);
+            })
+            .unwrap();
+
+        perform_search(search_view, "TWO", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view.unreadable_files_notice(cx).as_deref(),
+                    Some("2 files could not be read"),
+                    "Dismissing the notice should only hide it for that search"
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_no_results_summary(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.model.read(cx).searched_file_count(), Some(3));
+                assert_eq!(
+                    search_view.no_results_summary(cx),
+                    None,
+                    "Searches with matches need no explanation"
+                );
+            })
+            .unwrap();
+
+        perform_search(search_view, "FOUR", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view.no_results_summary(cx).as_deref(),
+                    Some("3 files searched, 0 matches")
+                );
+                search_view
+                    .included_files_editor
+                    .update(cx, |editor, cx| editor.set_text("*.md", cx));
+            })
+            .unwrap();
+
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                let model = search_view.model.read(cx);
+                assert_eq!(model.files_matched_filters, Some(0));
+                assert_eq!(model.searched_file_count(), Some(0));
+                assert_eq!(
+                    search_view.no_results_summary(cx).as_deref(),
+                    Some("0 files matched filters")
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_tab_title_match_count(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "long.rs": "const ONE_TWO_THREE_FOUR_FIVE_SIX: usize = 123456;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.tab_title(cx).as_ref(), "Project Search");
+            })
+            .unwrap();
+
+        perform_search(search_view, "TWO", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.tab_title(cx).as_ref(), "TWO (2)");
+            })
+            .unwrap();
+
+        perform_search(search_view, "SEVEN", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view.tab_title(cx).as_ref(),
+                    "SEVEN",
+                    "Searches without matches should show no count"
+                );
+            })
+            .unwrap();
+
+        perform_search(search_view, "ONE_TWO_THREE_FOUR_FIVE_SIX", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                let title = search_view.tab_title(cx);
+                assert_eq!(title.as_ref(), "ONE_TWO_THREE_FOUR_… (1)");
+                assert!(title.chars().count() <= MAX_TAB_TITLE_LEN);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_rerun_search(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search.update(cx, |search, cx| search.rerun_search(cx));
+        assert!(
+            search.read_with(cx, |search, _| search.pending_search.is_none()),
+            "Nothing should be searched before a query was run"
+        );
+
+        perform_search(search_view, "ONE", cx);
+        assert_eq!(
+            search.read_with(cx, |search, _| search.match_ranges.len()),
+            5
+        );
+
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/dir/one.rs", cx)
+            })
+            .await
+            .unwrap();
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(0..0, "// ONE is one.\n")], None, cx)
+        });
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view
+                    .query_editor
+                    .update(cx, |query_editor, cx| query_editor.set_text("TWO", cx));
+                search_view
+                    .model
+                    .update(cx, |model, cx| model.rerun_search(cx));
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        search.read_with(cx, |search, _| {
+            assert_eq!(
+                search.match_ranges.len(),
+                7,
+                "The latest query should be run again, rather than the query editor's text"
+            );
+            assert_eq!(search.active_query.as_ref().unwrap().as_str(), "ONE");
+        });
+    }
+
+    #[gpui::test]
+    async fn test_reset_search_options(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
+        window
+            .update(cx, {
+                let search_bar = search_bar.clone();
+                move |workspace, cx| {
+                    workspace.active_pane().update(cx, move |pane, cx| {
+                        pane.toolbar()
+                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
+                    });
+                    ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
+                }
+            })
+            .unwrap();
+        let search_view = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_item_as::<ProjectSearchView>(cx)
+                .unwrap()
+        });
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.search_options =
+                        SearchOptions::CASE_SENSITIVE | SearchOptions::WHOLE_WORD;
+                    search_view
+                        .included_files_editor
+                        .update(cx, |editor, cx| editor.set_text("two.rs", cx));
+                    search_view
+                        .excluded_files_editor
+                        .update(cx, |editor, cx| editor.set_text("[", cx));
+                    search_view.set_query("one", cx);
+                    search_view.search(cx);
+                    assert!(search_view
+                        .panels_with_errors
+                        .contains(&InputPanel::Exclude));
+                })
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| search_bar.reset_search_options(cx))
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.search_options, SearchOptions::NONE);
+                    assert_eq!(search_view.included_files_editor.read(cx).text(cx), "");
+                    assert_eq!(search_view.excluded_files_editor.read(cx).text(cx), "");
+                    assert!(search_view.panels_with_errors.is_empty());
+                    assert_eq!(
+                        search_view.model.read(cx).match_ranges.len(),
+                        5,
+                        "The search should run again without the options and filters"
+                    );
+                })
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_swap_include_exclude(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.ts": "const ONE = 1;",
+                "three.md": "ONE",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
+        window
+            .update(cx, {
+                let search_bar = search_bar.clone();
+                move |workspace, cx| {
+                    workspace.active_pane().update(cx, move |pane, cx| {
+                        pane.toolbar()
+                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
+                    });
+                    ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
+                }
+            })
+            .unwrap();
+        let search_view = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_item_as::<ProjectSearchView>(cx)
+                .unwrap()
+        });
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view
+                        .included_files_editor
+                        .update(cx, |editor, cx| editor.set_text("*.rs, *.ts", cx));
+                    search_view.set_query("ONE", cx);
+                    search_view.search(cx);
+                })
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.model.read(cx).match_ranges.len(), 2);
+                })
+            })
+            .unwrap();
+
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| search_bar.swap_include_exclude(cx))
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.included_files_editor.read(cx).text(cx), "");
+                    assert_eq!(
+                        search_view.excluded_files_editor.read(cx).text(cx),
+                        "*.rs, *.ts"
+                    );
+                    assert_eq!(
+                        search_view
+                            .results_editor
+                            .update(cx, |editor, cx| editor.display_text(cx)),
+                        "\n\nONE",
+                        "The search should run again for the complement of the filters"
+                    );
+                })
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_cycle_fields(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree("/dir", json!({ "one.rs": "const ONE: usize = 1;" }))
+            .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
+        window
+            .update(cx, {
+                let search_bar = search_bar.clone();
+                move |workspace, cx| {
+                    workspace.active_pane().update(cx, move |pane, cx| {
+                        pane.toolbar()
+                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
+                    });
+                    ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
+                }
+            })
+            .unwrap();
+        let search_view = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_item_as::<ProjectSearchView>(cx)
+                .unwrap()
+        });
+
+        fn focused_field(
+            search_view: &View<ProjectSearchView>,
+            cx: &WindowContext,
+        ) -> &'static str {
+            let search_view = search_view.read(cx);
+            [
+                (&search_view.query_editor, "query"),
+                (&search_view.replacement_editor, "replace"),
+                (&search_view.included_files_editor, "include"),
+                (&search_view.excluded_files_editor, "exclude"),
+            ]
+            .into_iter()
+            .find(|(editor, _)| editor.focus_handle(cx).is_focused(cx))
+            .map_or("none", |(_, name)| name)
+        }
+
+        let cycle = |direction: Direction, cx: &mut TestAppContext| {
+            window
+                .update(cx, |_, cx| {
+                    search_bar.update(cx, |search_bar, cx| search_bar.cycle_field(direction, cx));
+                    focused_field(&search_view, cx)
+                })
+                .unwrap()
+        };
+
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.toggle_filters(cx);
+                    search_bar.focus_search(cx);
+                })
+            })
+            .unwrap();
+        assert_eq!(cycle(Direction::Next, cx), "include");
+        assert_eq!(cycle(Direction::Next, cx), "exclude");
+        assert_eq!(cycle(Direction::Next, cx), "query");
+        assert_eq!(cycle(Direction::Prev, cx), "exclude");
+
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.toggle_replace(&ToggleReplace, cx)
+                })
+            })
+            .unwrap();
+        assert_eq!(cycle(Direction::Next, cx), "include");
+        assert_eq!(cycle(Direction::Next, cx), "exclude");
+        assert_eq!(cycle(Direction::Next, cx), "query");
+        assert_eq!(cycle(Direction::Next, cx), "replace");
+        assert_eq!(cycle(Direction::Prev, cx), "query");
+        assert_eq!(cycle(Direction::Prev, cx), "exclude");
+
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.toggle_replace(&ToggleReplace, cx)
+                })
+            })
+            .unwrap();
+        assert_eq!(cycle(Direction::Next, cx), "include");
+        assert_eq!(cycle(Direction::Prev, cx), "query");
+        assert_eq!(cycle(Direction::Prev, cx), "exclude");
+    }
+
+    #[gpui::test]
+    async fn test_match_counts_by_directory(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "src": {
+                    "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                    "nested": {
+                        "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+                    },
+                },
+                "docs": {
+                    "four.md": "no matches here",
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                let counts = search_view.match_counts_by_directory(cx);
+                let count = |path: &str| {
+                    counts
+                        .get(&ProjectPath {
+                            worktree_id,
+                            path: Arc::from(Path::new(path)),
+                        })
+                        .copied()
+                };
+                assert_eq!(count(""), Some(7));
+                assert_eq!(count("src"), Some(6));
+                assert_eq!(count("src/nested"), Some(2));
+                assert_eq!(count("docs"), None);
+                assert_eq!(counts.len(), 3, "Only directories should be counted");
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_match_read_api(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.match_count(cx), 0);
+                assert_eq!(search_view.active_match_index(), None);
+                assert_eq!(search_view.matches(cx).count(), 0);
+            })
+            .unwrap();
+
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                let path = |path: &str| {
+                    Some(ProjectPath {
+                        worktree_id,
+                        path: Arc::from(Path::new(path)),
+                    })
+                };
+                let point = language::Point::new;
+                assert_eq!(search_view.match_count(cx), 5);
+                assert_eq!(search_view.active_match_index(), Some(0));
+                assert_eq!(
+                    search_view.matches(cx).collect::<Vec<_>>(),
+                    [
+                        (path("one.rs"), point(0, 6)..point(0, 9)),
+                        (path("two.rs"), point(0, 19)..point(0, 22)),
+                        (path("two.rs"), point(0, 24)..point(0, 27)),
+                        (path("two.rs"), point(0, 30)..point(0, 33)),
+                        (path("two.rs"), point(0, 35)..point(0, 38)),
+                    ]
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_results_truncated(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "many.txt": "x ".repeat(10_001),
+                "one.rs": "const ONE: usize = 1;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "x", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert!(search_view.results_truncated(cx));
+            })
+            .unwrap();
+
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert!(
+                    !search_view.results_truncated(cx),
+                    "Truncation should be reset for every new search"
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_results_editor_is_read_only(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "TWO", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                let original_text = search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx));
+
+                // Typing into the results does nothing.
+                search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.handle_input("typo", cx));
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    original_text
+                );
+
+                // Replacements still apply.
+                search_view
+                    .replacement_editor
+                    .update(cx, |editor, cx| editor.set_text("2", cx));
+                search_view.replace_next(&ReplaceNext, cx);
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst 2: usize = one::ONE + one::ONE;"
+                );
+                search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.handle_input("typo", cx));
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst 2: usize = one::ONE + one::ONE;"
+                );
+
+                // Saving follows whether the results can be changed.
+                assert!(!search_view.can_save(cx));
+                search_view.replace_enabled = true;
+                assert!(search_view.can_save(cx));
+                search_view.replace_enabled = false;
+
+                // Editing can be explicitly enabled.
+                search_view.toggle_edit_results(cx);
+                search_view.results_editor.update(cx, |editor, cx| {
+                    assert!(!editor.read_only(cx));
+                });
+                assert!(search_view.can_save(cx));
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_replace_match_at_cursor(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree("/dir", json!({ "one.rs": "let a = ONE + ONE + ONE;" }))
+            .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view
+                    .replacement_editor
+                    .update(cx, |editor, cx| editor.set_text("1", cx));
+
+                // Only the match under the cursor is replaced.
+                search_view.results_editor.update(cx, |editor, cx| {
+                    editor.change_selections(None, cx, |s| {
+                        s.select_display_ranges(
+                            [DisplayPoint::new(2, 16)..DisplayPoint::new(2, 16)],
+                        )
+                    })
+                });
+                search_view.replace_match_at_cursor(&ReplaceMatchAtCursor, cx);
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nlet a = ONE + 1 + ONE;"
+                );
+
+                // Outside of a match, nothing is replaced.
+                search_view.results_editor.update(cx, |editor, cx| {
+                    editor.change_selections(None, cx, |s| {
+                        s.select_display_ranges([DisplayPoint::new(2, 4)..DisplayPoint::new(2, 4)])
+                    })
+                });
+                search_view.replace_match_at_cursor(&ReplaceMatchAtCursor, cx);
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nlet a = ONE + 1 + ONE;"
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_replace_range_of_inactive_match(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree("/dir", json!({ "one.rs": "let a = ONE + ONE + ONE;" }))
+            .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        let (last_match, query) = search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.active_match_index, Some(0));
+                let model = search.read(cx);
+                let query = model
+                    .active_query
+                    .clone()
+                    .unwrap()
+                    .with_replacement("3".to_string());
+                (model.match_ranges[2].clone(), query)
+            })
+            .unwrap();
+
+        // Only the given match is replaced, while the active one stays as it is.
+        assert!(search.update(cx, |search, cx| {
+            search.replace_range(last_match.clone(), &query, cx)
+        }));
+        cx.background_executor.run_until_parked();
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nlet a = ONE + ONE + 3;"
+                );
+                assert_eq!(search.read(cx).match_ranges.len(), 2);
+                assert_eq!(search_view.active_match_index, Some(0));
+            })
+            .unwrap();
+
+        // Ranges that are no longer matches aren't replaced again.
+        assert!(!search.update(cx, |search, cx| {
+            search.replace_range(last_match, &query, cx)
+        }));
+
+        // The results' context menu offers to replace a match only while replacing is enabled.
+        search_view
+            .update(cx, |search_view, cx| {
+                let second_match = search.read(cx).match_ranges[1].clone();
+                assert_eq!(
+                    search_view.replaceable_match_at(&second_match.start, cx),
+                    None
+                );
+                search_view.replace_enabled = true;
+                assert_eq!(
+                    search_view.replaceable_match_at(&second_match.start, cx),
+                    Some(second_match.clone())
+                );
+                search_view
+                    .replacement_editor
+                    .update(cx, |editor, cx| editor.set_text("2", cx));
+                assert!(search_view.replace_match(&second_match, cx));
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nlet a = ONE + 2 + 3;"
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_replace_all_confirmation(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        let results_text = |cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, cx| {
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx))
+                })
+                .unwrap()
+        };
+        let replace_all = |cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, cx| {
+                    search_view
+                        .replacement_editor
+                        .update(cx, |editor, cx| editor.set_text("1", cx));
+                    search_view.replace_all(&ReplaceAll, cx);
+                })
+                .unwrap();
+            cx.background_executor.run_until_parked();
+        };
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<SearchSettings>(cx, |search_settings| {
+                    search_settings.replace_all_confirmation_threshold = Some(1)
+                });
+            })
+        });
+
+        // Replacing in more files than the threshold asks first, and cancelling keeps the matches.
+        replace_all(cx);
+        assert!(cx.has_pending_prompt());
+        cx.simulate_prompt_answer(1);
+        cx.background_executor.run_until_parked();
+        assert_eq!(
+            results_text(cx),
+            "\n\nconst ONE: usize = 1;\n\nconst TWO: usize = one::ONE;"
+        );
+
+        // Confirming after the matches changed replaces nothing.
+        replace_all(cx);
+        assert!(cx.has_pending_prompt());
+        perform_search(search_view, "ONE", cx);
+        cx.simulate_prompt_answer(0);
+        cx.background_executor.run_until_parked();
+        assert_eq!(
+            results_text(cx),
+            "\n\nconst ONE: usize = 1;\n\nconst TWO: usize = one::ONE;"
+        );
+
+        replace_all(cx);
+        assert!(cx.has_pending_prompt());
+        cx.simulate_prompt_answer(0);
+        cx.background_executor.run_until_parked();
+        assert_eq!(
+            results_text(cx),
+            "\n\nconst 1: usize = 1;\n\nconst TWO: usize = 1::1;"
+        );
+
+        // Within the threshold, everything is replaced right away.
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<SearchSettings>(cx, |search_settings| {
+                    search_settings.replace_all_confirmation_threshold = Some(2)
+                });
+            })
+        });
+        perform_search(search_view, "usize", cx);
+        replace_all(cx);
+        assert!(!cx.has_pending_prompt());
+        assert_eq!(
+            results_text(cx),
+            "\n\nconst 1: 1 = 1;\n\nconst TWO: 1 = 1::1;"
+        );
+
+        // With every match replaced, the edited results are still rendered instead of the landing page.
+        search_view
+            .update(cx, |search_view, cx| {
+                assert!(!search_view.has_matches());
+                assert!(search_view.has_results(cx));
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_highlights_follow_edited_results(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "two.rs": "const TWO: usize = 2; // TWO",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "TWO", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.toggle_edit_results(cx);
+                search_view.results_editor.update(cx, |editor, cx| {
+                    editor.change_selections(None, cx, |s| s.select_ranges([0..0]));
+                    editor.handle_input("pub ", cx);
+                });
+            })
+            .unwrap();
+        search_view
+            .update(cx, |search_view, cx| {
+                // Break the second match by replacing its first character.
+                search_view.results_editor.update(cx, |editor, cx| {
+                    editor.change_selections(None, cx, |s| s.select_ranges([29..30]));
+                    editor.handle_input("X", cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor
+            .advance_clock(EDITED_RESULTS_REFRESH_DEBOUNCE);
+        cx.background_executor.run_until_parked();
+
+        search_view
+            .update(cx, |search_view, cx| {
+                let match_background_color = cx.theme().colors().search_match_background;
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\npub const TWO: usize = 2; // XWO"
+                );
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.all_text_background_highlights(cx)),
+                    &[
+                        (
+                            DisplayPoint::new(2, 10)..DisplayPoint::new(2, 13),
+                            match_background_color
+                        ),
+                        (
+                            DisplayPoint::new(2, 10)..DisplayPoint::new(2, 13),
+                            match_background_color
+                        )
+                    ],
+                    "The only match should be highlighted both as a match and as the active one"
+                );
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 1);
+            })
+            .unwrap();
+
+        // Edits to the file from outside the results leave the matches as they were.
+        search_view
+            .update(cx, |search_view, cx| {
+                let model = search_view.model.read(cx);
+                let buffer_id = model.match_ranges[0].start.buffer_id.unwrap();
+                let buffer = model.excerpts.read(cx).buffer(buffer_id).unwrap();
+                buffer.update(cx, |buffer, cx| buffer.edit([(10..11, "X")], None, cx));
+            })
+            .unwrap();
+        cx.background_executor
+            .advance_clock(EDITED_RESULTS_REFRESH_DEBOUNCE);
+        cx.background_executor.run_until_parked();
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 1);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_dim_context_lines(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<SearchSettings>(cx, |search_settings| {
+                    search_settings.dim_context_lines = Some(true)
+                });
+            })
+        });
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "fn a() {}\nfn b() {}\nconst TWO: usize = 2;\nfn c() {}",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "TWO", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                let editor = search_view.results_editor.read(cx);
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let (style, ranges) = editor.text_highlights::<DimmedContextLines>(cx).unwrap();
+                assert_eq!(style.fade_out, Some(0.5));
+                assert_eq!(
+                    ranges
+                        .iter()
+                        .map(|range| snapshot.text_for_range(range.clone()).collect::<String>())
+                        .collect::<Vec<_>>(),
+                    ["fn a() {}\nfn b() {}", "fn c() {}"]
+                );
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_parse_inline_filters() {
+        assert_eq!(
+            ProjectSearchView::parse_inline_filters("error handling"),
+            ("error handling", vec![], vec![], vec![])
+        );
+        assert_eq!(
+            ProjectSearchView::parse_inline_filters("path:src/**/*.rs error handling"),
+            ("error handling", vec!["src/**/*.rs"], vec![], vec![])
+        );
+        assert_eq!(
+            ProjectSearchView::parse_inline_filters(
+                "  path:src/**  -path:*.md   path:*.rs  error path:x"
+            ),
+            ("error path:x", vec!["src/**", "*.rs"], vec!["*.md"], vec![])
+        );
+        assert_eq!(
+            ProjectSearchView::parse_inline_filters("path:*.rs"),
+            ("", vec!["*.rs"], vec![], vec![])
+        );
+        assert_eq!(
+            ProjectSearchView::parse_inline_filters(" a path"),
+            (" a path", vec![], vec![], vec![])
+        );
+        assert_eq!(
+            ProjectSearchView::parse_inline_filters("lang:rust -path:tests/** lang:TOML unwrap"),
+            ("unwrap", vec![], vec!["tests/**"], vec!["rust", "TOML"])
+        );
+        assert_eq!(
+            ProjectSearchView::parse_inline_filters("path: lang:rust"),
+            ("path: lang:rust", vec![], vec![], vec![])
+        );
+        assert_eq!(
+            ProjectSearchView::parse_inline_filters("lang:rust -path: x"),
+            ("-path: x", vec![], vec![], vec!["rust"])
+        );
+    }
+
+    #[gpui::test]
+    async fn test_inline_path_filters(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.ts": "const ONE = 1;",
+                "three.md": "ONE",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "path:*.ts ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst ONE = 1;"
+                );
+            })
+            .unwrap();
+
+        // Inline globs are combined with the ones from the filter editors.
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view
+                    .included_files_editor
+                    .update(cx, |editor, cx| editor.set_text("*.md", cx));
+            })
+            .unwrap();
+        perform_search(search_view, "path:*.ts ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 2);
+            })
+            .unwrap();
+
+        perform_search(search_view, "-path:*.ts ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                let results_text = search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx));
+                assert_eq!(results_text, "\n\nONE");
+            })
+            .unwrap();
+
+        // Splitting the results restores the query with its inline filters.
+        let cloned_view = search_view
+            .update(cx, |search_view, cx| {
+                let model = search_view.model.update(cx, |model, cx| model.clone(cx));
+                cx.new_view(|cx| ProjectSearchView::new(model, cx, None))
+            })
+            .unwrap();
+        cx.run_until_parked();
+        search_view
+            .update(cx, |_, cx| {
+                assert_eq!(
+                    cloned_view.read(cx).query_editor.read(cx).text(cx),
+                    "-path:*.ts ONE"
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_inline_language_filters(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.ts": "const ONE = 1;",
+                "three.md": "ONE",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        project.read_with(cx, |project, _| {
+            project.languages().add(Arc::new(Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    matcher: LanguageMatcher {
+                        path_suffixes: vec!["rs".to_string()],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                None,
+            )))
+        });
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "lang:rust ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst ONE: usize = 1;"
+                );
+            })
+            .unwrap();
+
+        // Languages missing from the registry are reported instead of matching nothing.
+        perform_search(search_view, "lang:cobol ONE", cx);
+        search_view
+            .update(cx, |search_view, _| {
+                assert!(search_view.panels_with_errors.contains(&InputPanel::Query));
+                assert_eq!(
+                    search_view.query_error.as_deref(),
+                    Some("Unknown language: cobol")
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_regex_query_error(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree("/dir", json!({ "one.rs": "const ONE: usize = 1;" }))
+            .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.toggle_search_option(SearchOptions::REGEX, cx);
+            })
+            .unwrap();
+        perform_search(search_view, "(?z)ONE", cx);
+        search_view
+            .update(cx, |search_view, _| {
+                assert!(search_view.panels_with_errors.contains(&InputPanel::Query));
+                let error = search_view.query_error.clone().unwrap();
+                assert!(error.contains("unrecognized flag"), "{error}");
+            })
+            .unwrap();
+
+        perform_search(search_view, "(?s)ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.query_error, None);
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 1);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_deploy_project_search_focus(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+                "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window;
+        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
+
+        let active_item = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .unwrap()
+                .active_pane()
+                .read(cx)
+                .active_item()
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+        });
+        assert!(
+            active_item.is_none(),
+            "Expected no search panel to be active"
+        );
+
+        window
+            .update(cx, move |workspace, cx| {
+                assert_eq!(workspace.panes().len(), 1);
+                workspace.panes()[0].update(cx, move |pane, cx| {
+                    pane.toolbar()
+                        .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
+                });
+
+                ProjectSearchView::deploy_search(workspace, &workspace::DeploySearch::find(), cx)
+            })
+            .unwrap();
+
+        let Some(search_view) = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .unwrap()
+                .active_pane()
+                .read(cx)
+                .active_item()
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+        }) else {
+            panic!("Search view expected to appear after new search event trigger")
+        };
+
+        cx.spawn(|mut cx| async move {
+            window
+                .update(&mut cx, |_, cx| {
+                    cx.dispatch_action(ToggleFocus.boxed_clone())
+                })
+                .unwrap();
+        })
+        .detach();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                assert!(
+                    search_view.query_editor.focus_handle(cx).is_focused(cx),
+                    "Empty search view should be focused after the toggle focus event: no results panel to focus on",
+                );
+           });
+        }).unwrap();
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    let query_editor = &search_view.query_editor;
+                    assert!(
+                        query_editor.focus_handle(cx).is_focused(cx),
+                        "Search view should be focused after the new search view is activated",
+                    );
+                    let query_text = query_editor.read(cx).text(cx);
+                    assert!(
+                        query_text.is_empty(),
+                        "New search query should be empty but got '{query_text}'",
+                    );
+                    let results_text = search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx));
+                    assert!(
+                        results_text.is_empty(),
+                        "Empty search view should have no results but got '{results_text}'"
+                    );
+                });
+            })
+            .unwrap();
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.query_editor.update(cx, |query_editor, cx| {
+                        query_editor.set_text("sOMETHINGtHATsURELYdOESnOTeXIST", cx)
+                    });
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+            search_view.update(cx, |search_view, cx| {
+                let results_text = search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx));
+                assert!(
+                    results_text.is_empty(),
+                    "Search view for mismatching query should have no results but got '{results_text}'"
+                );
+                assert!(
+                    search_view.query_editor.focus_handle(cx).is_focused(cx),
+                    "Search view should be focused after mismatching query had been used in search",
+                );
+            });
+        }).unwrap();
+
+        cx.spawn(|mut cx| async move {
+            window.update(&mut cx, |_, cx| {
+                cx.dispatch_action(ToggleFocus.boxed_clone())
+            })
+        })
+        .detach();
+        cx.background_executor.run_until_parked();
+        window.update(cx, |_, cx| {
+            search_view.update(cx, |search_view, cx| {
+                assert!(
+                    search_view.query_editor.focus_handle(cx).is_focused(cx),
+                    "Search view with mismatching query should be focused after the toggle focus event: still no results panel to focus on",
+                );
+            });
+        }).unwrap();
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("TWO", cx));
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window.update(cx, |_, cx| {
+            search_view.update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst THREE: usize = one::ONE + two::TWO;\n\n\nconst TWO: usize = one::ONE + one::ONE;",
+                    "Search view results should match the query"
+                );
+                assert!(
+                    search_view.results_editor.focus_handle(cx).is_focused(cx),
+                    "Search view with mismatching query should be focused after search results are available",
+                );
+            });
+        }).unwrap();
+        cx.spawn(|mut cx| async move {
+            window
+                .update(&mut cx, |_, cx| {
+                    cx.dispatch_action(ToggleFocus.boxed_clone())
+                })
+                .unwrap();
+        })
+        .detach();
+        cx.background_executor.run_until_parked();
+        window.update(cx, |_, cx| {
+            search_view.update(cx, |search_view, cx| {
+                assert!(
+                    search_view.query_editor.focus_handle(cx).is_focused(cx),
+                    "Search view with matching query should move focus back to the query editor after the toggle focus event",
+                );
+                assert_eq!(
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.selections.newest::<usize>(cx).range()),
+                    3..3,
+                    "Query cursor should be restored instead of selecting the whole query",
+                );
+            });
+        }).unwrap();
+
+        workspace
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::deploy_search(workspace, &workspace::DeploySearch::find(), cx)
+            })
+            .unwrap();
+        window.update(cx, |_, cx| {
+            search_view.update(cx, |search_view, cx| {
+                assert_eq!(search_view.query_editor.read(cx).text(cx), "two", "Query should be updated to first search result after search view 2nd open in a row");
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst THREE: usize = one::ONE + two::TWO;\n\n\nconst TWO: usize = one::ONE + one::ONE;",
+                    "Results should be unchanged after search view 2nd open in a row"
+                );
+                assert!(
+                    search_view.query_editor.focus_handle(cx).is_focused(cx),
+                    "Focus should be moved into query editor again after search view 2nd open in a row"
+                );
+            });
+        }).unwrap();
+
+        cx.spawn(|mut cx| async move {
+            window
+                .update(&mut cx, |_, cx| {
+                    cx.dispatch_action(ToggleFocus.boxed_clone())
+                })
+                .unwrap();
+        })
+        .detach();
+        cx.background_executor.run_until_parked();
+        window.update(cx, |_, cx| {
+            search_view.update(cx, |search_view, cx| {
+                assert!(
+                    search_view.results_editor.focus_handle(cx).is_focused(cx),
+                    "Search view with matching query should switch focus to the results editor after the toggle focus event",
+                );
+            });
+        }).unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_new_project_search_focus(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+                "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window;
+        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
+
+        let active_item = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .unwrap()
+                .active_pane()
+                .read(cx)
+                .active_item()
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+        });
+        assert!(
+            active_item.is_none(),
+            "Expected no search panel to be active"
+        );
+
+        window
+            .update(cx, move |workspace, cx| {
+                assert_eq!(workspace.panes().len(), 1);
+                workspace.panes()[0].update(cx, move |pane, cx| {
+                    pane.toolbar()
+                        .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
+                });
+
+                ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
+            })
+            .unwrap();
+
+        let Some(search_view) = cx.read(|cx| {
             workspace
                 .read(cx)
                 .unwrap()
@@ -2427,54 +7944,367 @@ pub mod tests {
         }) else {
             panic!("Search view expected to appear after new search in directory event trigger")
         };
-        cx.background_executor.run_until_parked();
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert!(
-                        search_view.query_editor.focus_handle(cx).is_focused(cx),
-                        "On new search in directory, focus should be moved into query editor"
-                    );
-                    search_view.excluded_files_editor.update(cx, |editor, cx| {
-                        assert!(
-                            editor.display_text(cx).is_empty(),
-                            "New search in directory should not have any excluded files"
-                        );
-                    });
-                    search_view.included_files_editor.update(cx, |editor, cx| {
-                        assert_eq!(
-                            editor.display_text(cx),
-                            a_dir_entry.path.to_str().unwrap(),
-                            "New search in directory should have included dir entry path"
-                        );
-                    });
-                });
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert!(
+                        search_view.query_editor.focus_handle(cx).is_focused(cx),
+                        "On new search in directory, focus should be moved into query editor"
+                    );
+                    search_view.excluded_files_editor.update(cx, |editor, cx| {
+                        assert!(
+                            editor.display_text(cx).is_empty(),
+                            "New search in directory should not have any excluded files"
+                        );
+                    });
+                    search_view.included_files_editor.update(cx, |editor, cx| {
+                        assert_eq!(
+                            editor.display_text(cx),
+                            a_dir_entry.path.to_str().unwrap(),
+                            "New search in directory should have included dir entry path"
+                        );
+                    });
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("const", cx));
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(
+                search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx)),
+                "\n\nconst ONE: usize = 1;\n\n\nconst TWO: usize = one::ONE + one::ONE;",
+                "New search in directory should have a filter that matches a certain directory"
+            );
+                })
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_new_project_search_in_directory_with_query(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a": {
+                    "one.rs": "const ONE: usize = 1;",
+                },
+                "b": {
+                    "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+
+        let search_view = window
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::new_search_in_directory_with_query(
+                    workspace,
+                    Path::new("a"),
+                    Some("ONE"),
+                    cx,
+                );
+                workspace.active_item_as::<ProjectSearchView>(cx).unwrap()
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "ONE");
+                    assert_eq!(
+                        search_view
+                            .results_editor
+                            .update(cx, |editor, cx| editor.display_text(cx)),
+                        "\n\nconst ONE: usize = 1;",
+                        "The query should be searched for right away, within the directory"
+                    );
+                });
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_new_project_search_in_file(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a": {
+                    "one.rs": "const ONE: usize = 1;",
+                    "[two].rs": "const TWO: usize = one::ONE + one::ONE;",
+                },
+                "b": {
+                    "one.rs": "const ONE: usize = 1;",
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+
+        for (file, expected_filter, expected_results) in [
+            ("a/one.rs", "a/one.rs", "\n\nconst ONE: usize = 1;"),
+            (
+                "a/[two].rs",
+                "a/[[]two[]].rs",
+                "\n\nconst TWO: usize = one::ONE + one::ONE;",
+            ),
+        ] {
+            let file_entry = window
+                .update(cx, |workspace, cx| {
+                    workspace
+                        .project()
+                        .read(cx)
+                        .entry_for_path(&(worktree_id, file).into(), cx)
+                        .expect("no entry for file")
+                })
+                .unwrap();
+            assert!(file_entry.is_file());
+
+            let search_view = window
+                .update(cx, |workspace, cx| {
+                    ProjectSearchView::new_search_in_file(workspace, &file_entry.path, cx);
+                    workspace.active_item_as::<ProjectSearchView>(cx).unwrap()
+                })
+                .unwrap();
+            cx.background_executor.run_until_parked();
+            window
+                .update(cx, |_, cx| {
+                    search_view.update(cx, |search_view, cx| {
+                        assert!(
+                            search_view.query_editor.focus_handle(cx).is_focused(cx),
+                            "On new search in file, focus should be moved into query editor"
+                        );
+                        assert_eq!(
+                            search_view.included_files_editor.read(cx).text(cx),
+                            expected_filter
+                        );
+                        search_view.set_query("const", cx);
+                        search_view.search(cx);
+                    });
+                })
+                .unwrap();
+            cx.background_executor.run_until_parked();
+            window
+                .update(cx, |_, cx| {
+                    search_view.update(cx, |search_view, cx| {
+                        assert_eq!(
+                            search_view
+                                .results_editor
+                                .update(cx, |editor, cx| editor.display_text(cx)),
+                            expected_results,
+                            "New search in file should only match within {file}"
+                        );
+                    });
+                })
+                .unwrap();
+        }
+
+        assert_eq!(
+            ProjectSearchView::file_include_glob(Path::new("a/one,two.rs")),
+            None
+        );
+    }
+
+    #[gpui::test]
+    async fn test_refresh_on_save(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a.rs": "const A: usize = 1;",
+                "b.rs": "let b = 2;",
+                "c.rs": "const C: usize = 3;",
+                "d.rs": "let d = 4;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        let set_refresh_on_save = |enabled: bool, cx: &mut TestAppContext| {
+            cx.update(|cx| {
+                cx.update_global::<SettingsStore, _>(|store, cx| {
+                    store.update_user_settings::<SearchSettings>(cx, |settings| {
+                        settings.refresh_on_save = Some(enabled);
+                    });
+                });
+            });
+        };
+        async fn edit_and_save(
+            project: &Model<Project>,
+            path: &str,
+            text: &str,
+            cx: &mut TestAppContext,
+        ) {
+            let buffer = project
+                .update(cx, |project, cx| project.open_local_buffer(path, cx))
+                .await
+                .unwrap();
+            buffer.update(cx, |buffer, cx| {
+                buffer.edit([(0..buffer.len(), text)], None, cx)
+            });
+            project
+                .update(cx, |project, cx| project.save_buffer(buffer, cx))
+                .await
+                .unwrap();
+            cx.background_executor.run_until_parked();
+        }
+        let results = |cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, cx| {
+                    let text = search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx));
+                    (text, search_view.model.read(cx).match_ranges.len())
+                })
+                .unwrap()
+        };
+
+        set_refresh_on_save(true, cx);
+        perform_search(search_view, "const", cx);
+        assert_eq!(
+            results(cx),
+            (
+                "\n\nconst A: usize = 1;\n\n\nconst C: usize = 3;".to_string(),
+                2
+            )
+        );
+
+        // A saved file in the results is searched again.
+        edit_and_save(
+            &project,
+            "/dir/c.rs",
+            "const C: usize = 3;\nconst CC: usize = 33;",
+            cx,
+        )
+        .await;
+        assert_eq!(
+            results(cx),
+            (
+                "\n\nconst A: usize = 1;\n\n\nconst C: usize = 3;\nconst CC: usize = 33;"
+                    .to_string(),
+                3
+            )
+        );
+
+        // A saved file that no longer matches is removed.
+        edit_and_save(&project, "/dir/a.rs", "let a = 1;", cx).await;
+        let refreshed_results = (
+            "\n\nconst C: usize = 3;\nconst CC: usize = 33;".to_string(),
+            2,
+        );
+        assert_eq!(results(cx), refreshed_results);
+
+        // A saved file that had no results is searched too, and placed among the others by its path.
+        edit_and_save(&project, "/dir/b.rs", "const B: usize = 2;", cx).await;
+        let refreshed_results = (
+            "\n\nconst B: usize = 2;\n\n\nconst C: usize = 3;\nconst CC: usize = 33;".to_string(),
+            3,
+        );
+        assert_eq!(results(cx), refreshed_results);
+
+        // Without the setting, results stay as they were.
+        set_refresh_on_save(false, cx);
+        edit_and_save(&project, "/dir/c.rs", "let c = 3;", cx).await;
+        edit_and_save(&project, "/dir/d.rs", "const D: usize = 4;", cx).await;
+        assert_eq!(results(cx), refreshed_results);
+    }
+
+    #[gpui::test]
+    async fn test_search_git_tracked_files_only(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".git": {},
+                ".gitignore": "ignored.rs",
+                "ignored.rs": "const IGNORED: usize = 1;",
+                "tracked.rs": "const TRACKED: usize = 2;",
+                "untracked.rs": "const UNTRACKED: usize = 3;",
+            }),
+        )
+        .await;
+        fs.set_index_for_repo(
+            Path::new("/dir/.git"),
+            &[(Path::new("tracked.rs"), "const TRACKED: usize = 2;".into())],
+        );
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        let results = |cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, cx| {
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx))
+                })
+                .unwrap()
+        };
+
+        perform_search(search_view, "const", cx);
+        assert_eq!(
+            results(cx),
+            "\n\nconst TRACKED: usize = 2;\n\n\nconst UNTRACKED: usize = 3;",
+            "untracked files are searched by default, ignored ones are not"
+        );
+
+        search_view
+            .update(cx, |search_view, _| {
+                search_view.search_options =
+                    SearchOptions::GIT_TRACKED_ONLY | SearchOptions::INCLUDE_IGNORED;
             })
             .unwrap();
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    search_view
-                        .query_editor
-                        .update(cx, |query_editor, cx| query_editor.set_text("const", cx));
-                    search_view.search(cx);
-                });
+        perform_search(search_view, "const", cx);
+        assert_eq!(
+            results(cx),
+            "\n\nconst TRACKED: usize = 2;",
+            "only tracked files are searched, even when ignored files are included"
+        );
+
+        let split_view = search_view
+            .update(cx, |search_view, cx| {
+                search_view.clone_on_split(WorkspaceId::default(), cx)
             })
+            .unwrap()
             .unwrap();
-        cx.background_executor.run_until_parked();
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(
-                search_view
-                    .results_editor
-                    .update(cx, |editor, cx| editor.display_text(cx)),
-                "\n\nconst ONE: usize = 1;\n\n\nconst TWO: usize = one::ONE + one::ONE;",
-                "New search in directory should have a filter that matches a certain directory"
+        cx.run_until_parked();
+        split_view.update(cx, |split_view, _| {
+            assert!(
+                split_view
+                    .search_options
+                    .contains(SearchOptions::GIT_TRACKED_ONLY),
+                "the split keeps searching git-tracked files only"
             );
-                })
-            })
-            .unwrap();
+        });
     }
 
     #[gpui::test]
@@ -2525,75 +8355,255 @@ pub mod tests {
         // Add 3 search items into the history + another unsubmitted one.
         window
             .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    search_view.search_options = SearchOptions::CASE_SENSITIVE;
-                    search_view
-                        .query_editor
-                        .update(cx, |query_editor, cx| query_editor.set_text("ONE", cx));
-                    search_view.search(cx);
+                search_view.update(cx, |search_view, cx| {
+                    search_view.search_options = SearchOptions::CASE_SENSITIVE;
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("ONE", cx));
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("TWO", cx));
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("THREE", cx));
+                    search_view.search(cx);
+                })
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.query_editor.update(cx, |query_editor, cx| {
+                        query_editor.set_text("JUST_TEXT_INPUT", cx)
+                    });
+                })
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        // Ensure that the latest input with search settings is active.
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(
+                        search_view.query_editor.read(cx).text(cx),
+                        "JUST_TEXT_INPUT"
+                    );
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+
+        // Next history query after the latest should set the query to the empty string.
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.next_history_query(&NextHistoryQuery, cx);
+                })
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.next_history_query(&NextHistoryQuery, cx);
+                })
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+
+        // First previous query for empty current query should set the query to the latest submitted one.
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "THREE");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+
+        // Further previous items should go over the history in reverse order.
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+
+        // Previous items should never go behind the first history item.
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "ONE");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "ONE");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+
+        // Next items should go over the history in the original order.
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.next_history_query(&NextHistoryQuery, cx);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("TWO_NEW", cx));
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO_NEW");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+
+        // New search input should add another entry to history and move the selection to the end of the history.
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
                 });
             })
             .unwrap();
-
-        cx.background_executor.run_until_parked();
         window
             .update(cx, |_, cx| {
                 search_view.update(cx, |search_view, cx| {
-                    search_view
-                        .query_editor
-                        .update(cx, |query_editor, cx| query_editor.set_text("TWO", cx));
-                    search_view.search(cx);
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "THREE");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
                 });
             })
             .unwrap();
-        cx.background_executor.run_until_parked();
         window
             .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    search_view
-                        .query_editor
-                        .update(cx, |query_editor, cx| query_editor.set_text("THREE", cx));
-                    search_view.search(cx);
-                })
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
+                });
             })
             .unwrap();
-        cx.background_executor.run_until_parked();
         window
             .update(cx, |_, cx| {
                 search_view.update(cx, |search_view, cx| {
-                    search_view.query_editor.update(cx, |query_editor, cx| {
-                        query_editor.set_text("JUST_TEXT_INPUT", cx)
-                    });
-                })
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.next_history_query(&NextHistoryQuery, cx);
+                });
             })
             .unwrap();
-        cx.background_executor.run_until_parked();
-
-        // Ensure that the latest input with search settings is active.
         window
             .update(cx, |_, cx| {
                 search_view.update(cx, |search_view, cx| {
-                    assert_eq!(
-                        search_view.query_editor.read(cx).text(cx),
-                        "JUST_TEXT_INPUT"
-                    );
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "THREE");
                     assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
                 });
             })
             .unwrap();
-
-        // Next history query after the latest should set the query to the empty string.
         window
             .update(cx, |_, cx| {
                 search_bar.update(cx, |search_bar, cx| {
                     search_bar.next_history_query(&NextHistoryQuery, cx);
-                })
+                });
             })
             .unwrap();
         window
             .update(cx, |_, cx| {
                 search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "");
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO_NEW");
                     assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
                 });
             })
@@ -2602,7 +8612,7 @@ pub mod tests {
             .update(cx, |_, cx| {
                 search_bar.update(cx, |search_bar, cx| {
                     search_bar.next_history_query(&NextHistoryQuery, cx);
-                })
+                });
             })
             .unwrap();
         window
@@ -2613,190 +8623,376 @@ pub mod tests {
                 });
             })
             .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_search_query_history_with_multiple_views(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let worktree_id = project.update(cx, |this, cx| {
+            this.worktrees().next().unwrap().read(cx).id()
+        });
+
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+
+        let panes: Vec<_> = window
+            .update(cx, |this, _| this.panes().to_owned())
+            .unwrap();
+
+        let search_bar_1 = window.build_view(cx, |_| ProjectSearchBar::new());
+        let search_bar_2 = window.build_view(cx, |_| ProjectSearchBar::new());
+
+        assert_eq!(panes.len(), 1);
+        let first_pane = panes.get(0).cloned().unwrap();
+        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 0);
+        window
+            .update(cx, |workspace, cx| {
+                workspace.open_path(
+                    (worktree_id, "one.rs"),
+                    Some(first_pane.downgrade()),
+                    true,
+                    cx,
+                )
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 1);
+
+        // Add a project search item to the first pane
+        window
+            .update(cx, {
+                let search_bar = search_bar_1.clone();
+                let pane = first_pane.clone();
+                move |workspace, cx| {
+                    pane.update(cx, move |pane, cx| {
+                        pane.toolbar()
+                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
+                    });
+
+                    ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
+                }
+            })
+            .unwrap();
+        let search_view_1 = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_item(cx)
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+                .expect("Search view expected to appear after new search event trigger")
+        });
+
+        let second_pane = window
+            .update(cx, |workspace, cx| {
+                workspace.split_and_clone(first_pane.clone(), workspace::SplitDirection::Right, cx)
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(cx.update(|cx| second_pane.read(cx).items_len()), 1);
+
+        assert_eq!(cx.update(|cx| second_pane.read(cx).items_len()), 1);
+        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 2);
+
+        // Add a project search item to the second pane
+        window
+            .update(cx, {
+                let search_bar = search_bar_2.clone();
+                let pane = second_pane.clone();
+                move |workspace, cx| {
+                    assert_eq!(workspace.panes().len(), 2);
+                    pane.update(cx, move |pane, cx| {
+                        pane.toolbar()
+                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
+                    });
+
+                    ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
+                }
+            })
+            .unwrap();
+
+        let search_view_2 = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_item(cx)
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+                .expect("Search view expected to appear after new search event trigger")
+        });
+
+        cx.run_until_parked();
+        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 2);
+        assert_eq!(cx.update(|cx| second_pane.read(cx).items_len()), 2);
+
+        let update_search_view =
+            |search_view: &View<ProjectSearchView>, query: &str, cx: &mut TestAppContext| {
+                window
+                    .update(cx, |_, cx| {
+                        search_view.update(cx, |search_view, cx| {
+                            search_view
+                                .query_editor
+                                .update(cx, |query_editor, cx| query_editor.set_text(query, cx));
+                            search_view.search(cx);
+                        });
+                    })
+                    .unwrap();
+            };
+
+        let active_query =
+            |search_view: &View<ProjectSearchView>, cx: &mut TestAppContext| -> String {
+                window
+                    .update(cx, |_, cx| {
+                        search_view.update(cx, |search_view, cx| {
+                            search_view.query_editor.read(cx).text(cx).to_string()
+                        })
+                    })
+                    .unwrap()
+            };
+
+        let select_prev_history_item =
+            |search_bar: &View<ProjectSearchBar>, cx: &mut TestAppContext| {
+                window
+                    .update(cx, |_, cx| {
+                        search_bar.update(cx, |search_bar, cx| {
+                            search_bar.previous_history_query(&PreviousHistoryQuery, cx);
+                        })
+                    })
+                    .unwrap();
+            };
+
+        let select_next_history_item =
+            |search_bar: &View<ProjectSearchBar>, cx: &mut TestAppContext| {
+                window
+                    .update(cx, |_, cx| {
+                        search_bar.update(cx, |search_bar, cx| {
+                            search_bar.next_history_query(&NextHistoryQuery, cx);
+                        })
+                    })
+                    .unwrap();
+            };
+
+        update_search_view(&search_view_1, "ONE", cx);
+        cx.background_executor.run_until_parked();
+
+        update_search_view(&search_view_2, "TWO", cx);
+        cx.background_executor.run_until_parked();
+
+        assert_eq!(active_query(&search_view_1, cx), "ONE");
+        assert_eq!(active_query(&search_view_2, cx), "TWO");
+
+        // Selecting previous history item should select the query from search view 1.
+        select_prev_history_item(&search_bar_2, cx);
+        assert_eq!(active_query(&search_view_2, cx), "ONE");
+
+        // Selecting the previous history item should not change the query as it is already the first item.
+        select_prev_history_item(&search_bar_2, cx);
+        assert_eq!(active_query(&search_view_2, cx), "ONE");
+
+        // Changing the query in search view 2 should not affect the history of search view 1.
+        assert_eq!(active_query(&search_view_1, cx), "ONE");
+
+        // Deploying a new search in search view 2
+        update_search_view(&search_view_2, "THREE", cx);
+        cx.background_executor.run_until_parked();
+
+        select_next_history_item(&search_bar_2, cx);
+        assert_eq!(active_query(&search_view_2, cx), "");
+
+        select_prev_history_item(&search_bar_2, cx);
+        assert_eq!(active_query(&search_view_2, cx), "THREE");
+
+        select_prev_history_item(&search_bar_2, cx);
+        assert_eq!(active_query(&search_view_2, cx), "TWO");
+
+        select_prev_history_item(&search_bar_2, cx);
+        assert_eq!(active_query(&search_view_2, cx), "ONE");
+
+        select_prev_history_item(&search_bar_2, cx);
+        assert_eq!(active_query(&search_view_2, cx), "ONE");
+
+        // Search view 1 should now see the query from search view 2.
+        assert_eq!(active_query(&search_view_1, cx), "ONE");
+
+        select_next_history_item(&search_bar_2, cx);
+        assert_eq!(active_query(&search_view_2, cx), "TWO");
 
-        // First previous query for empty current query should set the query to the latest submitted one.
-        window
-            .update(cx, |_, cx| {
-                search_bar.update(cx, |search_bar, cx| {
-                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
-                });
-            })
-            .unwrap();
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "THREE");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
-                });
-            })
-            .unwrap();
+        // Here is the new query from search view 2
+        select_next_history_item(&search_bar_2, cx);
+        assert_eq!(active_query(&search_view_2, cx), "THREE");
 
-        // Further previous items should go over the history in reverse order.
-        window
-            .update(cx, |_, cx| {
-                search_bar.update(cx, |search_bar, cx| {
-                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
-                });
-            })
-            .unwrap();
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
-                });
-            })
-            .unwrap();
+        select_next_history_item(&search_bar_2, cx);
+        assert_eq!(active_query(&search_view_2, cx), "");
 
-        // Previous items should never go behind the first history item.
-        window
-            .update(cx, |_, cx| {
-                search_bar.update(cx, |search_bar, cx| {
-                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
-                });
-            })
+        select_next_history_item(&search_bar_1, cx);
+        assert_eq!(active_query(&search_view_1, cx), "TWO");
+
+        select_next_history_item(&search_bar_1, cx);
+        assert_eq!(active_query(&search_view_1, cx), "THREE");
+
+        select_next_history_item(&search_bar_1, cx);
+        assert_eq!(active_query(&search_view_1, cx), "");
+    }
+
+    #[gpui::test]
+    async fn test_deploy_search_with_multiple_panes(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        // Setup 2 panes, both with a file open and one with a project search.
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let worktree_id = project.update(cx, |this, cx| {
+            this.worktrees().next().unwrap().read(cx).id()
+        });
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let panes: Vec<_> = window
+            .update(cx, |this, _| this.panes().to_owned())
             .unwrap();
+        assert_eq!(panes.len(), 1);
+        let first_pane = panes.get(0).cloned().unwrap();
+        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 0);
         window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "ONE");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
-                });
+            .update(cx, |workspace, cx| {
+                workspace.open_path(
+                    (worktree_id, "one.rs"),
+                    Some(first_pane.downgrade()),
+                    true,
+                    cx,
+                )
             })
+            .unwrap()
+            .await
             .unwrap();
-        window
-            .update(cx, |_, cx| {
-                search_bar.update(cx, |search_bar, cx| {
-                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
-                });
+        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 1);
+        let second_pane = window
+            .update(cx, |workspace, cx| {
+                workspace.split_and_clone(first_pane.clone(), workspace::SplitDirection::Right, cx)
             })
+            .unwrap()
             .unwrap();
+        assert_eq!(cx.update(|cx| second_pane.read(cx).items_len()), 1);
+        assert!(window
+            .update(cx, |_, cx| second_pane
+                .focus_handle(cx)
+                .contains_focused(cx))
+            .unwrap());
+        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
         window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "ONE");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
-                });
+            .update(cx, {
+                let search_bar = search_bar.clone();
+                let pane = first_pane.clone();
+                move |workspace, cx| {
+                    assert_eq!(workspace.panes().len(), 2);
+                    pane.update(cx, move |pane, cx| {
+                        pane.toolbar()
+                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
+                    });
+                }
             })
             .unwrap();
 
-        // Next items should go over the history in the original order.
-        window
-            .update(cx, |_, cx| {
-                search_bar.update(cx, |search_bar, cx| {
-                    search_bar.next_history_query(&NextHistoryQuery, cx);
-                });
-            })
-            .unwrap();
+        // Add a project search item to the second pane
         window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
-                });
-            })
-            .unwrap();
+            .update(cx, {
+                let search_bar = search_bar.clone();
+                let pane = second_pane.clone();
+                move |workspace, cx| {
+                    assert_eq!(workspace.panes().len(), 2);
+                    pane.update(cx, move |pane, cx| {
+                        pane.toolbar()
+                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
+                    });
 
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    search_view
-                        .query_editor
-                        .update(cx, |query_editor, cx| query_editor.set_text("TWO_NEW", cx));
-                    search_view.search(cx);
-                });
-            })
-            .unwrap();
-        cx.background_executor.run_until_parked();
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO_NEW");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
-                });
+                    ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
+                }
             })
             .unwrap();
 
-        // New search input should add another entry to history and move the selection to the end of the history.
-        window
-            .update(cx, |_, cx| {
-                search_bar.update(cx, |search_bar, cx| {
-                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
-                });
-            })
-            .unwrap();
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "THREE");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
-                });
-            })
-            .unwrap();
-        window
-            .update(cx, |_, cx| {
-                search_bar.update(cx, |search_bar, cx| {
-                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
-                });
-            })
-            .unwrap();
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
-                });
-            })
-            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(cx.update(|cx| second_pane.read(cx).items_len()), 2);
+        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 1);
+
+        // Focus the first pane
         window
-            .update(cx, |_, cx| {
-                search_bar.update(cx, |search_bar, cx| {
-                    search_bar.next_history_query(&NextHistoryQuery, cx);
+            .update(cx, |workspace, cx| {
+                assert_eq!(workspace.active_pane(), &second_pane);
+                second_pane.update(cx, |this, cx| {
+                    assert_eq!(this.active_item_index(), 1);
+                    this.activate_prev_item(false, cx);
+                    assert_eq!(this.active_item_index(), 0);
                 });
+                workspace.activate_pane_in_direction(workspace::SplitDirection::Left, cx);
             })
             .unwrap();
         window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "THREE");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
-                });
+            .update(cx, |workspace, cx| {
+                assert_eq!(workspace.active_pane(), &first_pane);
+                assert_eq!(first_pane.read(cx).items_len(), 1);
+                assert_eq!(second_pane.read(cx).items_len(), 2);
             })
             .unwrap();
+
+        // Deploy a new search
+        cx.dispatch_action(window.into(), DeploySearch::find());
+
+        // Both panes should now have a project search in them
         window
-            .update(cx, |_, cx| {
-                search_bar.update(cx, |search_bar, cx| {
-                    search_bar.next_history_query(&NextHistoryQuery, cx);
+            .update(cx, |workspace, cx| {
+                assert_eq!(workspace.active_pane(), &first_pane);
+                first_pane.update(cx, |this, _| {
+                    assert_eq!(this.active_item_index(), 1);
+                    assert_eq!(this.items_len(), 2);
                 });
-            })
-            .unwrap();
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO_NEW");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                second_pane.update(cx, |this, cx| {
+                    assert!(!cx.focus_handle().contains_focused(cx));
+                    assert_eq!(this.items_len(), 2);
                 });
             })
             .unwrap();
+
+        // Focus the second pane's non-search item
         window
-            .update(cx, |_, cx| {
-                search_bar.update(cx, |search_bar, cx| {
-                    search_bar.next_history_query(&NextHistoryQuery, cx);
-                });
+            .update(cx, |_workspace, cx| {
+                second_pane.update(cx, |pane, cx| pane.activate_next_item(true, cx));
             })
             .unwrap();
+
+        // Deploy a new search
+        cx.dispatch_action(window.into(), DeploySearch::find());
+
+        // The project search view should now be focused in the second pane
+        // And the number of items should be unchanged.
         window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+            .update(cx, |_workspace, cx| {
+                second_pane.update(cx, |pane, _cx| {
+                    assert!(pane
+                        .active_item()
+                        .unwrap()
+                        .downcast::<ProjectSearchView>()
+                        .is_some());
+
+                    assert_eq!(pane.items_len(), 2);
                 });
             })
             .unwrap();
     }
 
     #[gpui::test]
-    async fn test_search_query_history_with_multiple_views(cx: &mut TestAppContext) {
+    async fn test_deploy_search_skips_pinned_search(cx: &mut TestAppContext) {
         init_test(cx);
 
         let fs = FakeFs::new(cx.background_executor.clone());
@@ -2808,355 +9004,379 @@ pub mod tests {
         )
         .await;
         let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
-        let worktree_id = project.update(cx, |this, cx| {
-            this.worktrees().next().unwrap().read(cx).id()
-        });
-
         let window = cx.add_window(|cx| Workspace::test_new(project, cx));
-        let workspace = window.root(cx).unwrap();
 
-        let panes: Vec<_> = window
-            .update(cx, |this, _| this.panes().to_owned())
+        let pinned_search = window
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::deploy_search(workspace, &DeploySearch::find(), cx);
+                let search = workspace.active_item_as::<ProjectSearchView>(cx).unwrap();
+                search.update(cx, |search, cx| search.toggle_pinned(cx));
+                search
+            })
             .unwrap();
 
-        let search_bar_1 = window.build_view(cx, |_| ProjectSearchBar::new());
-        let search_bar_2 = window.build_view(cx, |_| ProjectSearchBar::new());
-
-        assert_eq!(panes.len(), 1);
-        let first_pane = panes.get(0).cloned().unwrap();
-        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 0);
-        window
+        // Deploying again opens a new search rather than reusing the pinned one.
+        let new_search = window
             .update(cx, |workspace, cx| {
-                workspace.open_path(
-                    (worktree_id, "one.rs"),
-                    Some(first_pane.downgrade()),
-                    true,
-                    cx,
-                )
+                ProjectSearchView::deploy_search(workspace, &DeploySearch::find(), cx);
+                assert_eq!(workspace.active_pane().read(cx).items_len(), 2);
+                workspace.active_item_as::<ProjectSearchView>(cx).unwrap()
             })
-            .unwrap()
-            .await
             .unwrap();
-        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 1);
+        assert_ne!(new_search, pinned_search);
 
-        // Add a project search item to the first pane
+        // Unpinned searches are still reused.
         window
-            .update(cx, {
-                let search_bar = search_bar_1.clone();
-                let pane = first_pane.clone();
-                move |workspace, cx| {
-                    pane.update(cx, move |pane, cx| {
-                        pane.toolbar()
-                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
-                    });
-
-                    ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
-                }
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::deploy_search(workspace, &DeploySearch::find(), cx);
+                assert_eq!(workspace.active_pane().read(cx).items_len(), 2);
+                assert_eq!(
+                    workspace.active_item_as::<ProjectSearchView>(cx).unwrap(),
+                    new_search
+                );
             })
             .unwrap();
-        let search_view_1 = cx.read(|cx| {
-            workspace
-                .read(cx)
-                .active_item(cx)
-                .and_then(|item| item.downcast::<ProjectSearchView>())
-                .expect("Search view expected to appear after new search event trigger")
-        });
+    }
 
-        let second_pane = window
+    #[gpui::test]
+    async fn test_go_to_match_definition(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+
+        let search_view = window
             .update(cx, |workspace, cx| {
-                workspace.split_and_clone(first_pane.clone(), workspace::SplitDirection::Right, cx)
+                ProjectSearchView::deploy_search(workspace, &DeploySearch::find(), cx);
+                workspace.active_item_as::<ProjectSearchView>(cx).unwrap()
             })
-            .unwrap()
             .unwrap();
-        assert_eq!(cx.update(|cx| second_pane.read(cx).items_len()), 1);
-
-        assert_eq!(cx.update(|cx| second_pane.read(cx).items_len()), 1);
-        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 2);
-
-        // Add a project search item to the second pane
         window
-            .update(cx, {
-                let search_bar = search_bar_2.clone();
-                let pane = second_pane.clone();
-                move |workspace, cx| {
-                    assert_eq!(workspace.panes().len(), 2);
-                    pane.update(cx, move |pane, cx| {
-                        pane.toolbar()
-                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
-                    });
-
-                    ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
-                }
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("ONE", cx));
+                    search_view.search(cx);
+                });
             })
             .unwrap();
-
-        let search_view_2 = cx.read(|cx| {
-            workspace
-                .read(cx)
-                .active_item(cx)
-                .and_then(|item| item.downcast::<ProjectSearchView>())
-                .expect("Search view expected to appear after new search event trigger")
-        });
-
-        cx.run_until_parked();
-        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 2);
-        assert_eq!(cx.update(|cx| second_pane.read(cx).items_len()), 2);
-
-        let update_search_view =
-            |search_view: &View<ProjectSearchView>, query: &str, cx: &mut TestAppContext| {
-                window
-                    .update(cx, |_, cx| {
-                        search_view.update(cx, |search_view, cx| {
-                            search_view
-                                .query_editor
-                                .update(cx, |query_editor, cx| query_editor.set_text(query, cx));
-                            search_view.search(cx);
-                        });
-                    })
-                    .unwrap();
-            };
-
-        let active_query =
-            |search_view: &View<ProjectSearchView>, cx: &mut TestAppContext| -> String {
-                window
-                    .update(cx, |_, cx| {
-                        search_view.update(cx, |search_view, cx| {
-                            search_view.query_editor.read(cx).text(cx).to_string()
-                        })
-                    })
-                    .unwrap()
-            };
-
-        let select_prev_history_item =
-            |search_bar: &View<ProjectSearchBar>, cx: &mut TestAppContext| {
-                window
-                    .update(cx, |_, cx| {
-                        search_bar.update(cx, |search_bar, cx| {
-                            search_bar.previous_history_query(&PreviousHistoryQuery, cx);
-                        })
-                    })
-                    .unwrap();
-            };
-
-        let select_next_history_item =
-            |search_bar: &View<ProjectSearchBar>, cx: &mut TestAppContext| {
-                window
-                    .update(cx, |_, cx| {
-                        search_bar.update(cx, |search_bar, cx| {
-                            search_bar.next_history_query(&NextHistoryQuery, cx);
-                        })
-                    })
-                    .unwrap();
-            };
-
-        update_search_view(&search_view_1, "ONE", cx);
         cx.background_executor.run_until_parked();
 
-        update_search_view(&search_view_2, "TWO", cx);
+        // Without a language server, the match's file is opened with the cursor on the match.
+        window
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::go_to_match_definition(workspace, &GoToMatchDefinition, cx);
+            })
+            .unwrap();
         cx.background_executor.run_until_parked();
+        window
+            .update(cx, |workspace, cx| {
+                let editor = workspace.active_item_as::<Editor>(cx).unwrap();
+                let editor = editor.read(cx);
+                assert_eq!(
+                    editor.buffer().read(cx).read(cx).text(),
+                    "const ONE: usize = 1;"
+                );
+                assert_eq!(
+                    editor.selections.newest::<language::Point>(cx).head(),
+                    language::Point::new(0, 6)
+                );
+            })
+            .unwrap();
+    }
 
-        assert_eq!(active_query(&search_view_1, cx), "ONE");
-        assert_eq!(active_query(&search_view_2, cx), "TWO");
-
-        // Selecting previous history item should select the query from search view 1.
-        select_prev_history_item(&search_bar_2, cx);
-        assert_eq!(active_query(&search_view_2, cx), "ONE");
-
-        // Selecting the previous history item should not change the query as it is already the first item.
-        select_prev_history_item(&search_bar_2, cx);
-        assert_eq!(active_query(&search_view_2, cx), "ONE");
+    #[gpui::test]
+    async fn test_open_match_in_split(cx: &mut TestAppContext) {
+        init_test(cx);
 
-        // Changing the query in search view 2 should not affect the history of search view 1.
-        assert_eq!(active_query(&search_view_1, cx), "ONE");
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
 
-        // Deploying a new search in search view 2
-        update_search_view(&search_view_2, "THREE", cx);
+        let search_view = window
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::deploy_search(workspace, &DeploySearch::find(), cx);
+                workspace.active_item_as::<ProjectSearchView>(cx).unwrap()
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("ONE", cx));
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
         cx.background_executor.run_until_parked();
 
-        select_next_history_item(&search_bar_2, cx);
-        assert_eq!(active_query(&search_view_2, cx), "");
-
-        select_prev_history_item(&search_bar_2, cx);
-        assert_eq!(active_query(&search_view_2, cx), "THREE");
-
-        select_prev_history_item(&search_bar_2, cx);
-        assert_eq!(active_query(&search_view_2, cx), "TWO");
-
-        select_prev_history_item(&search_bar_2, cx);
-        assert_eq!(active_query(&search_view_2, cx), "ONE");
-
-        select_prev_history_item(&search_bar_2, cx);
-        assert_eq!(active_query(&search_view_2, cx), "ONE");
-
-        // Search view 1 should now see the query from search view 2.
-        assert_eq!(active_query(&search_view_1, cx), "ONE");
-
-        select_next_history_item(&search_bar_2, cx);
-        assert_eq!(active_query(&search_view_2, cx), "TWO");
-
-        // Here is the new query from search view 2
-        select_next_history_item(&search_bar_2, cx);
-        assert_eq!(active_query(&search_view_2, cx), "THREE");
-
-        select_next_history_item(&search_bar_2, cx);
-        assert_eq!(active_query(&search_view_2, cx), "");
-
-        select_next_history_item(&search_bar_1, cx);
-        assert_eq!(active_query(&search_view_1, cx), "TWO");
+        let split_editor_text = |cx: &mut TestAppContext| {
+            window
+                .update(cx, |workspace, cx| {
+                    assert_eq!(workspace.panes().len(), 2);
+                    assert_eq!(
+                        workspace.active_item(cx).unwrap().item_id(),
+                        search_view.item_id(),
+                        "The results should stay active"
+                    );
+                    let editor = workspace.panes()[1]
+                        .read(cx)
+                        .active_item()
+                        .and_then(|item| item.downcast::<Editor>())
+                        .unwrap();
+                    let editor = editor.read(cx);
+                    (
+                        editor.buffer().read(cx).read(cx).text(),
+                        editor.selections.newest::<language::Point>(cx).head(),
+                    )
+                })
+                .unwrap()
+        };
 
-        select_next_history_item(&search_bar_1, cx);
-        assert_eq!(active_query(&search_view_1, cx), "THREE");
+        window
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::open_match_in_split(workspace, &OpenMatchInSplit, cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        assert_eq!(
+            split_editor_text(cx),
+            (
+                "const ONE: usize = 1;".to_string(),
+                language::Point::new(0, 6)
+            )
+        );
 
-        select_next_history_item(&search_bar_1, cx);
-        assert_eq!(active_query(&search_view_1, cx), "");
+        // Further matches are opened in the same split.
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.select_match(Direction::Next, cx);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::open_match_in_split(workspace, &OpenMatchInSplit, cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        assert_eq!(
+            split_editor_text(cx),
+            (
+                "const TWO: usize = one::ONE + one::ONE;".to_string(),
+                language::Point::new(0, 24)
+            )
+        );
     }
 
     #[gpui::test]
-    async fn test_deploy_search_with_multiple_panes(cx: &mut TestAppContext) {
+    async fn test_default_search_filters(cx: &mut TestAppContext) {
         init_test(cx);
 
-        // Setup 2 panes, both with a file open and one with a project search.
         let fs = FakeFs::new(cx.background_executor.clone());
         fs.insert_tree(
-            "/dir",
+            "/saved_filters",
             json!({
                 "one.rs": "const ONE: usize = 1;",
             }),
         )
         .await;
-        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
-        let worktree_id = project.update(cx, |this, cx| {
-            this.worktrees().next().unwrap().read(cx).id()
-        });
-        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
-        let panes: Vec<_> = window
-            .update(cx, |this, _| this.panes().to_owned())
-            .unwrap();
-        assert_eq!(panes.len(), 1);
-        let first_pane = panes.get(0).cloned().unwrap();
-        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 0);
-        window
-            .update(cx, |workspace, cx| {
-                workspace.open_path(
-                    (worktree_id, "one.rs"),
-                    Some(first_pane.downgrade()),
-                    true,
-                    cx,
-                )
+        let project = Project::test(fs.clone(), ["/saved_filters".as_ref()], cx).await;
+        let new_search_view = |cx: &mut TestAppContext| {
+            let search = cx.new_model(|cx| ProjectSearch::new(project.clone(), cx));
+            cx.add_window(|cx| ProjectSearchView::new(search, cx, None))
+        };
+
+        let search_view = new_search_view(cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert!(!search_view.filters_enabled);
+                search_view
+                    .included_files_editor
+                    .update(cx, |editor, cx| editor.set_text("src/**", cx));
+                search_view
+                    .excluded_files_editor
+                    .update(cx, |editor, cx| editor.set_text("target/*", cx));
+                search_view.save_filters_as_default(cx);
             })
-            .unwrap()
-            .await
             .unwrap();
-        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 1);
-        let second_pane = window
-            .update(cx, |workspace, cx| {
-                workspace.split_and_clone(first_pane.clone(), workspace::SplitDirection::Right, cx)
+        cx.background_executor.run_until_parked();
+
+        let search_view = new_search_view(cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert!(search_view.filters_enabled);
+                assert_eq!(
+                    search_view.included_files_editor.read(cx).text(cx),
+                    "src/**"
+                );
+                assert_eq!(
+                    search_view.excluded_files_editor.read(cx).text(cx),
+                    "target/*"
+                );
+                search_view.reset_filters(cx);
+                assert_eq!(search_view.included_files_editor.read(cx).text(cx), "");
+                assert_eq!(search_view.excluded_files_editor.read(cx).text(cx), "");
             })
-            .unwrap()
             .unwrap();
-        assert_eq!(cx.update(|cx| second_pane.read(cx).items_len()), 1);
-        assert!(window
-            .update(cx, |_, cx| second_pane
-                .focus_handle(cx)
-                .contains_focused(cx))
-            .unwrap());
-        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
-        window
-            .update(cx, {
-                let search_bar = search_bar.clone();
-                let pane = first_pane.clone();
-                move |workspace, cx| {
-                    assert_eq!(workspace.panes().len(), 2);
-                    pane.update(cx, move |pane, cx| {
-                        pane.toolbar()
-                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
-                    });
-                }
+        cx.background_executor.run_until_parked();
+
+        let search_view = new_search_view(cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert!(!search_view.filters_enabled);
+                assert_eq!(search_view.included_files_editor.read(cx).text(cx), "");
             })
             .unwrap();
+    }
 
-        // Add a project search item to the second pane
-        window
-            .update(cx, {
-                let search_bar = search_bar.clone();
-                let pane = second_pane.clone();
-                move |workspace, cx| {
-                    assert_eq!(workspace.panes().len(), 2);
-                    pane.update(cx, move |pane, cx| {
-                        pane.toolbar()
-                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
-                    });
+    #[gpui::test]
+    async fn test_search_presets(cx: &mut TestAppContext) {
+        init_test(cx);
 
-                    ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
-                }
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/search_presets",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/search_presets".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let search = cx.new_model(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search, cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.set_query("TWO", cx);
+                search_view.save_search_preset(Some("Uses of ONE".to_string()), cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.set_query("ONE", cx);
+                search_view.toggle_search_option(SearchOptions::CASE_SENSITIVE, cx);
+                search_view.toggle_search_option(SearchOptions::REGEX, cx);
+                search_view.toggle_literal(cx);
+                search_view
+                    .included_files_editor
+                    .update(cx, |editor, cx| editor.set_text("two.rs", cx));
+                search_view.save_search_preset(Some("Uses of ONE".to_string()), cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.save_search_preset(None, cx)
             })
             .unwrap();
+        cx.background_executor.run_until_parked();
 
-        cx.run_until_parked();
-        assert_eq!(cx.update(|cx| second_pane.read(cx).items_len()), 2);
-        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 1);
+        let presets = cx.update(|cx| load_search_presets(&project, cx));
+        assert_eq!(
+            presets
+                .iter()
+                .map(|preset| preset.name.as_str())
+                .collect::<Vec<_>>(),
+            ["Uses of ONE", "ONE"],
+            "Saving under an existing name should replace that preset"
+        );
 
-        // Focus the first pane
         window
             .update(cx, |workspace, cx| {
-                assert_eq!(workspace.active_pane(), &second_pane);
-                second_pane.update(cx, |this, cx| {
-                    assert_eq!(this.active_item_index(), 1);
-                    this.activate_prev_item(false, cx);
-                    assert_eq!(this.active_item_index(), 0);
-                });
-                workspace.activate_pane_in_direction(workspace::SplitDirection::Left, cx);
+                ProjectSearchView::apply_search_preset(
+                    workspace,
+                    &ApplySearchPreset {
+                        name: "Uses of ONE".to_string(),
+                    },
+                    cx,
+                )
             })
             .unwrap();
+        cx.background_executor.run_until_parked();
         window
             .update(cx, |workspace, cx| {
-                assert_eq!(workspace.active_pane(), &first_pane);
-                assert_eq!(first_pane.read(cx).items_len(), 1);
-                assert_eq!(second_pane.read(cx).items_len(), 2);
+                let search_view = workspace.active_item_as::<ProjectSearchView>(cx).unwrap();
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "ONE");
+                    assert_eq!(
+                        search_view.search_options,
+                        SearchOptions::CASE_SENSITIVE | SearchOptions::REGEX
+                    );
+                    assert!(search_view.literal_enabled);
+                    assert!(search_view.filters_enabled);
+                    assert_eq!(
+                        search_view.included_files_editor.read(cx).text(cx),
+                        "two.rs"
+                    );
+                    assert_eq!(
+                        search_view
+                            .results_editor
+                            .update(cx, |editor, cx| editor.display_text(cx)),
+                        "\n\nconst TWO: usize = one::ONE;"
+                    );
+                });
             })
             .unwrap();
+    }
 
-        // Deploy a new search
-        cx.dispatch_action(window.into(), DeploySearch::find());
-
-        // Both panes should now have a project search in them
-        window
-            .update(cx, |workspace, cx| {
-                assert_eq!(workspace.active_pane(), &first_pane);
-                first_pane.update(cx, |this, _| {
-                    assert_eq!(this.active_item_index(), 1);
-                    assert_eq!(this.items_len(), 2);
-                });
-                second_pane.update(cx, |this, cx| {
-                    assert!(!cx.focus_handle().contains_focused(cx));
-                    assert_eq!(this.items_len(), 2);
+    #[gpui::test]
+    async fn test_show_filters_by_default(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<SearchSettings>(cx, |search_settings| {
+                    search_settings.show_filters_by_default = Some(true)
                 });
             })
-            .unwrap();
+        });
 
-        // Focus the second pane's non-search item
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree("/dir", json!({ "one.rs": "const ONE: usize = 1;" }))
+            .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
         window
-            .update(cx, |_workspace, cx| {
-                second_pane.update(cx, |pane, cx| pane.activate_next_item(true, cx));
+            .update(cx, |workspace, cx| {
+                workspace.active_pane().update(cx, |pane, cx| {
+                    pane.toolbar()
+                        .update(cx, |toolbar, cx| toolbar.add_item(search_bar.clone(), cx))
+                });
+                ProjectSearchView::deploy_search(workspace, &workspace::DeploySearch::find(), cx)
             })
             .unwrap();
 
-        // Deploy a new search
-        cx.dispatch_action(window.into(), DeploySearch::find());
-
-        // The project search view should now be focused in the second pane
-        // And the number of items should be unchanged.
         window
-            .update(cx, |_workspace, cx| {
-                second_pane.update(cx, |pane, _cx| {
-                    assert!(pane
-                        .active_item()
-                        .unwrap()
-                        .downcast::<ProjectSearchView>()
-                        .is_some());
-
-                    assert_eq!(pane.items_len(), 2);
-                });
+            .update(cx, |workspace, cx| {
+                let search_view = workspace
+                    .active_item_as::<ProjectSearchView>(cx)
+                    .expect("Search view expected to appear after new search event trigger");
+                assert!(search_view.read(cx).filters_enabled);
+                assert_eq!(search_bar.read(cx).row_count(cx), 2);
             })
             .unwrap();
     }
@@ -3232,6 +9452,62 @@ pub mod tests {
             .expect("unable to update search view");
     }
 
+    #[gpui::test]
+    async fn test_autoscroll_on_navigate(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "1.rs": "\n\n\n\n\n A \n\n\n\n\n",
+                "2.rs": "\n\n\n\n\n A \n\n\n\n\n",
+                "3.rs": "\n\n\n\n\n A \n\n\n\n\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        let set_autoscroll_on_navigate = |enabled: bool, cx: &mut TestAppContext| {
+            cx.update(|cx| {
+                cx.update_global::<SettingsStore, _>(|store, cx| {
+                    store.update_user_settings::<SearchSettings>(cx, |settings| {
+                        settings.autoscroll_on_navigate = Some(enabled);
+                    });
+                });
+            });
+        };
+
+        set_autoscroll_on_navigate(false, cx);
+        perform_search(search_view, "A", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.results_editor.update(cx, |editor, cx| {
+                    editor.set_scroll_position(Point::new(0., 3.), cx);
+                });
+                search_view.select_match(Direction::Next, cx);
+                search_view.results_editor.update(cx, |editor, cx| {
+                    assert!(!editor.autoscroll_requested());
+                    assert_eq!(editor.scroll_position(cx), Point::new(0., 3.));
+                });
+            })
+            .unwrap();
+        search_view
+            .update(cx, |search_view, _| {
+                assert_eq!(search_view.active_match_index, Some(1));
+            })
+            .unwrap();
+
+        set_autoscroll_on_navigate(true, cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.select_match(Direction::Next, cx);
+                assert!(search_view.results_editor.read(cx).autoscroll_requested());
+            })
+            .unwrap();
+    }
+
     fn init_test(cx: &mut TestAppContext) {
         cx.update(|cx| {
             let settings = SettingsStore::test(cx);
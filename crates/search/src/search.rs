@@ -1,14 +1,16 @@
 use bitflags::bitflags;
 pub use buffer_search::BufferSearchBar;
 use gpui::{actions, Action, AppContext, IntoElement};
-use project::search::SearchQuery;
+use project::search::{Inversion, SearchQuery};
 pub use project_search::ProjectSearchView;
 use ui::{prelude::*, Tooltip};
 use ui::{ButtonStyle, IconButton};
 
 pub mod buffer_search;
+mod path_completion;
 pub mod project_search;
 pub(crate) mod search_bar;
+pub mod search_settings;
 
 pub fn init(cx: &mut AppContext) {
     menu::init();
@@ -23,7 +25,10 @@ actions!(
         ToggleWholeWord,
         ToggleCaseSensitive,
         ToggleIncludeIgnored,
+        ToggleGitTrackedOnly,
         ToggleRegex,
+        ToggleInvertMatches,
+        ToggleInvertFiles,
         ToggleReplace,
         SelectNextMatch,
         SelectPrevMatch,
@@ -43,6 +48,9 @@ bitflags! {
         const CASE_SENSITIVE = 0b010;
         const INCLUDE_IGNORED = 0b100;
         const REGEX = 0b1000;
+        const INVERT_MATCHES = 0b10000;
+        const INVERT_FILES = 0b100000;
+        const GIT_TRACKED_ONLY = 0b1000000;
     }
 }
 
@@ -53,6 +61,9 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => "match case",
             SearchOptions::INCLUDE_IGNORED => "include Ignored",
             SearchOptions::REGEX => "regular expression",
+            SearchOptions::INVERT_MATCHES => "invert matches",
+            SearchOptions::INVERT_FILES => "invert whole files",
+            SearchOptions::GIT_TRACKED_ONLY => "git-tracked files only",
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -63,6 +74,9 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => ui::IconName::CaseSensitive,
             SearchOptions::INCLUDE_IGNORED => ui::IconName::FileGit,
             SearchOptions::REGEX => ui::IconName::Regex,
+            SearchOptions::INVERT_MATCHES => ui::IconName::XCircle,
+            SearchOptions::INVERT_FILES => ui::IconName::FileGeneric,
+            SearchOptions::GIT_TRACKED_ONLY => ui::IconName::GitBranch,
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -73,6 +87,9 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => Box::new(ToggleCaseSensitive),
             SearchOptions::INCLUDE_IGNORED => Box::new(ToggleIncludeIgnored),
             SearchOptions::REGEX => Box::new(ToggleRegex),
+            SearchOptions::INVERT_MATCHES => Box::new(ToggleInvertMatches),
+            SearchOptions::INVERT_FILES => Box::new(ToggleInvertFiles),
+            SearchOptions::GIT_TRACKED_ONLY => Box::new(ToggleGitTrackedOnly),
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -87,6 +104,12 @@ impl SearchOptions {
         options.set(SearchOptions::CASE_SENSITIVE, query.case_sensitive());
         options.set(SearchOptions::INCLUDE_IGNORED, query.include_ignored());
         options.set(SearchOptions::REGEX, query.is_regex());
+        options.set(SearchOptions::INVERT_MATCHES, query.inversion().is_some());
+        options.set(
+            SearchOptions::INVERT_FILES,
+            query.inversion() == Some(Inversion::Files),
+        );
+        options.set(SearchOptions::GIT_TRACKED_ONLY, query.git_tracked_only());
         options
     }
 
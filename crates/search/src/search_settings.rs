@@ -0,0 +1,102 @@
+use gpui::AppContext;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct SearchSettings {
+    pub dim_context_lines: bool,
+    pub show_filters_by_default: bool,
+    pub option_buttons: OptionButtons,
+    pub path_display: PathDisplay,
+    pub replace_all_confirmation_threshold: usize,
+    pub searchable_results: bool,
+    pub autoscroll_on_navigate: bool,
+    pub refresh_on_save: bool,
+}
+
+/// How file paths are shown in project search results.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PathDisplay {
+    /// Paths relative to the worktree containing the file.
+    #[default]
+    Relative,
+    /// Absolute paths on the file system.
+    Absolute,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct OptionButtons {
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    pub regex: bool,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct SearchSettingsContent {
+    /// Whether to fade out the context lines around matches in project search results,
+    /// so that the matching lines stand out.
+    ///
+    /// Default: false
+    pub dim_context_lines: Option<bool>,
+    /// Whether to show the include and exclude filters when a project search opens.
+    ///
+    /// Default: false
+    pub show_filters_by_default: Option<bool>,
+    /// Which search option toggles to show as buttons next to the project search query.
+    pub option_buttons: Option<OptionButtonsContent>,
+    /// Whether file headers and copied match paths in project search results are
+    /// relative to their worktree or absolute.
+    ///
+    /// Default: relative
+    pub path_display: Option<PathDisplay>,
+    /// How many files a project-wide replace-all may change before asking for confirmation.
+    ///
+    /// Default: 20
+    pub replace_all_confirmation_threshold: Option<usize>,
+    /// Whether the buffer search can find text within project search results, without
+    /// running the project search again.
+    ///
+    /// Default: false
+    pub searchable_results: Option<bool>,
+    /// Whether moving between project search matches scrolls the results to reveal the
+    /// selected match. When disabled, the selection still moves without scrolling.
+    ///
+    /// Default: true
+    pub autoscroll_on_navigate: Option<bool>,
+    /// Whether saving a file searches it again for the current project search, so that its
+    /// results stay up to date without searching the whole project.
+    ///
+    /// Default: false
+    pub refresh_on_save: Option<bool>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug, PartialEq, Eq)]
+pub struct OptionButtonsContent {
+    /// Whether to show the button toggling case sensitive search.
+    ///
+    /// Default: true
+    pub case_sensitive: Option<bool>,
+    /// Whether to show the button toggling whole word search.
+    ///
+    /// Default: true
+    pub whole_word: Option<bool>,
+    /// Whether to show the button toggling regex search.
+    ///
+    /// Default: true
+    pub regex: Option<bool>,
+}
+
+impl Settings for SearchSettings {
+    const KEY: Option<&'static str> = Some("search");
+
+    type FileContent = SearchSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
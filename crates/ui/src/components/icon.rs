@@ -78,6 +78,7 @@ pub enum IconName {
     Folder,
     FolderOpen,
     FolderX,
+    GitBranch,
     Github,
     Hash,
     InlayHint,
@@ -95,6 +96,7 @@ pub enum IconName {
     PageDown,
     PageUp,
     Pencil,
+    Pin,
     Person,
     Play,
     Plus,
@@ -181,6 +183,7 @@ impl IconName {
             IconName::Folder => "icons/file_icons/folder.svg",
             IconName::FolderOpen => "icons/file_icons/folder_open.svg",
             IconName::FolderX => "icons/stop_sharing.svg",
+            IconName::GitBranch => "icons/git_branch.svg",
             IconName::Github => "icons/github.svg",
             IconName::Hash => "icons/hash.svg",
             IconName::InlayHint => "icons/inlay_hint.svg",
@@ -199,6 +202,7 @@ impl IconName {
             IconName::PageUp => "icons/page_up.svg",
             IconName::Person => "icons/person.svg",
             IconName::Pencil => "icons/pencil.svg",
+            IconName::Pin => "icons/pin.svg",
             IconName::Play => "icons/play.svg",
             IconName::Plus => "icons/plus.svg",
             IconName::Public => "icons/public.svg",
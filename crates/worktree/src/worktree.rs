@@ -3138,6 +3138,8 @@ pub struct Entry {
     pub path: Arc<Path>,
     pub inode: u64,
     pub mtime: Option<SystemTime>,
+    /// The size of the file in bytes as of the last scan.
+    pub size: u64,
     pub is_symlink: bool,
 
     /// Whether this entry is ignored by Git.
@@ -3208,6 +3210,7 @@ impl Entry {
             path,
             inode: metadata.inode,
             mtime: Some(metadata.mtime),
+            size: metadata.len,
             is_symlink: metadata.is_symlink,
             is_ignored: false,
             is_external: false,
@@ -4793,6 +4796,7 @@ impl<'a> From<&'a Entry> for proto::Entry {
             is_ignored: entry.is_ignored,
             is_external: entry.is_external,
             git_status: entry.git_status.map(git_status_to_proto),
+            size: entry.size,
         }
     }
 }
@@ -4815,6 +4819,7 @@ impl<'a> TryFrom<(&'a CharBag, proto::Entry)> for Entry {
             path,
             inode: entry.inode,
             mtime: entry.mtime.map(|time| time.into()),
+            size: entry.size,
             is_symlink: entry.is_symlink,
             is_ignored: entry.is_ignored,
             is_external: entry.is_external,